@@ -1,9 +1,141 @@
+use std::fmt;
+use std::ops::Deref;
+
+/// Source position of a node, taken from the token that starts it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(line: usize, column: usize) -> Self {
+        Span { line, column }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// A literal value, as used in literal types and match patterns
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Number(f64),
+    String(String),
+    Boolean(bool),
+}
+
+impl Literal {
+    /// Returns the primitive type every value of this literal belongs to
+    pub fn base_type(&self) -> Type {
+        match self {
+            Literal::Number(_) => Type::Number,
+            Literal::String(_) => Type::String,
+            Literal::Boolean(_) => Type::Boolean,
+        }
+    }
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Literal::Number(n) => write!(f, "{}", n),
+            Literal::String(s) => write!(f, "\"{}\"", s),
+            Literal::Boolean(b) => write!(f, "{}", b),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Type {
     Number,
     String,
     Boolean,
-    Custom(String), // For user-defined types
+    Custom(String, Vec<Type>), // For user-defined types and their type arguments
+    List(Box<Type>),
+    Object(Vec<(String, Type)>), // Inline record types such as `{ created: String }`
+    Literal(Literal),            // A single value, such as `"dev"`
+    Union(Vec<Type>),            // Any one of several types, such as `"dev" | "prod"`
+}
+
+impl Type {
+    /// Replaces every reference to one of `params` with the matching entry in `args`
+    pub fn substitute(&self, params: &[String], args: &[Type]) -> Type {
+        match self {
+            Type::Custom(name, type_args) if type_args.is_empty() => {
+                match params.iter().position(|param| param == name) {
+                    Some(index) => args.get(index).cloned().unwrap_or_else(|| self.clone()),
+                    None => self.clone(),
+                }
+            }
+            Type::Custom(name, type_args) => Type::Custom(
+                name.clone(),
+                type_args
+                    .iter()
+                    .map(|arg| arg.substitute(params, args))
+                    .collect(),
+            ),
+            Type::List(element) => Type::List(Box::new(element.substitute(params, args))),
+            Type::Object(fields) => Type::Object(
+                fields
+                    .iter()
+                    .map(|(name, field_type)| (name.clone(), field_type.substitute(params, args)))
+                    .collect(),
+            ),
+            Type::Union(members) => Type::Union(
+                members
+                    .iter()
+                    .map(|member| member.substitute(params, args))
+                    .collect(),
+            ),
+            Type::Number | Type::String | Type::Boolean | Type::Literal(_) => self.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Number => write!(f, "Number"),
+            Type::String => write!(f, "String"),
+            Type::Boolean => write!(f, "Boolean"),
+            Type::Custom(name, args) if args.is_empty() => write!(f, "{}", name),
+            Type::Custom(name, args) => {
+                write!(f, "{}<", name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ">")
+            }
+            Type::List(element) => write!(f, "{}[]", element),
+            Type::Object(fields) => {
+                write!(f, "{{ ")?;
+                for (i, (name, field_type)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", name, field_type)?;
+                }
+                write!(f, " }}")
+            }
+            Type::Literal(literal) => write!(f, "{}", literal),
+            Type::Union(members) => {
+                for (i, member) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " | ")?;
+                    }
+                    write!(f, "{}", member)?;
+                }
+                Ok(())
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -12,38 +144,233 @@ pub enum Expression {
     StringLiteral(String),
     BooleanLiteral(bool),
     Identifier(String),
-    Object { fields: Vec<(String, Expression)> },
+    Object {
+        entries: Vec<ObjectEntry>,
+    },
+    List(Vec<Expr>), // `[1, 2, 3]`, whose elements all share a type
+    If {
+        condition: Box<Expr>,
+        then_branch: Box<Expr>,
+        else_branch: Box<Expr>,
+    },
+    Match {
+        scrutinee: Box<Expr>,
+        arms: Vec<MatchArm>,
+    },
+    Template(Vec<Part>), // A string such as `"http://${host}:${port}"`
+    Unary {
+        op: UnaryOp,
+        operand: Box<Expr>,
+    },
+    Binary {
+        op: BinaryOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Negate, // `-`
+}
+
+impl fmt::Display for UnaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnaryOp::Negate => write!(f, "-"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,       // `+`, which also joins strings
+    Subtract,  // `-`
+    Multiply,  // `*`
+    Divide,    // `/`
+    Remainder, // `%`
+}
+
+impl fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinaryOp::Add => write!(f, "+"),
+            BinaryOp::Subtract => write!(f, "-"),
+            BinaryOp::Multiply => write!(f, "*"),
+            BinaryOp::Divide => write!(f, "/"),
+            BinaryOp::Remainder => write!(f, "%"),
+        }
+    }
+}
+
+/// A piece of a template string
+#[derive(Debug, Clone, PartialEq)]
+pub enum Part {
+    Text(String),
+    Expr(Expr), // Formatted and inserted into the string
+}
+
+/// One `pattern | pattern => body` arm of a match expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm {
+    pub patterns: Vec<Pattern>,
+    pub body: Expr,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    Literal(Literal),
+    Wildcard, // `_`
+}
+
+impl Pattern {
+    /// Returns true if this pattern matches every value
+    pub fn is_wildcard(&self) -> bool {
+        matches!(self, Pattern::Wildcard)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjectEntry {
+    /// `name: value`, or the shorthand `name` for `name: name`
+    Field {
+        name: String,
+        value: Expr,
+        span: Span,
+    },
+    /// `...value`, copying every field of another object
+    Spread(Expr),
+}
+
+/// Identifies an expression within a parsed program
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExprId(pub usize);
+
+/// An expression together with the position it starts at
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    pub id: ExprId,
+    pub kind: Expression,
+    pub span: Span,
+}
+
+impl Expr {
+    pub fn new(id: ExprId, kind: Expression, span: Span) -> Self {
+        Expr { id, kind, span }
+    }
+}
+
+impl Expression {
+    /// Returns the value of a literal expression
+    pub fn literal(&self) -> Option<Literal> {
+        match self {
+            Expression::NumberLiteral(n) => Some(Literal::Number(*n)),
+            Expression::StringLiteral(s) => Some(Literal::String(s.clone())),
+            Expression::BooleanLiteral(b) => Some(Literal::Boolean(*b)),
+            _ => None,
+        }
+    }
+}
+
+impl Deref for Expr {
+    type Target = Expression;
+
+    fn deref(&self) -> &Expression {
+        &self.kind
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct TypeField {
     pub name: String,
     pub field_type: Type,
+    pub default: Option<Expr>, // Used when an object literal of this type omits the field
+    pub docs: Option<String>,  // From `///` comments, one line per comment
+    pub span: Span,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct TypeDefinition {
     pub name: String,
+    pub params: Vec<String>,
+    pub extends: Vec<Type>, // Record types whose fields are inherited
     pub fields: Vec<TypeField>,
+    pub alias: Option<Type>, // Set instead of fields for non-record types like `"a" | "b"`
+    pub docs: Option<String>, // From `///` comments, one line per comment
+    pub span: Span,
+}
+
+impl TypeDefinition {
+    /// Returns the fields declared directly on this definition with `args`
+    /// substituted for the type parameters
+    pub fn instantiate(&self, args: &[Type]) -> Vec<TypeField> {
+        self.fields
+            .iter()
+            .map(|field| TypeField {
+                field_type: field.field_type.substitute(&self.params, args),
+                ..field.clone()
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Statement {
     Let {
         name: String,
-        value: Box<Expression>,
+        ty: Option<Type>,
+        value: Expr,
+        span: Span,
     },
     Const {
         name: String,
-        value: Box<Expression>,
+        ty: Option<Type>,
+        value: Expr,
+        span: Span,
     },
     TypeDef(TypeDefinition),
+    /// `name = value` or `name.field = value`
+    Assign {
+        target: Place,
+        value: Expr,
+        span: Span,
+    },
+}
+
+impl Statement {
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::Let { span, .. }
+            | Statement::Const { span, .. }
+            | Statement::Assign { span, .. } => *span,
+            Statement::TypeDef(def) => def.span,
+        }
+    }
+}
+
+/// Something that can be assigned to: a binding, or a field nested in one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Place {
+    pub name: String,
+    pub fields: Vec<String>,
+}
+
+impl fmt::Display for Place {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        for field in &self.fields {
+            write!(f, ".{}", field)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Module {
     pub name: String,
     pub statements: Vec<Statement>,
+    pub span: Span, // The `module` keyword
 }
 
 // Root node of our AST
@@ -59,3 +386,9 @@ impl Program {
         }
     }
 }
+
+impl Default for Program {
+    fn default() -> Self {
+        Self::new()
+    }
+}
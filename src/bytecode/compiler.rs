@@ -0,0 +1,304 @@
+use crate::ast::*;
+use crate::eval::Value;
+use crate::typeck::TypeEnv;
+
+use super::{Bytecode, Chunk, Op};
+
+/// Compiles a program to bytecode. Names are resolved while compiling, so
+/// the virtual machine reads bindings by slot instead of searching for them
+/// by name; a name that is not bound yet compiles to an instruction that
+/// fails, as the interpreter does when it reaches it.
+pub struct Compiler<'a> {
+    program: &'a Program,
+}
+
+impl<'a> Compiler<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        Compiler { program }
+    }
+
+    pub fn compile(&self) -> Bytecode {
+        let chunks = self
+            .program
+            .modules
+            .iter()
+            .map(|module| {
+                let mut compiler = ChunkCompiler {
+                    types: TypeEnv::new(module),
+                    chunk: Chunk {
+                        module: module.name.clone(),
+                        ..Chunk::default()
+                    },
+                };
+                for statement in &module.statements {
+                    compiler.statement(statement);
+                }
+                compiler.chunk
+            })
+            .collect();
+
+        Bytecode { chunks }
+    }
+}
+
+struct ChunkCompiler<'a> {
+    types: TypeEnv<'a>,
+    chunk: Chunk,
+}
+
+impl ChunkCompiler<'_> {
+    fn emit(&mut self, op: Op, span: Span) -> usize {
+        self.chunk.code.push(op);
+        self.chunk.spans.push(span);
+        self.chunk.code.len() - 1
+    }
+
+    /// Points the jump at `at` to the next instruction
+    fn patch(&mut self, at: usize) {
+        let next = self.chunk.code.len() as u32;
+        match &mut self.chunk.code[at] {
+            Op::Jump(target)
+            | Op::JumpIfFalse(target)
+            | Op::JumpIfHas { target, .. }
+            | Op::JumpIfMatches { target, .. } => *target = next,
+            op => unreachable!("{:?} is not a jump", op),
+        }
+    }
+
+    fn constant(&mut self, value: Value) -> u32 {
+        // Numbers are compared by bits so that 0 and -0 stay apart
+        let same = |constant: &Value| match (constant, &value) {
+            (Value::Number(a), Value::Number(b)) => a.to_bits() == b.to_bits(),
+            (constant, value) => constant == value,
+        };
+        let index = match self.chunk.constants.iter().position(same) {
+            Some(index) => index,
+            None => {
+                self.chunk.constants.push(value);
+                self.chunk.constants.len() - 1
+            }
+        };
+        index as u32
+    }
+
+    fn name(&mut self, name: &str) -> u32 {
+        let index = match self.chunk.names.iter().position(|n| n == name) {
+            Some(index) => index,
+            None => {
+                self.chunk.names.push(name.to_string());
+                self.chunk.names.len() - 1
+            }
+        };
+        index as u32
+    }
+
+    /// Returns the slot of the latest binding called `name`
+    fn resolve(&self, name: &str) -> Option<u32> {
+        self.chunk
+            .globals
+            .iter()
+            .rposition(|global| global == name)
+            .map(|slot| slot as u32)
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Let {
+                name,
+                ty,
+                value,
+                span,
+            }
+            | Statement::Const {
+                name,
+                ty,
+                value,
+                span,
+            } => {
+                self.expression(value, ty.as_ref());
+                let slot = self.chunk.globals.len() as u32;
+                self.chunk.globals.push(name.clone());
+                self.emit(Op::DefineGlobal(slot), *span);
+            }
+            Statement::Assign {
+                target,
+                value,
+                span,
+            } => {
+                self.expression(value, None);
+                let Some(slot) = self.resolve(&target.name) else {
+                    let name = self.name(&target.name);
+                    self.emit(Op::Undefined(name), *span);
+                    return;
+                };
+                if target.fields.is_empty() {
+                    self.emit(Op::StoreGlobal(slot), *span);
+                } else {
+                    // Paths are stored whole, so their names may repeat
+                    let path = self.chunk.names.len() as u32;
+                    self.chunk.names.extend(target.fields.iter().cloned());
+                    let len = target.fields.len() as u32;
+                    self.emit(Op::StoreField { slot, path, len }, *span);
+                }
+            }
+            Statement::TypeDef(_) => {}
+        }
+    }
+
+    /// Compiles an expression that leaves its value on the stack; `expected`
+    /// is the type it is annotated with, if any
+    fn expression(&mut self, expr: &Expr, expected: Option<&Type>) {
+        let span = expr.span;
+        match &expr.kind {
+            Expression::NumberLiteral(n) => {
+                let index = self.constant(Value::Number(*n));
+                self.emit(Op::Constant(index), span);
+            }
+            Expression::StringLiteral(s) => {
+                let index = self.constant(Value::String(s.clone()));
+                self.emit(Op::Constant(index), span);
+            }
+            Expression::BooleanLiteral(b) => {
+                let index = self.constant(Value::Bool(*b));
+                self.emit(Op::Constant(index), span);
+            }
+            Expression::Identifier(name) => match self.resolve(name) {
+                Some(slot) => {
+                    self.emit(Op::LoadGlobal(slot), span);
+                }
+                None => {
+                    let name = self.name(name);
+                    self.emit(Op::Undefined(name), span);
+                }
+            },
+            Expression::Object { entries } => self.object(entries, expected, span),
+            Expression::List(elements) => {
+                let element_type = match expected.map(|ty| self.types.expand(ty)) {
+                    Some(Type::List(element_type)) => Some(*element_type),
+                    _ => None,
+                };
+                for element in elements {
+                    self.expression(element, element_type.as_ref());
+                }
+                self.emit(Op::NewList(elements.len() as u32), span);
+            }
+            Expression::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.expression(condition, None);
+                let to_else = self.emit(Op::JumpIfFalse(0), condition.span);
+                self.expression(then_branch, expected);
+                let to_end = self.emit(Op::Jump(0), span);
+                self.patch(to_else);
+                self.expression(else_branch, expected);
+                self.patch(to_end);
+            }
+            Expression::Match { scrutinee, arms } => {
+                self.expression(scrutinee, None);
+
+                // Test every pattern first, then lay out the arm bodies
+                let mut tests: Vec<Vec<usize>> = Vec::new();
+                for arm in arms {
+                    let mut jumps = Vec::new();
+                    for pattern in &arm.patterns {
+                        let jump = match pattern {
+                            Pattern::Wildcard => Op::Jump(0),
+                            Pattern::Literal(literal) => Op::JumpIfMatches {
+                                constant: self.constant(literal_value(literal)),
+                                target: 0,
+                            },
+                        };
+                        jumps.push(self.emit(jump, arm.span));
+                    }
+                    tests.push(jumps);
+                }
+                self.emit(Op::NoMatch, span);
+
+                let mut to_end = Vec::new();
+                for (arm, jumps) in arms.iter().zip(tests) {
+                    for jump in jumps {
+                        self.patch(jump);
+                    }
+                    self.emit(Op::Pop, arm.span);
+                    self.expression(&arm.body, expected);
+                    to_end.push(self.emit(Op::Jump(0), arm.span));
+                }
+                for jump in to_end {
+                    self.patch(jump);
+                }
+            }
+            Expression::Template(parts) => {
+                for part in parts {
+                    match part {
+                        Part::Text(text) => {
+                            let index = self.constant(Value::String(text.clone()));
+                            self.emit(Op::Constant(index), span);
+                        }
+                        Part::Expr(expr) => {
+                            self.expression(expr, None);
+                            self.emit(Op::Format, expr.span);
+                        }
+                    }
+                }
+                self.emit(Op::Concat(parts.len() as u32), span);
+            }
+            Expression::Unary { op, operand } => {
+                self.expression(operand, None);
+                self.emit(Op::Unary(*op), span);
+            }
+            Expression::Binary { op, left, right } => {
+                self.expression(left, None);
+                self.expression(right, None);
+                self.emit(Op::Binary(*op), span);
+            }
+        }
+    }
+
+    /// Builds an object from its entries in order, then fills in the
+    /// defaults of the fields it still lacks
+    fn object(&mut self, entries: &[ObjectEntry], expected: Option<&Type>, span: Span) {
+        let record = expected
+            .and_then(|ty| self.types.record_fields(ty))
+            .unwrap_or_default();
+
+        self.emit(Op::NewObject, span);
+        for entry in entries {
+            match entry {
+                ObjectEntry::Field { name, value, span } => {
+                    let field_type = record
+                        .iter()
+                        .find(|field| field.name == *name)
+                        .map(|field| &field.field_type);
+                    self.expression(value, field_type);
+                    let name = self.name(name);
+                    self.emit(Op::SetField(name), *span);
+                }
+                ObjectEntry::Spread(expr) => {
+                    self.expression(expr, None);
+                    self.emit(Op::Spread, expr.span);
+                }
+            }
+        }
+
+        for field in &record {
+            let Some(default) = &field.default else {
+                continue;
+            };
+            let name = self.name(&field.name);
+            let skip = self.emit(Op::JumpIfHas { name, target: 0 }, field.span);
+            self.expression(default, Some(&field.field_type));
+            self.emit(Op::SetField(name), field.span);
+            self.patch(skip);
+        }
+    }
+}
+
+fn literal_value(literal: &Literal) -> Value {
+    match literal {
+        Literal::Number(n) => Value::Number(*n),
+        Literal::String(s) => Value::String(s.clone()),
+        Literal::Boolean(b) => Value::Bool(*b),
+    }
+}
@@ -0,0 +1,306 @@
+mod compiler;
+mod vm;
+
+pub use compiler::Compiler;
+pub use vm::Vm;
+
+use crate::ast::{BinaryOp, Span, UnaryOp};
+use crate::eval::Value;
+
+/// One instruction of the stack machine. Operands are indices into the
+/// tables of the chunk, or the offsets of jump targets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    /// Pushes an entry of the constant pool
+    Constant(u32),
+    /// Pushes a copy of a binding
+    LoadGlobal(u32),
+    /// Pops a value into the next binding
+    DefineGlobal(u32),
+    /// Pops a value into a binding
+    StoreGlobal(u32),
+    /// Pops a value into a field nested in a binding, following `len` names
+    /// of the name table starting at `path`
+    StoreField {
+        slot: u32,
+        path: u32,
+        len: u32,
+    },
+    /// Fails because the name at this index is not bound at this point
+    Undefined(u32),
+
+    /// Pops this many values and pushes them as a list
+    NewList(u32),
+    /// Pushes an empty object
+    NewObject,
+    /// Pops a value into the named field of the object below it
+    SetField(u32),
+    /// Pops an object and copies its fields into the object below it
+    Spread,
+    /// Jumps if the object on top of the stack has the named field
+    JumpIfHas {
+        name: u32,
+        target: u32,
+    },
+
+    Jump(u32),
+    /// Pops a condition and jumps if it is false
+    JumpIfFalse(u32),
+    /// Jumps if the value on top of the stack equals the constant
+    JumpIfMatches {
+        constant: u32,
+        target: u32,
+    },
+    /// Fails because no arm of a match matches the value on top of the stack
+    NoMatch,
+    Pop,
+
+    /// Formats the value on top of the stack for a template string
+    Format,
+    /// Pops this many strings and pushes them joined
+    Concat(u32),
+    Unary(UnaryOp),
+    Binary(BinaryOp),
+}
+
+/// The compiled form of one module
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Chunk {
+    pub module: String,
+    pub code: Vec<Op>,
+    pub spans: Vec<Span>, // The source position of each instruction
+    pub constants: Vec<Value>,
+    pub names: Vec<String>,   // Field and variable names
+    pub globals: Vec<String>, // The name of each binding, by slot
+}
+
+/// The compiled form of a program, one chunk per module
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bytecode {
+    pub chunks: Vec<Chunk>,
+}
+
+/// Lists the instructions of a chunk, one per line, with the source position
+/// they come from and what their operands refer to:
+///
+/// ```text
+/// == module app ==
+/// 0000    2:19  constant            0  true
+/// 0001    2:11  define_global       0  DEBUG
+/// 0002    3:21  load_global         0  DEBUG
+/// 0003       |  jump_if_false          -> 0006
+/// 0004    3:32  constant            1  3000
+/// 0005    3:18  jump                   -> 0007
+/// 0006    3:42  constant            2  443
+/// 0007    3:11  define_global       1  PORT
+/// ```
+pub fn disassemble(chunk: &Chunk) -> String {
+    let mut out = format!("== module {} ==\n", chunk.module);
+
+    for (offset, op) in chunk.code.iter().enumerate() {
+        let span = chunk.spans[offset];
+        let position = if offset > 0 && chunk.spans[offset - 1] == span {
+            "|".to_string()
+        } else {
+            format!("{}:{}", span.line, span.column)
+        };
+
+        let name = |index: &u32| chunk.names[*index as usize].as_str();
+        let (mnemonic, operands) = match op {
+            Op::Constant(index) => (
+                "constant",
+                format!("{:>5}  {}", index, chunk.constants[*index as usize]),
+            ),
+            Op::LoadGlobal(slot) => (
+                "load_global",
+                format!("{:>5}  {}", slot, chunk.globals[*slot as usize]),
+            ),
+            Op::DefineGlobal(slot) => (
+                "define_global",
+                format!("{:>5}  {}", slot, chunk.globals[*slot as usize]),
+            ),
+            Op::StoreGlobal(slot) => (
+                "store_global",
+                format!("{:>5}  {}", slot, chunk.globals[*slot as usize]),
+            ),
+            Op::StoreField { slot, path, len } => {
+                let fields = &chunk.names[*path as usize..(*path + *len) as usize];
+                (
+                    "store_field",
+                    format!(
+                        "{:>5}  {}.{}",
+                        slot,
+                        chunk.globals[*slot as usize],
+                        fields.join(".")
+                    ),
+                )
+            }
+            Op::Undefined(index) => ("undefined", format!("{:>5}  {}", index, name(index))),
+            Op::NewList(count) => ("new_list", format!("{:>5}", count)),
+            Op::NewObject => ("new_object", String::new()),
+            Op::SetField(index) => ("set_field", format!("{:>5}  {}", index, name(index))),
+            Op::Spread => ("spread", String::new()),
+            Op::JumpIfHas {
+                name: index,
+                target,
+            } => (
+                "jump_if_has",
+                format!("{:>5}  {} -> {:04}", index, name(index), target),
+            ),
+            Op::Jump(target) => ("jump", format!("{:>5}  -> {:04}", "", target)),
+            Op::JumpIfFalse(target) => ("jump_if_false", format!("{:>5}  -> {:04}", "", target)),
+            Op::JumpIfMatches { constant, target } => (
+                "jump_if_matches",
+                format!(
+                    "{:>5}  {} -> {:04}",
+                    constant, chunk.constants[*constant as usize], target
+                ),
+            ),
+            Op::NoMatch => ("no_match", String::new()),
+            Op::Pop => ("pop", String::new()),
+            Op::Format => ("format", String::new()),
+            Op::Concat(count) => ("concat", format!("{:>5}", count)),
+            Op::Unary(op) => ("unary", format!("{:>5}", op.to_string())),
+            Op::Binary(op) => ("binary", format!("{:>5}", op.to_string())),
+        };
+
+        let line = format!(
+            "{:04}  {:>6}  {:<16}{}",
+            offset, position, mnemonic, operands
+        );
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Program;
+    use crate::eval::{EvalError, Globals, Interpreter};
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn parse(source: &str) -> Program {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        Parser::new(tokens).parse().unwrap()
+    }
+
+    /// Runs a program on the virtual machine, checking that the interpreter
+    /// agrees
+    fn run(source: &str) -> Result<Globals, EvalError> {
+        let program = parse(source);
+        let result = Vm::new(&Compiler::new(&program).compile()).run();
+        assert_eq!(result, Interpreter::new(&program).run());
+        result
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let program = parse(
+            "module app {
+    const DEBUG = true
+    const PORT = if DEBUG then 3000 else 443
+}",
+        );
+        let bytecode = Compiler::new(&program).compile();
+
+        assert_eq!(
+            disassemble(&bytecode.chunks[0]),
+            "== module app ==
+0000    2:19  constant            0  true
+0001    2:11  define_global       0  DEBUG
+0002    3:21  load_global         0  DEBUG
+0003       |  jump_if_false          -> 0006
+0004    3:32  constant            1  3000
+0005    3:18  jump                   -> 0007
+0006    3:42  constant            2  443
+0007    3:11  define_global       1  PORT
+"
+        );
+    }
+
+    #[test]
+    fn test_constants_are_shared() {
+        let program = parse("module m {\n  const A = 1\n  const B = 1\n  const C = -0\n}");
+        let chunk = &Compiler::new(&program).compile().chunks[0];
+
+        assert_eq!(
+            chunk.constants,
+            vec![Value::Number(1.0), Value::Number(0.0)]
+        );
+        assert_eq!(chunk.globals, vec!["A", "B", "C"]);
+    }
+
+    #[test]
+    fn test_runs_objects_with_defaults() {
+        let globals = run("module m {
+            type Server => { host: String, port: Number = 8080 }
+            const base = { host: \"a\", tls: false }
+            let server: Server = { ...base, host: \"b\" }
+            server.port = 10
+        }")
+        .unwrap();
+
+        assert_eq!(
+            globals.get("m", "server").unwrap().to_string(),
+            "{ host: \"b\", tls: false, port: 10 }"
+        );
+    }
+
+    #[test]
+    fn test_runs_lists() {
+        let globals = run("module m {
+            type Server => { host: String, port: Number = 8080 }
+            let servers: Server[] = [{ host: \"a\" }, { host: \"b\", port: 80 }]
+            let empty = []
+        }")
+        .unwrap();
+
+        assert_eq!(
+            globals.get("m", "servers").unwrap().to_string(),
+            "[{ host: \"a\", port: 8080 }, { host: \"b\", port: 80 }]"
+        );
+        assert_eq!(globals.get("m", "empty"), Some(&Value::List(vec![])));
+    }
+
+    #[test]
+    fn test_runs_control_flow() {
+        let globals = run("module m {
+            const ENV = \"prod\"
+            const port = match ENV { \"dev\" | \"test\" => 3000, _ => 443 }
+            const tls = match port { 443 => true, _ => false }
+            const url = \"http${if tls then \"s\" else \"\"}://example.com:${port}\"
+        }")
+        .unwrap();
+
+        assert_eq!(globals.get("m", "port"), Some(&Value::Number(443.0)));
+        assert_eq!(
+            globals.get("m", "url"),
+            Some(&Value::String("https://example.com:443".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_fails_like_the_interpreter() {
+        let err = run("module m {\n  const x = match 1 { 2 => 3 }\n}").unwrap_err();
+        assert!(matches!(err, EvalError::NoMatchingArm { .. }));
+
+        let err = run("module m {\n  const x = if 1 then 2 else 3\n}").unwrap_err();
+        assert!(matches!(err, EvalError::InvalidCondition { .. }));
+
+        let err = run("module m {\n  const x = { ...1 }\n}").unwrap_err();
+        assert!(matches!(err, EvalError::InvalidSpread { .. }));
+
+        let err = run("module m {\n  const x = y + 1\n  const y = 2\n}").unwrap_err();
+        assert!(matches!(err, EvalError::UndefinedVariable { .. }));
+
+        let err = run("module m {\n  let x = 1\n  x.port = 2\n}").unwrap_err();
+        assert!(matches!(err, EvalError::InvalidFieldAccess { .. }));
+
+        let err = run("module m {\n  let s = { x: 1 }\n  s.y.z = 2\n}").unwrap_err();
+        assert!(matches!(err, EvalError::UnknownField { field, .. } if field == "y"));
+    }
+}
@@ -0,0 +1,158 @@
+use crate::eval::{EvalError, Globals, Value, assign_field, binary, interpolate, unary};
+
+use super::{Bytecode, Chunk, Op};
+
+/// Runs compiled programs. Produces the same bindings, and fails with the
+/// same errors at the same positions, as the tree-walking interpreter.
+pub struct Vm<'a> {
+    bytecode: &'a Bytecode,
+}
+
+impl<'a> Vm<'a> {
+    pub fn new(bytecode: &'a Bytecode) -> Self {
+        Vm { bytecode }
+    }
+
+    /// Runs every chunk in order, stopping at the first runtime error
+    pub fn run(&self) -> Result<Globals, EvalError> {
+        let mut globals = Globals::default();
+        for chunk in &self.bytecode.chunks {
+            let slots = run_chunk(chunk)?;
+            let bindings = chunk.globals.iter().cloned().zip(slots).collect();
+            globals.push(chunk.module.clone(), bindings);
+        }
+        Ok(globals)
+    }
+}
+
+fn run_chunk(chunk: &Chunk) -> Result<Vec<Value>, EvalError> {
+    let mut slots: Vec<Value> = Vec::with_capacity(chunk.globals.len());
+    let mut stack: Vec<Value> = Vec::new();
+    let mut pc = 0;
+
+    // The compiler only emits balanced code, so an empty stack is a bug
+    let pop = |stack: &mut Vec<Value>| stack.pop().expect("stack underflow");
+
+    while let Some(op) = chunk.code.get(pc) {
+        let span = chunk.spans[pc];
+        pc += 1;
+
+        match *op {
+            Op::Constant(index) => stack.push(chunk.constants[index as usize].clone()),
+            Op::LoadGlobal(slot) => stack.push(slots[slot as usize].clone()),
+            Op::DefineGlobal(slot) => {
+                debug_assert_eq!(slot as usize, slots.len());
+                slots.push(pop(&mut stack));
+            }
+            Op::StoreGlobal(slot) => slots[slot as usize] = pop(&mut stack),
+            Op::StoreField { slot, path, len } => {
+                let fields = &chunk.names[path as usize..(path + len) as usize];
+                assign_field(&mut slots[slot as usize], fields, pop(&mut stack), span)?;
+            }
+            Op::Undefined(index) => {
+                return Err(EvalError::UndefinedVariable {
+                    name: chunk.names[index as usize].clone(),
+                    span,
+                });
+            }
+
+            Op::NewList(count) => {
+                let elements = stack.split_off(stack.len() - count as usize);
+                stack.push(Value::List(elements));
+            }
+            Op::NewObject => stack.push(Value::Object(Vec::new())),
+            Op::SetField(index) => {
+                let value = pop(&mut stack);
+                set_field(&mut stack, &chunk.names[index as usize], value);
+            }
+            Op::Spread => match pop(&mut stack) {
+                Value::Object(fields) => {
+                    for (name, value) in fields {
+                        set_field(&mut stack, &name, value);
+                    }
+                }
+                other => {
+                    return Err(EvalError::InvalidSpread {
+                        found: other.type_name(),
+                        span,
+                    });
+                }
+            },
+            Op::JumpIfHas { name, target } => {
+                if stack
+                    .last()
+                    .and_then(|object| object.get(&chunk.names[name as usize]))
+                    .is_some()
+                {
+                    pc = target as usize;
+                }
+            }
+
+            Op::Jump(target) => pc = target as usize,
+            Op::JumpIfFalse(target) => match pop(&mut stack) {
+                Value::Bool(true) => {}
+                Value::Bool(false) => pc = target as usize,
+                other => {
+                    return Err(EvalError::InvalidCondition {
+                        found: other.type_name(),
+                        span,
+                    });
+                }
+            },
+            Op::JumpIfMatches { constant, target } => {
+                // Patterns are scalars, so this is the equality `matches` uses
+                if stack.last() == Some(&chunk.constants[constant as usize]) {
+                    pc = target as usize;
+                }
+            }
+            Op::NoMatch => {
+                return Err(EvalError::NoMatchingArm {
+                    value: pop(&mut stack).to_string(),
+                    span,
+                });
+            }
+            Op::Pop => {
+                pop(&mut stack);
+            }
+
+            Op::Format => {
+                let text = interpolate(pop(&mut stack), span)?;
+                stack.push(Value::String(text));
+            }
+            Op::Concat(count) => {
+                let parts = stack.split_off(stack.len() - count as usize);
+                let mut text = String::new();
+                for part in parts {
+                    match part {
+                        Value::String(s) => text.push_str(&s),
+                        other => unreachable!("{} in a template", other.type_name()),
+                    }
+                }
+                stack.push(Value::String(text));
+            }
+            Op::Unary(op) => {
+                let operand = pop(&mut stack);
+                stack.push(unary(op, operand, span)?);
+            }
+            Op::Binary(op) => {
+                let right = pop(&mut stack);
+                let left = pop(&mut stack);
+                stack.push(binary(op, left, right, span)?);
+            }
+        }
+    }
+
+    Ok(slots)
+}
+
+/// Sets a field of the object on top of the stack. A field it already has
+/// keeps its position.
+fn set_field(stack: &mut [Value], name: &str, value: Value) {
+    let Some(Value::Object(fields)) = stack.last_mut() else {
+        unreachable!("fields are only set on objects");
+    };
+    match fields.iter_mut().find(|(field, _)| field == name) {
+        Some((_, existing)) => *existing = value,
+        None => fields.push((name.to_string(), value)),
+    }
+}
@@ -0,0 +1,39 @@
+use std::fmt;
+
+/// Represents names and values a target language has no way to express
+#[derive(Debug, Clone, PartialEq)]
+pub enum CodegenError {
+    /// A module, type or binding is named after a word the target reserves
+    ReservedWord { name: String, target: &'static str },
+}
+
+impl std::error::Error for CodegenError {}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodegenError::ReservedWord { name, target } => write!(
+                f,
+                "'{}' is a reserved word in {} and cannot name a declaration",
+                name, target
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_display() {
+        let err = CodegenError::ReservedWord {
+            name: "delete".to_string(),
+            target: "TypeScript",
+        };
+        assert_eq!(
+            err.to_string(),
+            "'delete' is a reserved word in TypeScript and cannot name a declaration"
+        );
+    }
+}
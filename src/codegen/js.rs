@@ -0,0 +1,718 @@
+use std::path::Path;
+
+use crate::ast::*;
+use crate::json::{JsonValue, write_json};
+use crate::typeck::TypeEnv;
+
+use super::sourcemap::SourceMap;
+use super::{CodegenError, INDENT, JS_RESERVED, OutputFile};
+
+/// Functions the generated code calls to keep jlang's semantics where
+/// JavaScript's differ: arithmetic reports division by zero and overflow
+/// instead of producing infinities, a match without a matching arm fails,
+/// fields left out of an object that spreads another only take their defaults
+/// if the spread did not supply them, and templates write numbers in full
+/// rather than with an exponent. Each module defines the ones it uses.
+const HELPERS: &[(&str, &str)] = &[
+    (
+        "$add",
+        "function $add(a, b) {\n    return typeof a === \"string\" ? a + b : $finite(a + b);\n}",
+    ),
+    (
+        "$subtract",
+        "function $subtract(a, b) {\n    return $finite(a - b);\n}",
+    ),
+    (
+        "$multiply",
+        "function $multiply(a, b) {\n    return $finite(a * b);\n}",
+    ),
+    (
+        "$divide",
+        "function $divide(a, b) {\n    return $finite(a / $divisor(b));\n}",
+    ),
+    (
+        "$remainder",
+        "function $remainder(a, b) {\n    return $finite(a % $divisor(b));\n}",
+    ),
+    (
+        "$divisor",
+        "function $divisor(b) {\n    if (b === 0) throw new RangeError(\"Division by zero\");\n    return b;\n}",
+    ),
+    (
+        "$finite",
+        "function $finite(n) {\n    if (!Number.isFinite(n)) throw new RangeError(\"Arithmetic overflow\");\n    return n;\n}",
+    ),
+    (
+        "$defaults",
+        "function $defaults(object, defaults) {\n    for (const [field, value] of Object.entries(defaults)) {\n        if (!Object.hasOwn(object, field)) object[field] = value();\n    }\n    return object;\n}",
+    ),
+    (
+        "$format",
+        "function $format(value) {\n    if (typeof value !== \"number\") return String(value);\n    if (Object.is(value, -0)) return \"-0\";\n    const text = String(value);\n    const e = text.indexOf(\"e\");\n    if (e < 0) return text;\n    const sign = value < 0 ? \"-\" : \"\";\n    const [whole, fraction = \"\"] = text.slice(sign.length, e).split(\".\");\n    const digits = whole + fraction;\n    const point = whole.length + Number(text.slice(e + 1));\n    if (point <= 0) return sign + \"0.\" + \"0\".repeat(-point) + digits;\n    if (point >= digits.length) return sign + digits + \"0\".repeat(point - digits.length);\n    return sign + digits.slice(0, point) + \".\" + digits.slice(point);\n}",
+    ),
+    (
+        "$noMatch",
+        "function $noMatch(value) {\n    throw new Error(\"No match arm matches \" + JSON.stringify(value));\n}",
+    ),
+];
+
+/// The helpers each helper calls
+fn helper_dependencies(helper: &str) -> &'static [&'static str] {
+    match helper {
+        "$add" | "$subtract" | "$multiply" => &["$finite"],
+        "$divide" | "$remainder" => &["$divisor", "$finite"],
+        _ => &[],
+    }
+}
+
+/// Compiles a program read from `filename` to JavaScript, producing an ES
+/// module and a source map per jlang module. Every `let` and `const` becomes
+/// an exported binding of the same kind, statements keep their order, and
+/// type definitions, which have no runtime form, are left out. Field defaults
+/// are written into the object literals that need them.
+///
+/// jlang copies objects where JavaScript shares them, which only shows once
+/// a field is assigned to. In modules that assign to fields, names are read
+/// through `structuredClone` wherever their value is stored.
+pub fn to_javascript(
+    program: &Program,
+    filename: &str,
+    source: &str,
+) -> Result<Vec<OutputFile>, CodegenError> {
+    let source_name = Path::new(filename)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(filename);
+
+    let mut files = Vec::new();
+    for module in &program.modules {
+        let file = format!("{}.js", module.name);
+        let map_file = format!("{}.js.map", module.name);
+
+        let mut writer = Writer::new(module, source);
+        writer.push(&format!(
+            "// Generated by jlang from {}. Do not edit.\n\n",
+            source_name
+        ));
+        writer.module()?;
+        writer.push(&format!("//# sourceMappingURL={}\n", map_file));
+
+        files.push(OutputFile {
+            name: file.clone(),
+            contents: writer.out,
+        });
+        files.push(OutputFile {
+            name: map_file,
+            contents: writer.map.to_json(&file, source_name, source),
+        });
+    }
+    Ok(files)
+}
+
+struct Writer<'a> {
+    module: &'a Module,
+    types: TypeEnv<'a>,
+    source_lines: Vec<&'a str>,
+    out: String,
+    line: usize,   // Zero-based line the end of `out` is on
+    column: usize, // UTF-16 code units since the start of that line
+    depth: usize,
+    map: SourceMap,
+    helpers: Vec<&'static str>,
+    clone_reads: bool,
+}
+
+impl<'a> Writer<'a> {
+    fn new(module: &'a Module, source: &'a str) -> Self {
+        let clone_reads = module.statements.iter().any(|statement| {
+            matches!(statement, Statement::Assign { target, .. } if !target.fields.is_empty())
+        });
+
+        Writer {
+            module,
+            types: TypeEnv::new(module),
+            source_lines: source.lines().collect(),
+            out: String::new(),
+            line: 0,
+            column: 0,
+            depth: 0,
+            map: SourceMap::new(),
+            helpers: Vec::new(),
+            clone_reads,
+        }
+    }
+
+    fn push(&mut self, text: &str) {
+        for c in text.chars() {
+            if c == '\n' {
+                self.line += 1;
+                self.column = 0;
+            } else {
+                self.column += c.len_utf16();
+            }
+        }
+        self.out.push_str(text);
+    }
+
+    fn newline(&mut self) {
+        self.push("\n");
+        self.push(&INDENT.repeat(self.depth));
+    }
+
+    /// Maps the current position to `span`, converting its character column
+    /// to UTF-16 code units
+    fn mark(&mut self, span: Span) {
+        let line = span.line.saturating_sub(1);
+        let column = self
+            .source_lines
+            .get(line)
+            .map(|text| {
+                text.chars()
+                    .take(span.column.saturating_sub(1))
+                    .map(char::len_utf16)
+                    .sum()
+            })
+            .unwrap_or(0);
+        self.map.add((self.line, self.column), (line, column));
+    }
+
+    fn use_helper(&mut self, helper: &'static str) {
+        if !self.helpers.contains(&helper) {
+            self.helpers.push(helper);
+            for dependency in helper_dependencies(helper) {
+                self.use_helper(dependency);
+            }
+        }
+    }
+
+    fn module(&mut self) -> Result<(), CodegenError> {
+        let module = self.module;
+        for statement in &module.statements {
+            self.statement(statement)?;
+        }
+
+        // Without an export the file would not be recognized as a module
+        let exports = module
+            .statements
+            .iter()
+            .any(|statement| matches!(statement, Statement::Let { .. } | Statement::Const { .. }));
+        if !exports {
+            self.push("export {};\n");
+        }
+
+        // Function declarations are hoisted, so helpers can come last
+        for (name, helper) in HELPERS {
+            if self.helpers.contains(name) {
+                self.push("\n");
+                self.push(helper);
+                self.push("\n");
+            }
+        }
+        Ok(())
+    }
+
+    fn statement(&mut self, statement: &Statement) -> Result<(), CodegenError> {
+        match statement {
+            Statement::Let {
+                name,
+                ty,
+                value,
+                span,
+            }
+            | Statement::Const {
+                name,
+                ty,
+                value,
+                span,
+            } => {
+                if JS_RESERVED.contains(&name.as_str()) {
+                    return Err(CodegenError::ReservedWord {
+                        name: name.clone(),
+                        target: "JavaScript",
+                    });
+                }
+                let keyword = match statement {
+                    Statement::Let { .. } => "let",
+                    _ => "const",
+                };
+                self.mark(*span);
+                self.push(&format!("export {} {} = ", keyword, name));
+                self.expression(value, ty.as_ref(), true);
+                self.push(";\n");
+            }
+            Statement::Assign {
+                target,
+                value,
+                span,
+            } => {
+                self.mark(*span);
+                self.push(&target.to_string());
+                self.push(" = ");
+                self.expression(value, None, true);
+                self.push(";\n");
+            }
+            Statement::TypeDef(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Writes an expression, where `expected` is the type it is annotated
+    /// with, if any, and `stored` is set when its value is kept somewhere
+    /// that could later be changed
+    fn expression(&mut self, expr: &Expr, expected: Option<&Type>, stored: bool) {
+        self.mark(expr.span);
+        match &expr.kind {
+            Expression::NumberLiteral(n) => self.push(&n.to_string()),
+            Expression::StringLiteral(s) => self.push(&string(s)),
+            Expression::BooleanLiteral(b) => self.push(&b.to_string()),
+            Expression::Identifier(name) if stored && self.clone_reads => {
+                self.push(&format!("structuredClone({})", name))
+            }
+            Expression::Identifier(name) => self.push(name),
+            Expression::Object { entries } => self.object(entries, expected, stored),
+            Expression::List(elements) => {
+                let element_type = match expected.map(|ty| self.types.expand(ty)) {
+                    Some(Type::List(element_type)) => Some(*element_type),
+                    _ => None,
+                };
+                self.push("[");
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        self.push(", ");
+                    }
+                    self.expression(element, element_type.as_ref(), stored);
+                }
+                self.push("]");
+            }
+            Expression::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.operand(condition);
+                self.push(" ? ");
+                self.expression(then_branch, expected, stored);
+                self.push(" : ");
+                self.expression(else_branch, expected, stored);
+            }
+            Expression::Match { scrutinee, arms } => {
+                self.push("(($value) => ");
+                let mut exhaustive = false;
+                for arm in arms {
+                    if arm.patterns.iter().any(Pattern::is_wildcard) {
+                        self.expression(&arm.body, expected, stored);
+                        exhaustive = true;
+                        break;
+                    }
+                    let tests: Vec<String> = arm
+                        .patterns
+                        .iter()
+                        .filter_map(|pattern| match pattern {
+                            Pattern::Literal(literal) => {
+                                Some(format!("$value === {}", literal_value(literal)))
+                            }
+                            Pattern::Wildcard => None,
+                        })
+                        .collect();
+                    self.mark(arm.span);
+                    self.push(&tests.join(" || "));
+                    self.push(" ? ");
+                    self.expression(&arm.body, expected, stored);
+                    self.push(" : ");
+                }
+                if !exhaustive {
+                    self.use_helper("$noMatch");
+                    self.push("$noMatch($value)");
+                }
+                self.push(")(");
+                self.expression(scrutinee, None, false);
+                self.push(")");
+            }
+            Expression::Template(parts) => {
+                self.push("`");
+                for part in parts {
+                    match part {
+                        Part::Text(text) => self.push(
+                            &text
+                                .replace('\\', "\\\\")
+                                .replace('`', "\\`")
+                                .replace("${", "\\${"),
+                        ),
+                        // Strings need no formatting; numbers are written
+                        // the way the interpreter writes them
+                        Part::Expr(expr) if is_string(expr) => {
+                            self.push("${");
+                            self.expression(expr, None, false);
+                            self.push("}");
+                        }
+                        Part::Expr(expr) => {
+                            self.use_helper("$format");
+                            self.push("${$format(");
+                            self.expression(expr, None, false);
+                            self.push(")}");
+                        }
+                    }
+                }
+                self.push("`");
+            }
+            Expression::Unary {
+                op: UnaryOp::Negate,
+                operand,
+            } => {
+                self.push("-");
+                self.operand(operand);
+            }
+            Expression::Binary { op, left, right } => {
+                let helper = match op {
+                    BinaryOp::Add => "$add",
+                    BinaryOp::Subtract => "$subtract",
+                    BinaryOp::Multiply => "$multiply",
+                    BinaryOp::Divide => "$divide",
+                    BinaryOp::Remainder => "$remainder",
+                };
+                self.use_helper(helper);
+                self.push(helper);
+                self.push("(");
+                self.expression(left, None, false);
+                self.push(", ");
+                self.expression(right, None, false);
+                self.push(")");
+            }
+        }
+    }
+
+    /// Writes an operand of `?:` or unary minus, parenthesized if it would
+    /// otherwise bind to the wrong operator
+    fn operand(&mut self, expr: &Expr) {
+        if matches!(expr.kind, Expression::If { .. } | Expression::Unary { .. }) {
+            self.push("(");
+            self.expression(expr, None, false);
+            self.push(")");
+        } else {
+            self.expression(expr, None, false);
+        }
+    }
+
+    fn object(&mut self, entries: &[ObjectEntry], expected: Option<&Type>, stored: bool) {
+        let record = expected
+            .and_then(|ty| self.types.record_fields(ty))
+            .unwrap_or_default();
+        let defaults: Vec<TypeField> = record
+            .into_iter()
+            .filter(|field| {
+                field.default.is_some()
+                    && !entries.iter().any(|entry| {
+                        matches!(entry, ObjectEntry::Field { name, .. } if *name == field.name)
+                    })
+            })
+            .collect();
+        let spreads = entries
+            .iter()
+            .any(|entry| matches!(entry, ObjectEntry::Spread(_)));
+
+        if entries.is_empty() && defaults.is_empty() {
+            self.push("{}");
+            return;
+        }
+
+        // A spread may supply a defaulted field, so check for it at runtime
+        let checked = spreads && !defaults.is_empty();
+        if checked {
+            self.use_helper("$defaults");
+            self.push("$defaults(");
+        }
+
+        self.push("{");
+        self.depth += 1;
+        for entry in entries {
+            self.newline();
+            match entry {
+                ObjectEntry::Field { name, value, span } => {
+                    let field_type = expected
+                        .and_then(|ty| self.types.record_fields(ty))
+                        .and_then(|fields| fields.into_iter().find(|field| field.name == *name))
+                        .map(|field| field.field_type);
+                    self.mark(*span);
+                    self.push(&format!("{}: ", name));
+                    self.expression(value, field_type.as_ref(), stored);
+                }
+                ObjectEntry::Spread(expr) => {
+                    self.push("...");
+                    self.expression(expr, None, stored);
+                }
+            }
+            self.push(",");
+        }
+        if !checked {
+            self.defaults(&defaults, stored, false);
+        }
+        self.depth -= 1;
+        self.newline();
+        self.push("}");
+
+        if checked {
+            self.push(", {");
+            self.depth += 1;
+            self.defaults(&defaults, stored, true);
+            self.depth -= 1;
+            self.newline();
+            self.push("})");
+        }
+    }
+
+    /// Writes defaulted fields, as functions when they are only evaluated if
+    /// the object turns out to lack them
+    fn defaults(&mut self, defaults: &[TypeField], stored: bool, lazy: bool) {
+        for field in defaults {
+            let Some(default) = &field.default else {
+                continue;
+            };
+            self.newline();
+            self.mark(field.span);
+            self.push(&format!("{}: ", field.name));
+            // A brace right after `=>` would open a block
+            let wrap = lazy && matches!(default.kind, Expression::Object { .. });
+            if lazy {
+                self.push(if wrap { "() => (" } else { "() => " });
+            }
+            self.expression(default, Some(&field.field_type), stored);
+            self.push(if wrap { ")," } else { "," });
+        }
+    }
+}
+
+fn literal_value(literal: &Literal) -> String {
+    match literal {
+        Literal::Number(n) => n.to_string(),
+        Literal::String(s) => string(s),
+        Literal::Boolean(b) => b.to_string(),
+    }
+}
+
+fn is_string(expr: &Expr) -> bool {
+    matches!(
+        expr.kind,
+        Expression::StringLiteral(_) | Expression::Template(_)
+    )
+}
+
+/// Quotes a string; JSON string syntax is valid JavaScript
+fn string(s: &str) -> String {
+    write_json(&JsonValue::String(s.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn build(source: &str) -> Vec<OutputFile> {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        to_javascript(&program, "src/app.j", source).unwrap()
+    }
+
+    #[test]
+    fn test_es_modules() {
+        let files = build(
+            "module app {
+    type Server => { host: String, port: Number = 80, tags: { a: Number } = { a: 1 } }
+    const base = { host: \"example.com\" }
+    const server: Server = { ...base, port: 8000 + 443 }
+    let local: Server = { host: \"localhost\" }
+    local.port = 9
+    const level = match 2 { 1 | 2 => \"low\" }
+    const url = \"`${base}` \\ \\${x} ${-(1 / 2)}\"
+}
+module empty {
+}",
+        );
+        let names: Vec<&str> = files.iter().map(|file| file.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["app.js", "app.js.map", "empty.js", "empty.js.map"]
+        );
+
+        assert_eq!(
+            files[0].contents,
+            r#"// Generated by jlang from app.j. Do not edit.
+
+export const base = {
+    host: "example.com",
+};
+export const server = $defaults({
+    ...structuredClone(base),
+    port: $add(8000, 443),
+}, {
+    tags: () => ({
+        a: 1,
+    }),
+});
+export let local = {
+    host: "localhost",
+    port: 80,
+    tags: {
+        a: 1,
+    },
+};
+local.port = 9;
+export const level = (($value) => $value === 1 || $value === 2 ? "low" : $noMatch($value))(2);
+export const url = `\`${$format(base)}\` \\ \${x} ${$format(-$divide(1, 2))}`;
+
+function $add(a, b) {
+    return typeof a === "string" ? a + b : $finite(a + b);
+}
+
+function $divide(a, b) {
+    return $finite(a / $divisor(b));
+}
+
+function $divisor(b) {
+    if (b === 0) throw new RangeError("Division by zero");
+    return b;
+}
+
+function $finite(n) {
+    if (!Number.isFinite(n)) throw new RangeError("Arithmetic overflow");
+    return n;
+}
+
+function $defaults(object, defaults) {
+    for (const [field, value] of Object.entries(defaults)) {
+        if (!Object.hasOwn(object, field)) object[field] = value();
+    }
+    return object;
+}
+
+function $format(value) {
+    if (typeof value !== "number") return String(value);
+    if (Object.is(value, -0)) return "-0";
+    const text = String(value);
+    const e = text.indexOf("e");
+    if (e < 0) return text;
+    const sign = value < 0 ? "-" : "";
+    const [whole, fraction = ""] = text.slice(sign.length, e).split(".");
+    const digits = whole + fraction;
+    const point = whole.length + Number(text.slice(e + 1));
+    if (point <= 0) return sign + "0." + "0".repeat(-point) + digits;
+    if (point >= digits.length) return sign + digits + "0".repeat(point - digits.length);
+    return sign + digits.slice(0, point) + "." + digits.slice(point);
+}
+
+function $noMatch(value) {
+    throw new Error("No match arm matches " + JSON.stringify(value));
+}
+//# sourceMappingURL=app.js.map
+"#
+        );
+        assert_eq!(
+            files[2].contents,
+            "// Generated by jlang from app.j. Do not edit.\n\nexport {};\n//# sourceMappingURL=empty.js.map\n"
+        );
+    }
+
+    #[test]
+    fn test_lists() {
+        let files = build(
+            "module m {
+    type Server => { host: String, port: Number = 80 }
+    const servers: Server[] = [{ host: \"a\" }, { host: \"b\", port: 8080 }]
+    let grid = [[1, 2], []]
+}",
+        );
+        assert!(files[0].contents.contains(
+            r#"export const servers = [{
+    host: "a",
+    port: 80,
+}, {
+    host: "b",
+    port: 8080,
+}];
+export let grid = [[1, 2], []];
+"#
+        ));
+    }
+
+    #[test]
+    fn test_source_map() {
+        let files = build("module m {\n    const a = 1\n    let b = a\n}\n");
+        assert!(files[1].contents.contains("\"file\": \"m.js\""));
+        assert!(
+            files[1]
+                .contents
+                .contains("\"sources\": [\n    \"app.j\"\n  ]")
+        );
+        // Each binding maps to its name, and each value to its initializer
+        assert!(
+            files[1]
+                .contents
+                .contains("\"mappings\": \";;AACU,iBAAI;AACN,eAAI\"")
+        );
+
+        // Columns in the source count UTF-16 code units
+        let module = Module {
+            name: "m".to_string(),
+            statements: Vec::new(),
+            span: Span::new(1, 1),
+        };
+        let mut writer = Writer::new(&module, "a\n🦀🦀b");
+        writer.push("ab");
+        writer.mark(Span::new(2, 3));
+        assert_eq!(writer.map.mappings(), "EACI");
+    }
+
+    #[test]
+    fn test_reserved_words() {
+        let tokens = Lexer::new("module m { const eval = 1 }")
+            .tokenize()
+            .unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        assert_eq!(
+            to_javascript(&program, "m.j", ""),
+            Err(CodegenError::ReservedWord {
+                name: "eval".to_string(),
+                target: "JavaScript",
+            })
+        );
+    }
+
+    #[test]
+    fn test_templates_format_numbers_like_the_interpreter() {
+        let files = build("module m {\n    const n = 10\n    const s = \"${n}${\"!\"}\"\n}\n");
+        assert!(
+            files[0]
+                .contents
+                .contains("export const s = `${$format(n)}${\"!\"}`;")
+        );
+        assert!(files[0].contents.contains("function $format(value) {"));
+
+        // JavaScript switches to exponents for large and small numbers, while
+        // the interpreter always writes them in full
+        let numbers = [1e21, -2.5e-8, 1e-7, 0.1 + 0.2, 150.0, -0.0, 1.5e300, 5e-324];
+        let (_, helper) = HELPERS.iter().find(|(name, _)| *name == "$format").unwrap();
+        let script = format!(
+            "{}\nfor (const n of [{}]) console.log($format(n));",
+            helper,
+            numbers.map(|n| format!("{:e}", n)).join(", ")
+        );
+        // Only checked where Node.js is installed
+        let Ok(output) = std::process::Command::new("node")
+            .args(["-e", &script])
+            .output()
+        else {
+            return;
+        };
+        let expected: Vec<String> = numbers
+            .iter()
+            .map(|n| crate::eval::Value::Number(*n).to_string())
+            .collect();
+        assert_eq!(
+            String::from_utf8(output.stdout)
+                .unwrap()
+                .lines()
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+}
@@ -0,0 +1,129 @@
+mod error;
+mod js;
+mod rust;
+mod sourcemap;
+mod typescript;
+
+pub use error::CodegenError;
+pub use js::to_javascript;
+pub use rust::to_rust;
+pub use sourcemap::SourceMap;
+pub use typescript::to_typescript;
+
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::ast::Program;
+
+const INDENT: &str = "    ";
+
+/// Words that cannot name a binding in a JavaScript module, which is strict
+/// mode code, and so cannot name a declaration in TypeScript either
+const JS_RESERVED: &[&str] = &[
+    "arguments",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "eval",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+/// A file written by a backend, named relative to the output directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputFile {
+    pub name: String,
+    pub contents: String,
+}
+
+/// The languages programs can be compiled to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    JavaScript,
+    Rust,
+    TypeScript,
+}
+
+impl Target {
+    /// Generates the files for `program`, which was parsed from `source` in
+    /// the file `filename`. Single-file targets name their output after it,
+    /// or `out` if it is not a file name, as with `<stdin>`.
+    pub fn generate(
+        self,
+        program: &Program,
+        filename: &str,
+        source: &str,
+    ) -> Result<Vec<OutputFile>, CodegenError> {
+        let stem = Path::new(filename)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .filter(|stem| !stem.starts_with('<'))
+            .unwrap_or("out");
+
+        Ok(match self {
+            Target::JavaScript => to_javascript(program, filename, source)?,
+            Target::Rust => vec![OutputFile {
+                name: format!("{}.rs", stem),
+                contents: to_rust(program)?,
+            }],
+            Target::TypeScript => vec![OutputFile {
+                name: format!("{}.d.ts", stem),
+                contents: to_typescript(program)?,
+            }],
+        })
+    }
+}
+
+impl FromStr for Target {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "js" | "javascript" => Ok(Target::JavaScript),
+            "rs" | "rust" => Ok(Target::Rust),
+            "ts" | "typescript" => Ok(Target::TypeScript),
+            _ => Err(format!("Unknown target '{}'; expected js, rs or ts", s)),
+        }
+    }
+}
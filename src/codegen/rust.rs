@@ -0,0 +1,916 @@
+use std::collections::HashSet;
+
+use crate::ast::*;
+use crate::consteval::ConstEvaluator;
+use crate::eval::Value;
+use crate::json::JsonValue;
+use crate::typeck::TypeEnv;
+use crate::validate::Validator;
+
+use super::{CodegenError, INDENT};
+
+/// Keywords that can still name an item when written as raw identifiers
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// Keywords that cannot be raw identifiers, and so cannot name anything
+const RESERVED: &[&str] = &["Self", "crate", "self", "super", "_"];
+
+/// Generates Rust source for a program, for use from a `build.rs` script.
+/// Each module becomes a `pub mod`:
+///
+/// - Record types become structs, with inherited fields flattened in
+/// - Unions become enums, with a unit variant per literal member and a tuple
+///   variant per other member
+/// - Inline object and union types become items named after their field,
+///   such as `UserSettings` for the `settings` field of `User`
+/// - Scalar consts become `pub const`s and object consts become statics,
+///   built on first use when they hold strings
+///
+/// Names are kept as written so they match the source and any serialized
+/// data, so the lints about naming conventions are allowed.
+pub fn to_rust(program: &Program) -> Result<String, CodegenError> {
+    let (values, _) = ConstEvaluator::new(program).evaluate();
+    let mut out = String::from("// Generated by jlang. Do not edit.\n");
+
+    for (index, module) in program.modules.iter().enumerate() {
+        let items = ModuleGenerator::new(module).generate(values.module(index))?;
+
+        out.push_str(
+            "\n#[allow(dead_code, non_camel_case_types, non_snake_case, non_upper_case_globals)]\n",
+        );
+        out.push_str(&format!("pub mod {} {{\n", ident(&module.name)?));
+
+        let mut previous: Option<&(String, bool)> = None;
+        for item in &items {
+            let (text, is_type) = item;
+            if let Some((previous, was_type)) = previous
+                && (previous.contains('\n') || text.contains('\n') || *was_type || *is_type)
+            {
+                out.push('\n');
+            }
+            for line in text.lines() {
+                out.push_str(INDENT);
+                out.push_str(line);
+                out.push('\n');
+            }
+            previous = Some(item);
+        }
+
+        out.push_str("}\n");
+    }
+
+    Ok(out)
+}
+
+/// The type definition an item is generated for, or whose value is being
+/// written with `args` for its parameters. Fields that refer back to it are
+/// boxed, since a Rust type cannot contain itself.
+#[derive(Clone, Copy)]
+struct Owner<'b> {
+    name: &'b str,
+    params: &'b [String],
+    args: &'b [Type],
+}
+
+const NO_OWNER: Owner<'static> = Owner {
+    name: "",
+    params: &[],
+    args: &[],
+};
+
+struct ModuleGenerator<'a> {
+    module: &'a Module,
+    types: TypeEnv<'a>,
+    validator: Validator<'a>,
+    items: Vec<(String, bool)>,  // Source and whether it declares a type
+    names: HashSet<String>,      // Item names in use
+    inline: Vec<(Type, String)>, // Items generated for inline types
+}
+
+impl<'a> ModuleGenerator<'a> {
+    fn new(module: &'a Module) -> Self {
+        let names = module
+            .statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::TypeDef(def) => Some(def.name.clone()),
+                _ => None,
+            })
+            .collect();
+
+        ModuleGenerator {
+            module,
+            types: TypeEnv::new(module),
+            validator: Validator::new(module),
+            items: Vec::new(),
+            names,
+            inline: Vec::new(),
+        }
+    }
+
+    /// Returns the items of the module: types in declaration order, each
+    /// followed by the items for its inline types, then the consts
+    fn generate(mut self, values: &[(String, Value)]) -> Result<Vec<(String, bool)>, CodegenError> {
+        for statement in &self.module.statements {
+            if let Statement::TypeDef(def) = statement {
+                self.type_definition(def)?;
+            }
+        }
+
+        // Consts are collected apart since they can add inline types too
+        let mut consts = Vec::new();
+        for statement in &self.module.statements {
+            if let Statement::Const { name, ty, .. } = statement
+                && let Some((_, value)) = values.iter().find(|(constant, _)| constant == name)
+            {
+                consts.push(self.constant(name, ty.as_ref(), value)?);
+            }
+        }
+
+        self.items
+            .extend(consts.into_iter().map(|text| (text, false)));
+        Ok(self.items)
+    }
+
+    fn type_definition(&mut self, def: &TypeDefinition) -> Result<(), CodegenError> {
+        let name = ident(&def.name)?;
+        let owner = Owner {
+            name: &def.name,
+            params: &def.params,
+            args: &[],
+        };
+        let params = generics(&def.params);
+
+        let item = match &def.alias {
+            Some(Type::Union(members)) => {
+                self.enum_item(&name, &params, members, def.docs.as_deref(), owner)?
+            }
+            Some(Type::Object(fields)) => {
+                let fields = object_fields(fields);
+                self.struct_item(&name, &params, &fields, def.docs.as_deref(), owner)?
+            }
+            // `type A => B` only renames B, which need not be a record
+            Some(alias) => {
+                let alias = self.rust_type(alias, &def.name, owner)?;
+                format!(
+                    "{}pub type {}{} = {};",
+                    doc_comment(def.docs.as_deref()),
+                    name,
+                    params,
+                    alias
+                )
+            }
+            _ => {
+                let args: Vec<Type> = def
+                    .params
+                    .iter()
+                    .map(|param| Type::Custom(param.clone(), Vec::new()))
+                    .collect();
+                let fields = self.types.flattened_fields(def, &args);
+                self.struct_item(&name, &params, &fields, def.docs.as_deref(), owner)?
+            }
+        };
+
+        self.items.push((item, true));
+        Ok(())
+    }
+
+    fn struct_item(
+        &mut self,
+        name: &str,
+        params: &str,
+        fields: &[TypeField],
+        docs: Option<&str>,
+        owner: Owner,
+    ) -> Result<String, CodegenError> {
+        let mut out = doc_comment(docs);
+        out.push_str("#[derive(Debug, Clone, PartialEq)]\n");
+        out.push_str(&format!("pub struct {}{} {{", name, params));
+        if fields.is_empty() {
+            out.push('}');
+            return Ok(out);
+        }
+
+        out.push('\n');
+        for field in fields {
+            let hint = format!(
+                "{}{}",
+                name.trim_start_matches("r#"),
+                pascal_case(&field.name)
+            );
+            let mut ty = self.rust_type(&field.field_type, &hint, owner)?;
+            if self.reaches(&field.field_type, owner.name, &mut Vec::new()) {
+                ty = format!("{}<{}>", self.boxed(), ty);
+            }
+            for line in doc_comment(field.docs.as_deref()).lines() {
+                out.push_str(&format!("{}{}\n", INDENT, line));
+            }
+            out.push_str(&format!("{}pub {}: {},\n", INDENT, ident(&field.name)?, ty));
+        }
+        out.push('}');
+        Ok(out)
+    }
+
+    fn enum_item(
+        &mut self,
+        name: &str,
+        params: &str,
+        members: &[Type],
+        docs: Option<&str>,
+        owner: Owner,
+    ) -> Result<String, CodegenError> {
+        let mut out = doc_comment(docs);
+        out.push_str("#[derive(Debug, Clone, PartialEq)]\n");
+        out.push_str(&format!("pub enum {}{} {{\n", name, params));
+
+        for (member, variant) in members.iter().zip(variant_names(members)) {
+            let payload = match member {
+                Type::Literal(_) => None,
+                _ => {
+                    let hint = format!("{}{}", name.trim_start_matches("r#"), variant);
+                    let ty = self.rust_type(member, &hint, owner)?;
+                    Some(if self.reaches(member, owner.name, &mut Vec::new()) {
+                        format!("{}<{}>", self.boxed(), ty)
+                    } else {
+                        ty
+                    })
+                }
+            };
+            match payload {
+                Some(payload) => out.push_str(&format!("{}{}({}),\n", INDENT, variant, payload)),
+                None => out.push_str(&format!("{}{},\n", INDENT, variant)),
+            }
+        }
+        out.push('}');
+        Ok(out)
+    }
+
+    /// Spells out a type in Rust, generating items for the inline object and
+    /// union types in it, named after `hint`
+    fn rust_type(&mut self, ty: &Type, hint: &str, owner: Owner) -> Result<String, CodegenError> {
+        Ok(match ty {
+            Type::Number | Type::Literal(Literal::Number(_)) => "f64".to_string(),
+            Type::String | Type::Literal(Literal::String(_)) => "String".to_string(),
+            Type::Boolean | Type::Literal(Literal::Boolean(_)) => "bool".to_string(),
+            Type::Custom(name, args) if args.is_empty() => ident(name)?,
+            Type::Custom(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.rust_type(arg, hint, owner))
+                    .collect::<Result<Vec<_>, _>>()?;
+                format!("{}<{}>", ident(name)?, args.join(", "))
+            }
+            Type::List(element) => {
+                let element = self.rust_type(element, hint, owner)?;
+                format!("{}<{}>", self.std_path("Vec", "std::vec::Vec"), element)
+            }
+            Type::Object(_) | Type::Union(_) => self.inline_item(ty, hint, owner)?,
+        })
+    }
+
+    /// Returns the item generated for an inline type, generating it on first
+    /// use. Identical inline types share one item.
+    fn inline_item(&mut self, ty: &Type, hint: &str, owner: Owner) -> Result<String, CodegenError> {
+        // Only the parameters the type uses, since unused ones are an error
+        let params: Vec<String> = owner
+            .params
+            .iter()
+            .filter(|param| mentions(ty, param))
+            .cloned()
+            .collect();
+        let params = generics(&params);
+
+        if let Some((_, name)) = self.inline.iter().find(|(inline, _)| inline == ty) {
+            return Ok(format!("{}{}", name, params));
+        }
+
+        let mut name = pascal_case(hint);
+        let mut suffix = 1;
+        while self.names.contains(&name) {
+            suffix += 1;
+            name = format!("{}{}", pascal_case(hint), suffix);
+        }
+        self.names.insert(name.clone());
+        self.inline.push((ty.clone(), name.clone()));
+
+        let item = match ty {
+            Type::Object(fields) => {
+                self.struct_item(&name, &params, &object_fields(fields), None, owner)?
+            }
+            Type::Union(members) => self.enum_item(&name, &params, members, None, owner)?,
+            _ => unreachable!("only object and union types are generated inline"),
+        };
+        self.items.push((item, true));
+        Ok(format!("{}{}", name, params))
+    }
+
+    /// Returns the name of a standard library type, or its full path when a
+    /// definition in the module takes the name
+    fn std_path(&self, name: &str, path: &str) -> String {
+        if self.types.lookup(name).is_some() {
+            path.to_string()
+        } else {
+            name.to_string()
+        }
+    }
+
+    fn boxed(&self) -> String {
+        self.std_path("Box", "std::boxed::Box")
+    }
+
+    /// Returns true if a value of `ty` can contain a `target` without going
+    /// through a list, whose elements live on the heap
+    fn reaches(&self, ty: &Type, target: &str, visited: &mut Vec<String>) -> bool {
+        match ty {
+            Type::Custom(name, args) => {
+                if name == target || args.iter().any(|arg| self.reaches(arg, target, visited)) {
+                    return true;
+                }
+                if visited.contains(name) {
+                    return false;
+                }
+                visited.push(name.clone());
+                let Some(def) = self.types.lookup(name) else {
+                    return false;
+                };
+                def.alias
+                    .iter()
+                    .chain(&def.extends)
+                    .chain(def.fields.iter().map(|field| &field.field_type))
+                    .any(|ty| self.reaches(ty, target, visited))
+            }
+            Type::Object(fields) => fields
+                .iter()
+                .any(|(_, ty)| self.reaches(ty, target, visited)),
+            Type::Union(members) => members
+                .iter()
+                .any(|member| self.reaches(member, target, visited)),
+            _ => false,
+        }
+    }
+
+    fn constant(
+        &mut self,
+        name: &str,
+        ty: Option<&Type>,
+        value: &Value,
+    ) -> Result<String, CodegenError> {
+        let ty = match ty {
+            Some(ty) => ty.clone(),
+            None => self.infer(value),
+        };
+        let hint = pascal_case(name);
+        let name = ident(name)?;
+
+        let scalar = match (self.types.expand(&ty), value) {
+            (Type::Number | Type::Literal(Literal::Number(_)), Value::Number(n)) => {
+                Some(("f64", number(*n)))
+            }
+            (Type::String | Type::Literal(Literal::String(_)), Value::String(s)) => {
+                Some(("&str", format!("{:?}", s)))
+            }
+            (Type::Boolean | Type::Literal(Literal::Boolean(_)), Value::Bool(b)) => {
+                Some(("bool", b.to_string()))
+            }
+            _ => None,
+        };
+        if let Some((rust_type, literal)) = scalar {
+            return Ok(format!("pub const {}: {} = {};", name, rust_type, literal));
+        }
+
+        let rust_type = self.rust_type(&ty, &hint, NO_OWNER)?;
+        let (expr, is_const) = self.value_expr(value, &ty, &hint, 0, NO_OWNER)?;
+        Ok(match (value, is_const) {
+            (Value::Object(_), true) => {
+                format!("pub static {}: {} = {};", name, rust_type, expr)
+            }
+            (_, true) => format!("pub const {}: {} = {};", name, rust_type, expr),
+            (_, false) => format!(
+                "pub static {}: std::sync::LazyLock<{}> = std::sync::LazyLock::new(|| {});",
+                name, rust_type, expr
+            ),
+        })
+    }
+
+    /// Returns the type of an unannotated const: the first record type its
+    /// value fits, or else its structure
+    fn infer(&self, value: &Value) -> Type {
+        match value {
+            Value::Number(_) => Type::Number,
+            Value::String(_) => Type::String,
+            Value::Bool(_) => Type::Boolean,
+            Value::List(elements) => {
+                let mut members: Vec<Type> = Vec::new();
+                for element in elements {
+                    let ty = self.infer(element);
+                    if !members.contains(&ty) {
+                        members.push(ty);
+                    }
+                }
+                if members.len() == 1 {
+                    Type::List(Box::new(members.remove(0)))
+                } else {
+                    Type::List(Box::new(Type::Union(members)))
+                }
+            }
+            Value::Object(fields) => {
+                let ty = Type::Object(
+                    fields
+                        .iter()
+                        .map(|(name, value)| (name.clone(), self.infer(value)))
+                        .collect(),
+                );
+                match self.types.matching_definition(&ty) {
+                    Some(def) => Type::Custom(def.name.clone(), Vec::new()),
+                    None => ty,
+                }
+            }
+        }
+    }
+
+    /// Writes a value of `ty` as a Rust expression, returning whether it can
+    /// be evaluated at compile time. `ty` is written in terms of the owner's
+    /// type parameters, so that its inline types are found under the names
+    /// they were generated with.
+    fn value_expr(
+        &mut self,
+        value: &Value,
+        ty: &Type,
+        hint: &str,
+        depth: usize,
+        owner: Owner,
+    ) -> Result<(String, bool), CodegenError> {
+        let actual = self.types.expand(&ty.substitute(owner.params, owner.args));
+        Ok(match (ty, value) {
+            (_, Value::Number(n)) if matches!(actual, Type::Number | Type::Literal(_)) => {
+                (number(*n), true)
+            }
+            (_, Value::String(s)) if matches!(actual, Type::String | Type::Literal(_)) => {
+                (format!("{:?}.to_string()", s), false)
+            }
+            (_, Value::Bool(b)) if matches!(actual, Type::Boolean | Type::Literal(_)) => {
+                (b.to_string(), true)
+            }
+            (Type::Custom(name, _), _) if owner.params.contains(name) => {
+                let arg = ty.substitute(owner.params, owner.args);
+                self.value_expr(value, &arg, hint, depth, NO_OWNER)?
+            }
+            (Type::Custom(name, args), _) => {
+                let Some(def) = self.types.lookup(name) else {
+                    return self.value_expr(value, &self.infer(value), hint, depth, owner);
+                };
+                let args: Vec<Type> = args
+                    .iter()
+                    .map(|arg| arg.substitute(owner.params, owner.args))
+                    .collect();
+                let owner = Owner {
+                    name: &def.name,
+                    params: &def.params,
+                    args: &args,
+                };
+                match &def.alias {
+                    Some(Type::Union(members)) => {
+                        self.enum_value(&ident(name)?, members, value, depth, owner)?
+                    }
+                    Some(Type::Object(fields)) => {
+                        let fields = object_fields(fields);
+                        self.struct_value(&ident(name)?, &fields, value, depth, owner)?
+                    }
+                    Some(alias) => self.value_expr(value, alias, &def.name, depth, owner)?,
+                    _ => {
+                        let params: Vec<Type> = def
+                            .params
+                            .iter()
+                            .map(|param| Type::Custom(param.clone(), Vec::new()))
+                            .collect();
+                        let fields = self.types.flattened_fields(def, &params);
+                        self.struct_value(&ident(name)?, &fields, value, depth, owner)?
+                    }
+                }
+            }
+            (Type::Object(fields), _) => {
+                let name = self.inline_item(ty, hint, owner)?;
+                let name = name.split('<').next().unwrap_or_default().to_string();
+                self.struct_value(&name, &object_fields(fields), value, depth, owner)?
+            }
+            (Type::Union(members), _) => {
+                let name = self.inline_item(ty, hint, owner)?;
+                let name = name.split('<').next().unwrap_or_default().to_string();
+                self.enum_value(&name, members, value, depth, owner)?
+            }
+            (Type::List(_), Value::List(elements)) if elements.is_empty() => (
+                format!("{}::new()", self.std_path("Vec", "std::vec::Vec")),
+                false,
+            ),
+            (Type::List(element), Value::List(elements)) => {
+                let mut out = "vec![\n".to_string();
+                for value in elements {
+                    let (expr, _) = self.value_expr(value, element, hint, depth + 1, owner)?;
+                    out.push_str(&format!("{}{},\n", INDENT.repeat(depth + 1), expr));
+                }
+                out.push_str(&INDENT.repeat(depth));
+                out.push(']');
+                (out, false)
+            }
+            _ => return self.value_expr(value, &self.infer(value), hint, depth, owner),
+        })
+    }
+
+    fn struct_value(
+        &mut self,
+        name: &str,
+        fields: &[TypeField],
+        value: &Value,
+        depth: usize,
+        owner: Owner,
+    ) -> Result<(String, bool), CodegenError> {
+        let mut out = format!("{} {{\n", name);
+        let mut is_const = true;
+
+        for field in fields {
+            let Some(field_value) = value.get(&field.name) else {
+                continue;
+            };
+            let hint = format!(
+                "{}{}",
+                name.trim_start_matches("r#"),
+                pascal_case(&field.name)
+            );
+            let (mut expr, field_const) =
+                self.value_expr(field_value, &field.field_type, &hint, depth + 1, owner)?;
+            is_const &= field_const;
+            if self.reaches(&field.field_type, owner.name, &mut Vec::new()) {
+                expr = format!("{}::new({})", self.boxed(), expr);
+                is_const = false;
+            }
+            out.push_str(&format!(
+                "{}{}: {},\n",
+                INDENT.repeat(depth + 1),
+                ident(&field.name)?,
+                expr
+            ));
+        }
+
+        out.push_str(&INDENT.repeat(depth));
+        out.push('}');
+        Ok((out, is_const))
+    }
+
+    /// Writes a value of a union as the variant of the first member it fits
+    fn enum_value(
+        &mut self,
+        name: &str,
+        members: &[Type],
+        value: &Value,
+        depth: usize,
+        owner: Owner,
+    ) -> Result<(String, bool), CodegenError> {
+        let document = JsonValue::from(value);
+        let index = members
+            .iter()
+            .position(|member| {
+                let member = member.substitute(owner.params, owner.args);
+                self.validator.validate_type(&member, &document).is_empty()
+            })
+            .unwrap_or(0);
+        let member = &members[index];
+        let variant = &variant_names(members)[index];
+
+        if let Type::Literal(_) = member {
+            return Ok((format!("{}::{}", name, variant), true));
+        }
+        let hint = format!("{}{}", name.trim_start_matches("r#"), variant);
+        let (mut expr, mut is_const) = self.value_expr(value, member, &hint, depth, owner)?;
+        if self.reaches(member, owner.name, &mut Vec::new()) {
+            expr = format!("{}::new({})", self.boxed(), expr);
+            is_const = false;
+        }
+        Ok((format!("{}::{}({})", name, variant, expr), is_const))
+    }
+}
+
+/// Names the variants of an enum generated for a union: literals are named
+/// after their value and other members after their type
+fn variant_names(members: &[Type]) -> Vec<String> {
+    fn variant(ty: &Type) -> String {
+        match ty {
+            Type::Number => "Number".to_string(),
+            Type::String => "String".to_string(),
+            Type::Boolean => "Boolean".to_string(),
+            Type::Literal(Literal::Number(n)) => {
+                format!("N{}", n.to_string().replace('-', "Minus").replace('.', "_"))
+            }
+            Type::Literal(Literal::String(s)) => pascal_case(s),
+            Type::Literal(Literal::Boolean(b)) => pascal_case(&b.to_string()),
+            Type::Custom(name, args) => {
+                let args: String = args.iter().map(variant).collect();
+                format!("{}{}", pascal_case(name), args)
+            }
+            Type::List(element) => format!("{}List", variant(element)),
+            Type::Object(_) => "Object".to_string(),
+            Type::Union(_) => "Union".to_string(),
+        }
+    }
+
+    let mut names: Vec<String> = Vec::new();
+    for member in members {
+        let base = variant(member);
+        let mut name = base.clone();
+        let mut suffix = 1;
+        while names.contains(&name) {
+            suffix += 1;
+            name = format!("{}{}", base, suffix);
+        }
+        names.push(name);
+    }
+    names
+}
+
+/// Returns true if `ty` refers to the type parameter `param`
+fn mentions(ty: &Type, param: &str) -> bool {
+    match ty {
+        Type::Custom(name, args) => name == param || args.iter().any(|arg| mentions(arg, param)),
+        Type::List(element) => mentions(element, param),
+        Type::Object(fields) => fields.iter().any(|(_, ty)| mentions(ty, param)),
+        Type::Union(members) => members.iter().any(|member| mentions(member, param)),
+        _ => false,
+    }
+}
+
+fn object_fields(fields: &[(String, Type)]) -> Vec<TypeField> {
+    fields
+        .iter()
+        .map(|(name, field_type)| TypeField {
+            name: name.clone(),
+            field_type: field_type.clone(),
+            default: None,
+            docs: None,
+            span: Span::default(),
+        })
+        .collect()
+}
+
+fn generics(params: &[String]) -> String {
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", params.join(", "))
+    }
+}
+
+/// Returns a name as a Rust identifier, escaping keywords
+fn ident(name: &str) -> Result<String, CodegenError> {
+    if RESERVED.contains(&name) {
+        return Err(CodegenError::ReservedWord {
+            name: name.to_string(),
+            target: "Rust",
+        });
+    }
+    Ok(if KEYWORDS.contains(&name) {
+        format!("r#{}", name)
+    } else {
+        name.to_string()
+    })
+}
+
+/// Joins the words of `text` into a type name, as in `MaxPort` for
+/// `max_port`, starting it with `V` when it would otherwise start with a digit
+fn pascal_case(text: &str) -> String {
+    let mut name: String = text
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, 'V');
+    }
+    name
+}
+
+/// Writes a number as an `f64` literal, which needs a decimal point or exponent
+fn number(n: f64) -> String {
+    format!("{:?}", n)
+}
+
+fn doc_comment(docs: Option<&str>) -> String {
+    let Some(docs) = docs else {
+        return String::new();
+    };
+    docs.lines()
+        .map(|line| {
+            if line.is_empty() {
+                "///\n".to_string()
+            } else {
+                format!("/// {}\n", line)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn parse(source: &str) -> Program {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        Parser::new(tokens).parse().unwrap()
+    }
+
+    #[test]
+    fn test_rust_items() {
+        let program = parse(
+            "module config {
+                /// Where the service runs
+                type Env => \"dev\" | \"prod\"
+
+                type Server => {
+                    /// Host name or address
+                    host: String,
+                    port: Number = 8080,
+                    env: Env,
+                    limits: { low: Number, high: Number },
+                    ref: Boolean
+                }
+
+                type Box<T> => { value: T | Number }
+
+                const MAX = 2 * 5
+                const NAME = \"api\"
+                const ENV: Env = \"prod\"
+                const LIMITS = { low: 1, high: MAX }
+                const MAIN: Server = {
+                    host: \"localhost\",
+                    env: \"dev\",
+                    limits: { low: 0, high: 1 },
+                    ref: true
+                }
+                const BOXED: Box<String> = { value: \"x\" }
+            }",
+        );
+
+        assert_eq!(
+            to_rust(&program).unwrap(),
+            r#"// Generated by jlang. Do not edit.
+
+#[allow(dead_code, non_camel_case_types, non_snake_case, non_upper_case_globals)]
+pub mod config {
+    /// Where the service runs
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Env {
+        Dev,
+        Prod,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ServerLimits {
+        pub low: f64,
+        pub high: f64,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Server {
+        /// Host name or address
+        pub host: String,
+        pub port: f64,
+        pub env: Env,
+        pub limits: ServerLimits,
+        pub r#ref: bool,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum BoxValue<T> {
+        T(T),
+        Number(f64),
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Box<T> {
+        pub value: BoxValue<T>,
+    }
+
+    pub const MAX: f64 = 10.0;
+    pub const NAME: &str = "api";
+    pub const ENV: Env = Env::Prod;
+
+    pub static LIMITS: ServerLimits = ServerLimits {
+        low: 1.0,
+        high: 10.0,
+    };
+
+    pub static MAIN: std::sync::LazyLock<Server> = std::sync::LazyLock::new(|| Server {
+        host: "localhost".to_string(),
+        port: 8080.0,
+        env: Env::Dev,
+        limits: ServerLimits {
+            low: 0.0,
+            high: 1.0,
+        },
+        r#ref: true,
+    });
+
+    pub static BOXED: std::sync::LazyLock<Box<String>> = std::sync::LazyLock::new(|| Box {
+        value: BoxValue::T("x".to_string()),
+    });
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_recursive_types_are_boxed() {
+        let program = parse(
+            "module m {
+                type Node => { value: Number, next: Node | \"end\", children: Node[] }
+            }",
+        );
+        let rust = to_rust(&program).unwrap();
+
+        assert!(rust.contains("Node(Box<Node>),"));
+        assert!(rust.contains("pub children: Vec<Node>,"));
+
+        // A definition named after a standard type takes its name
+        let program = parse(
+            "module m {
+                type Vec => { x: Number }
+                type List => { items: Number[], next: List | Number }
+            }",
+        );
+        let rust = to_rust(&program).unwrap();
+
+        assert!(rust.contains("pub items: std::vec::Vec<f64>,"));
+        assert!(rust.contains("List(Box<List>),"));
+    }
+
+    #[test]
+    fn test_lists() {
+        let program = parse(
+            "module m {
+                type Server => { hosts: String[], ports: Number[] = [] }
+                const PORTS = [80, 443]
+                const MAIN: Server = { hosts: [\"a\", \"b\"] }
+            }",
+        );
+        let rust = to_rust(&program).unwrap();
+
+        assert!(rust.contains(
+            "    pub static PORTS: std::sync::LazyLock<Vec<f64>> = std::sync::LazyLock::new(|| vec![
+        80.0,
+        443.0,
+    ]);"
+        ));
+        assert!(rust.contains(
+            "        hosts: vec![
+            \"a\".to_string(),
+            \"b\".to_string(),
+        ],
+        ports: Vec::new(),"
+        ));
+    }
+
+    #[test]
+    fn test_variant_names() {
+        let members = vec![
+            Type::Literal(Literal::String("read-only".to_string())),
+            Type::Literal(Literal::Number(-1.5)),
+            Type::Custom("Pair".to_string(), vec![Type::Number, Type::String]),
+            Type::List(Box::new(Type::String)),
+            Type::Literal(Literal::String("read only".to_string())),
+        ];
+        assert_eq!(
+            variant_names(&members),
+            vec![
+                "ReadOnly",
+                "NMinus1_5",
+                "PairNumberString",
+                "StringList",
+                "ReadOnly2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reserved_words() {
+        assert_eq!(ident("match"), Ok("r#match".to_string()));
+        assert_eq!(
+            ident("crate"),
+            Err(CodegenError::ReservedWord {
+                name: "crate".to_string(),
+                target: "Rust",
+            })
+        );
+    }
+}
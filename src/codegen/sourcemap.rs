@@ -0,0 +1,160 @@
+use crate::json::{JsonValue, write_json};
+
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Records which source position each piece of generated code came from, and
+/// writes the result as a version 3 source map. Lines and columns are
+/// zero-based, and columns count UTF-16 code units as JavaScript does.
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    mappings: Vec<Mapping>, // In generated order
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Mapping {
+    generated_line: usize,
+    generated_column: usize,
+    original_line: usize,
+    original_column: usize,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        SourceMap::default()
+    }
+
+    /// Maps a position in the generated code to one in the source. Positions
+    /// must be added in the order they appear in the generated code.
+    pub fn add(&mut self, generated: (usize, usize), original: (usize, usize)) {
+        let mapping = Mapping {
+            generated_line: generated.0,
+            generated_column: generated.1,
+            original_line: original.0,
+            original_column: original.1,
+        };
+        // Nested expressions can start where their parent does
+        match self.mappings.last_mut() {
+            Some(last)
+                if last.generated_line == mapping.generated_line
+                    && last.generated_column == mapping.generated_column =>
+            {
+                *last = mapping
+            }
+            _ => self.mappings.push(mapping),
+        }
+    }
+
+    /// Returns the `mappings` field: one group per generated line, separated
+    /// by `;`, of comma-separated segments whose fields are Base64 VLQ
+    /// encoded relative to the previous segment
+    pub fn mappings(&self) -> String {
+        let mut out = String::new();
+        let mut line = 0;
+        let mut previous_column = 0;
+        let mut previous_original = (0, 0);
+
+        for (i, mapping) in self.mappings.iter().enumerate() {
+            if mapping.generated_line > line {
+                out.push_str(&";".repeat(mapping.generated_line - line));
+                line = mapping.generated_line;
+                previous_column = 0;
+            } else if i > 0 {
+                out.push(',');
+            }
+
+            vlq(
+                &mut out,
+                mapping.generated_column as i64 - previous_column as i64,
+            );
+            vlq(&mut out, 0); // The only source
+            vlq(
+                &mut out,
+                mapping.original_line as i64 - previous_original.0 as i64,
+            );
+            vlq(
+                &mut out,
+                mapping.original_column as i64 - previous_original.1 as i64,
+            );
+
+            previous_column = mapping.generated_column;
+            previous_original = (mapping.original_line, mapping.original_column);
+        }
+
+        out
+    }
+
+    /// Writes the source map for the generated file `file`, embedding the
+    /// contents of the source so it can be shown without finding the file
+    pub fn to_json(&self, file: &str, source_name: &str, source: &str) -> String {
+        let string = |s: &str| JsonValue::String(s.to_string());
+        write_json(&JsonValue::Object(vec![
+            ("version".to_string(), JsonValue::Number(3.0)),
+            ("file".to_string(), string(file)),
+            (
+                "sources".to_string(),
+                JsonValue::Array(vec![string(source_name)]),
+            ),
+            (
+                "sourcesContent".to_string(),
+                JsonValue::Array(vec![string(source)]),
+            ),
+            ("names".to_string(), JsonValue::Array(Vec::new())),
+            ("mappings".to_string(), string(&self.mappings())),
+        ])) + "\n"
+    }
+}
+
+/// Appends `value` in Base64 VLQ: the sign in the lowest bit, then five bits
+/// per digit, least significant first, with the sixth bit set on every digit
+/// but the last
+fn vlq(out: &mut String, value: i64) {
+    let mut rest = if value < 0 {
+        ((-value) << 1) | 1
+    } else {
+        value << 1
+    };
+
+    loop {
+        let mut digit = rest & 0b11111;
+        rest >>= 5;
+        if rest > 0 {
+            digit |= 0b100000;
+        }
+        out.push(BASE64[digit as usize] as char);
+        if rest == 0 {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vlq() {
+        let encode = |value| {
+            let mut out = String::new();
+            vlq(&mut out, value);
+            out
+        };
+        assert_eq!(encode(0), "A");
+        assert_eq!(encode(1), "C");
+        assert_eq!(encode(-1), "D");
+        assert_eq!(encode(15), "e");
+        assert_eq!(encode(16), "gB");
+        assert_eq!(encode(123), "2H");
+        assert_eq!(encode(-1000), "x+B");
+    }
+
+    #[test]
+    fn test_mappings() {
+        let mut map = SourceMap::new();
+        map.add((1, 0), (0, 4));
+        map.add((1, 13), (0, 10));
+        map.add((1, 13), (0, 12)); // Replaces the mapping before
+        map.add((3, 4), (2, 8));
+
+        assert_eq!(map.mappings(), ";AAAI,aAAQ;;IAEJ");
+    }
+}
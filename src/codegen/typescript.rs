@@ -0,0 +1,340 @@
+use crate::ast::*;
+use crate::consteval::ConstEvaluator;
+use crate::eval::Value;
+use crate::json::{JsonValue, write_json};
+
+use super::{CodegenError, INDENT, JS_RESERVED};
+
+/// Predefined types, whose names cannot be taken by an interface or type alias
+const TYPE_NAMES: &[&str] = &[
+    "any",
+    "bigint",
+    "boolean",
+    "never",
+    "number",
+    "object",
+    "string",
+    "symbol",
+    "undefined",
+    "unknown",
+];
+
+/// Generates a TypeScript declaration file for a program. Each module becomes
+/// a namespace holding an interface per record type, a type alias per other
+/// definition and a const per `const` binding, typed with its folded value.
+/// `let` bindings may change at runtime and are left out.
+///
+/// Declarations keep the order they are written in, so regenerating the file
+/// after an unrelated change leaves it untouched.
+pub fn to_typescript(program: &Program) -> Result<String, CodegenError> {
+    let (values, _) = ConstEvaluator::new(program).evaluate();
+    let mut out = String::from("// Generated by jlang. Do not edit.\n");
+
+    for (index, module) in program.modules.iter().enumerate() {
+        check_name(&module.name, JS_RESERVED)?;
+        // Members of a `declare namespace` are ambient already, so they take
+        // no `declare` of their own
+        out.push_str(&format!("\nexport declare namespace {} {{\n", module.name));
+
+        let mut previous: Option<(String, bool)> = None;
+        for statement in &module.statements {
+            let (text, is_type) = match statement {
+                Statement::TypeDef(def) => (type_definition(def)?, true),
+                Statement::Const { name, ty, .. } => {
+                    check_name(name, JS_RESERVED)?;
+                    let ty = match values.get(index, name) {
+                        Some(value) => literal_type(value, 0),
+                        None => ty.as_ref().map_or("unknown".to_string(), type_expr),
+                    };
+                    (format!("export const {}: {};", name, ty), false)
+                }
+                _ => continue,
+            };
+
+            if let Some((previous, was_type)) = &previous
+                && (previous.contains('\n') || text.contains('\n') || *was_type || is_type)
+            {
+                out.push('\n');
+            }
+            for line in text.lines() {
+                out.push_str(INDENT);
+                out.push_str(line);
+                out.push('\n');
+            }
+            previous = Some((text, is_type));
+        }
+
+        out.push_str("}\n");
+    }
+
+    Ok(out)
+}
+
+fn check_name(name: &str, reserved: &[&str]) -> Result<(), CodegenError> {
+    if reserved.contains(&name) {
+        return Err(CodegenError::ReservedWord {
+            name: name.to_string(),
+            target: "TypeScript",
+        });
+    }
+    Ok(())
+}
+
+/// Declares a record type as an interface, and any other type as an alias
+fn type_definition(def: &TypeDefinition) -> Result<String, CodegenError> {
+    check_name(&def.name, JS_RESERVED)?;
+    check_name(&def.name, TYPE_NAMES)?;
+
+    let mut out = doc_comment(def.docs.as_deref(), "");
+    let name = if def.params.is_empty() {
+        def.name.clone()
+    } else {
+        format!("{}<{}>", def.name, def.params.join(", "))
+    };
+
+    if let Some(alias) = &def.alias {
+        out.push_str(&format!("export type {} = {};", name, type_expr(alias)));
+        return Ok(out);
+    }
+
+    out.push_str(&format!("export interface {}", name));
+    if !def.extends.is_empty() {
+        let parents: Vec<String> = def.extends.iter().map(type_expr).collect();
+        out.push_str(&format!(" extends {}", parents.join(", ")));
+    }
+    if def.fields.is_empty() {
+        out.push_str(" {}");
+        return Ok(out);
+    }
+
+    out.push_str(" {\n");
+    for field in &def.fields {
+        out.push_str(&doc_comment(field.docs.as_deref(), INDENT));
+        // Fields with defaults may be left out of object literals
+        let optional = if field.default.is_some() { "?" } else { "" };
+        out.push_str(&format!(
+            "{}{}{}: {};\n",
+            INDENT,
+            field.name,
+            optional,
+            type_expr(&field.field_type)
+        ));
+    }
+    out.push('}');
+    Ok(out)
+}
+
+/// Formats `///` documentation as a JSDoc comment, which editors show on hover
+fn doc_comment(docs: Option<&str>, indent: &str) -> String {
+    let Some(docs) = docs else {
+        return String::new();
+    };
+    let docs = docs.replace("*/", "*\\/");
+
+    if !docs.contains('\n') {
+        return format!("{}/** {} */\n", indent, docs);
+    }
+    let mut out = format!("{}/**\n", indent);
+    for line in docs.lines() {
+        if line.is_empty() {
+            out.push_str(&format!("{} *\n", indent));
+        } else {
+            out.push_str(&format!("{} * {}\n", indent, line));
+        }
+    }
+    out.push_str(&format!("{} */\n", indent));
+    out
+}
+
+fn type_expr(ty: &Type) -> String {
+    match ty {
+        Type::Number => "number".to_string(),
+        Type::String => "string".to_string(),
+        Type::Boolean => "boolean".to_string(),
+        Type::Literal(Literal::Number(n)) => n.to_string(),
+        Type::Literal(Literal::String(s)) => string(s),
+        Type::Literal(Literal::Boolean(b)) => b.to_string(),
+        Type::Custom(name, args) if args.is_empty() => name.clone(),
+        Type::Custom(name, args) => format!(
+            "{}<{}>",
+            name,
+            args.iter().map(type_expr).collect::<Vec<_>>().join(", ")
+        ),
+        Type::List(element) if matches!(**element, Type::Union(_)) => {
+            format!("({})[]", type_expr(element))
+        }
+        Type::List(element) => format!("{}[]", type_expr(element)),
+        Type::Object(fields) if fields.is_empty() => "{}".to_string(),
+        Type::Object(fields) => {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|(name, ty)| format!("{}: {}", name, type_expr(ty)))
+                .collect();
+            format!("{{ {} }}", fields.join("; "))
+        }
+        Type::Union(members) => members
+            .iter()
+            .map(type_expr)
+            .collect::<Vec<_>>()
+            .join(" | "),
+    }
+}
+
+/// Spells out a folded const as the narrowest type holding it, laying
+/// objects out over several lines
+fn literal_type(value: &Value, depth: usize) -> String {
+    match value {
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => string(s),
+        Value::Bool(b) => b.to_string(),
+        Value::List(elements) => {
+            let elements: Vec<String> = elements
+                .iter()
+                .map(|element| literal_type(element, depth))
+                .collect();
+            format!("readonly [{}]", elements.join(", "))
+        }
+        Value::Object(fields) if fields.is_empty() => "{}".to_string(),
+        Value::Object(fields) => {
+            let mut out = "{\n".to_string();
+            for (name, value) in fields {
+                out.push_str(&INDENT.repeat(depth + 1));
+                out.push_str(&format!(
+                    "readonly {}: {};\n",
+                    name,
+                    literal_type(value, depth + 1)
+                ));
+            }
+            out.push_str(&INDENT.repeat(depth));
+            out.push('}');
+            out
+        }
+    }
+}
+
+/// Quotes a string; JSON string syntax is valid TypeScript
+fn string(s: &str) -> String {
+    write_json(&JsonValue::String(s.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn parse(source: &str) -> Program {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        Parser::new(tokens).parse().unwrap()
+    }
+
+    #[test]
+    fn test_typescript_declarations() {
+        let program = parse(
+            "module users {
+                /// Access level
+                type Role => \"admin\" | \"member\"
+
+                /// A registered user
+                type User extends Named => {
+                    /// Unique across all users
+                    id: Number,
+                    active: Boolean = true,
+                    role: Role,
+                    tags: String[],
+                    home: Pair<Number, String>,
+                    settings: { theme: String, size: Number }
+                }
+
+                type Named => { name: String }
+                type Pair<A, B> => { first: A, second: B }
+                type Admin => User
+
+                const MAX = 2 * 5
+                const GREETING = \"hi ${MAX}\"
+                let counter = 0
+                const ROOT = { name: \"root\", limits: { max: MAX, strict: false } }
+                const PORTS = [80, [443]]
+            }
+
+            module empty {
+            }",
+        );
+
+        assert_eq!(
+            to_typescript(&program).unwrap(),
+            r#"// Generated by jlang. Do not edit.
+
+export declare namespace users {
+    /** Access level */
+    export type Role = "admin" | "member";
+
+    /** A registered user */
+    export interface User extends Named {
+        /** Unique across all users */
+        id: number;
+        active?: boolean;
+        role: Role;
+        tags: string[];
+        home: Pair<number, string>;
+        settings: { theme: string; size: number };
+    }
+
+    export interface Named {
+        name: string;
+    }
+
+    export interface Pair<A, B> {
+        first: A;
+        second: B;
+    }
+
+    export type Admin = User;
+
+    export const MAX: 10;
+    export const GREETING: "hi 10";
+
+    export const ROOT: {
+        readonly name: "root";
+        readonly limits: {
+            readonly max: 10;
+            readonly strict: false;
+        };
+    };
+
+    export const PORTS: readonly [80, readonly [443]];
+}
+
+export declare namespace empty {
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_type_expr() {
+        let list = Type::List(Box::new(Type::Union(vec![
+            Type::String,
+            Type::Literal(Literal::Number(-1.5)),
+        ])));
+        assert_eq!(type_expr(&list), "(string | -1.5)[]");
+        assert_eq!(type_expr(&Type::Object(Vec::new())), "{}");
+    }
+
+    #[test]
+    fn test_reserved_words() {
+        let program = parse("module m { const delete = 1 }");
+        assert_eq!(
+            to_typescript(&program),
+            Err(CodegenError::ReservedWord {
+                name: "delete".to_string(),
+                target: "TypeScript",
+            })
+        );
+
+        let program = parse("module m { type string => { x: Number } }");
+        assert!(to_typescript(&program).is_err());
+        let program = parse("module m { const string = 1 }");
+        assert!(to_typescript(&program).is_ok());
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt;
+
+use crate::ast::Span;
+use crate::diagnostic::Diagnostic;
+use crate::eval::EvalError;
+
+/// Represents reasons a `const` initializer cannot be folded at compile time
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstError {
+    /// Consts whose initializers refer to each other in a loop, reported at
+    /// the reference to a later const that leads back
+    Cycle { path: Vec<String>, span: Span },
+
+    /// A const refers to a `let` binding, whose value may change
+    NotConstant { name: String, span: Span },
+
+    /// Evaluating an initializer failed, for example by dividing by zero
+    Eval(EvalError),
+}
+
+impl ConstError {
+    /// Returns the position the error is reported at
+    pub fn span(&self) -> Span {
+        match self {
+            ConstError::Cycle { span, .. } | ConstError::NotConstant { span, .. } => *span,
+            ConstError::Eval(err) => err.span(),
+        }
+    }
+
+    /// Describes the error with a label at the reference or expression at fault
+    pub fn diagnostic(&self) -> Diagnostic {
+        match self {
+            ConstError::Cycle { path, span } => {
+                Diagnostic::error(format!("cyclic definition of const '{}'", path[0]))
+                    .with_label(*span, format!("'{}' refers back to itself here", path[0]))
+                    .with_note(format!("the cycle is {}", path.join(" -> ")))
+            }
+            ConstError::NotConstant { name, span } => {
+                Diagnostic::error(format!("'{}' is not a constant", name))
+                    .with_label(*span, "consts may only refer to other consts")
+                    .with_note(format!(
+                        "declare '{}' with 'const' if it never changes",
+                        name
+                    ))
+            }
+            ConstError::Eval(err) => err.diagnostic(),
+        }
+    }
+}
+
+impl From<EvalError> for ConstError {
+    fn from(err: EvalError) -> Self {
+        ConstError::Eval(err)
+    }
+}
+
+impl std::error::Error for ConstError {}
+
+impl fmt::Display for ConstError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstError::Cycle { path, span } => write!(
+                f,
+                "Cyclic const definition at {}: {}",
+                span,
+                path.join(" -> ")
+            ),
+            ConstError::NotConstant { name, span } => write!(
+                f,
+                "Const initializer refers to non-const '{}' at {}",
+                name, span
+            ),
+            ConstError::Eval(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_display() {
+        let err = ConstError::Cycle {
+            path: vec!["A".to_string(), "B".to_string(), "A".to_string()],
+            span: Span::new(3, 15),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Cyclic const definition at line 3, column 15: A -> B -> A"
+        );
+
+        let err = ConstError::from(EvalError::DivisionByZero {
+            span: Span::new(2, 20),
+        });
+        assert_eq!(err.to_string(), "Division by zero at line 2, column 20");
+        assert_eq!(err.span(), Span::new(2, 20));
+    }
+}
@@ -0,0 +1,399 @@
+mod error;
+
+pub use error::ConstError;
+
+use crate::ast::*;
+use crate::eval::{Environment, EvalError, Value, evaluate};
+use crate::typeck::TypeEnv;
+
+/// The folded value of every const that could be evaluated, per module
+#[derive(Debug, Default, PartialEq)]
+pub struct ConstValues {
+    modules: Vec<Vec<(String, Value)>>, // In declaration order
+}
+
+impl ConstValues {
+    /// Returns the consts of the module at `module`, in declaration order
+    pub fn module(&self, module: usize) -> &[(String, Value)] {
+        &self.modules[module]
+    }
+
+    pub fn get(&self, module: usize, name: &str) -> Option<&Value> {
+        self.modules[module]
+            .iter()
+            .find(|(constant, _)| constant == name)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Folds `const` initializers into values at compile time, in declaration
+/// order like the program runs. A const may refer to the consts declared
+/// before it, but not to a `let` binding.
+///
+/// Consts that refer to each other in a loop are reported here. Other uses
+/// of a const before its definition, and undefined names, leave the const
+/// unfolded without an error: name resolution reports them, so its errors
+/// belong next to these.
+pub struct ConstEvaluator<'a> {
+    program: &'a Program,
+}
+
+impl<'a> ConstEvaluator<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        ConstEvaluator { program }
+    }
+
+    /// Evaluates every const, returning the values that could be folded along
+    /// with the reasons the others could not
+    pub fn evaluate(&self) -> (ConstValues, Vec<ConstError>) {
+        let mut values = ConstValues::default();
+        let mut errors = Vec::new();
+
+        for module in &self.program.modules {
+            let types = TypeEnv::new(module);
+            let mut consts = ModuleConsts::default();
+            for statement in &module.statements {
+                if let Statement::Const { name, value, .. } = statement {
+                    consts.declared.push((name, value));
+                }
+            }
+
+            for statement in &module.statements {
+                let (name, ty, value) = match statement {
+                    Statement::Const {
+                        name, ty, value, ..
+                    } => (name, ty, value),
+                    Statement::Let { name, .. } => {
+                        consts.lets.push(name);
+                        continue;
+                    }
+                    _ => continue,
+                };
+                // Duplicate definitions are reported by name resolution; the first wins
+                if consts.folded.iter().any(|(folded, _)| folded == name)
+                    || consts.failed.contains(&name.as_str())
+                {
+                    continue;
+                }
+
+                consts.current = name;
+                match evaluate(&types, &mut consts, value, ty.as_ref()) {
+                    Ok(value) => consts.folded.push((name.clone(), value)),
+                    Err(failure) => {
+                        if let Failure::Error(err) = failure {
+                            errors.push(err);
+                        }
+                        consts.failed.push(name);
+                    }
+                }
+            }
+
+            values.modules.push(consts.folded);
+        }
+
+        (values, errors)
+    }
+}
+
+/// Why a lookup failed: either a new error, or one that another pass or an
+/// earlier const already reported
+enum Failure {
+    Error(ConstError),
+    Reported,
+}
+
+impl From<EvalError> for Failure {
+    fn from(err: EvalError) -> Self {
+        match err {
+            // Only the values decide these; the rest are type errors, which
+            // the type checker reports
+            EvalError::DivisionByZero { .. } | EvalError::Overflow { .. } => {
+                Failure::Error(ConstError::Eval(err))
+            }
+            _ => Failure::Reported,
+        }
+    }
+}
+
+/// The consts of one module evaluated so far, and the `let` bindings
+/// declared before the current one
+#[derive(Default)]
+struct ModuleConsts<'a> {
+    folded: Vec<(String, Value)>,
+    failed: Vec<&'a str>,
+    lets: Vec<&'a str>,
+    declared: Vec<(&'a str, &'a Expr)>, // Every const of the module, in order
+    current: &'a str,                   // The const being evaluated
+}
+
+impl ModuleConsts<'_> {
+    /// Returns the consts from `name` leading back to the current one, if
+    /// `name` is a const yet to be evaluated whose initializer depends on it
+    fn cycle(&self, name: &str, path: &mut Vec<String>) -> Option<Vec<String>> {
+        if name == self.current {
+            let mut cycle = path.clone();
+            cycle.push(name.to_string());
+            return Some(cycle);
+        }
+        if path.iter().any(|visited| visited == name) {
+            return None;
+        }
+        let (_, value) = self
+            .declared
+            .iter()
+            .find(|(declared, _)| *declared == name && !self.failed.contains(declared))?;
+
+        path.push(name.to_string());
+        let mut names = Vec::new();
+        identifiers(value, &mut names);
+        for next in names {
+            if let Some(cycle) = self.cycle(next, path) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        None
+    }
+}
+
+/// Collects the names `expr` refers to
+fn identifiers<'e>(expr: &'e Expr, names: &mut Vec<&'e str>) {
+    match &expr.kind {
+        Expression::NumberLiteral(_)
+        | Expression::StringLiteral(_)
+        | Expression::BooleanLiteral(_) => {}
+        Expression::Identifier(name) => names.push(name),
+        Expression::Object { entries } => {
+            for entry in entries {
+                match entry {
+                    ObjectEntry::Field { value, .. } | ObjectEntry::Spread(value) => {
+                        identifiers(value, names)
+                    }
+                }
+            }
+        }
+        Expression::List(elements) => {
+            for element in elements {
+                identifiers(element, names);
+            }
+        }
+        Expression::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            identifiers(condition, names);
+            identifiers(then_branch, names);
+            identifiers(else_branch, names);
+        }
+        Expression::Match { scrutinee, arms } => {
+            identifiers(scrutinee, names);
+            for arm in arms {
+                identifiers(&arm.body, names);
+            }
+        }
+        Expression::Template(parts) => {
+            for part in parts {
+                if let Part::Expr(value) = part {
+                    identifiers(value, names);
+                }
+            }
+        }
+        Expression::Unary { operand, .. } => identifiers(operand, names),
+        Expression::Binary { left, right, .. } => {
+            identifiers(left, names);
+            identifiers(right, names);
+        }
+    }
+}
+
+impl Environment for ModuleConsts<'_> {
+    type Error = Failure;
+
+    fn lookup(&mut self, name: &str, span: Span) -> Result<Value, Failure> {
+        if let Some((_, value)) = self.folded.iter().find(|(folded, _)| folded == name) {
+            return Ok(value.clone());
+        }
+
+        if self.lets.contains(&name) {
+            return Err(Failure::Error(ConstError::NotConstant {
+                name: name.to_string(),
+                span,
+            }));
+        }
+
+        if let Some(path) = self.cycle(name, &mut vec![self.current.to_string()]) {
+            return Err(Failure::Error(ConstError::Cycle { path, span }));
+        }
+
+        // Consts that failed, other consts used before their definition and
+        // undefined names are all reported elsewhere
+        Err(Failure::Reported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn fold(source: &str) -> (ConstValues, Vec<ConstError>) {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        ConstEvaluator::new(&program).evaluate()
+    }
+
+    #[test]
+    fn test_folds_consts_in_declaration_order() {
+        let (values, errors) = fold(
+            "module m {
+                const RETRIES = 3
+                const RETRY_DELAY = 2.5
+                const TIMEOUT = RETRY_DELAY * RETRIES + 1
+                const PORT = 8000 + 80
+                const URL = \"http://localhost:${PORT}\"
+                type Server => { url: String, timeout: Number = TIMEOUT }
+                const SERVER: Server = { url: URL }
+                const OFFSET = -(PORT % 1000)
+            }",
+        );
+
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(values.get(0, "TIMEOUT"), Some(&Value::Number(8.5)));
+        assert_eq!(
+            values.get(0, "URL"),
+            Some(&Value::String("http://localhost:8080".to_string()))
+        );
+        assert_eq!(
+            values.get(0, "SERVER").unwrap().to_string(),
+            "{ url: \"http://localhost:8080\", timeout: 8.5 }"
+        );
+        assert_eq!(values.get(0, "OFFSET"), Some(&Value::Number(-80.0)));
+
+        let names: Vec<&str> = values
+            .module(0)
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "RETRIES",
+                "RETRY_DELAY",
+                "TIMEOUT",
+                "PORT",
+                "URL",
+                "SERVER",
+                "OFFSET"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_forward_references_are_not_folded() {
+        // Name resolution reports uses before the definition, as the
+        // program would fail on them when run
+        let (values, errors) = fold(
+            "module m {
+                const TIMEOUT = DELAY * 2
+                const DELAY = 5
+                const D = TIMEOUT
+            }",
+        );
+
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(
+            values.module(0),
+            &[("DELAY".to_string(), Value::Number(5.0))]
+        );
+    }
+
+    #[test]
+    fn test_cycles() {
+        let (values, errors) = fold(
+            "module m {
+                const A = B
+                const B = { a: A }
+                const C = C
+                const D = 1 + E
+                const E = F
+                const F = \"${D}\"
+                const G = 1
+            }",
+        );
+
+        assert_eq!(
+            errors,
+            vec![
+                ConstError::Cycle {
+                    path: vec!["A".to_string(), "B".to_string(), "A".to_string()],
+                    span: Span::new(2, 27),
+                },
+                ConstError::Cycle {
+                    path: vec!["C".to_string(), "C".to_string()],
+                    span: Span::new(4, 27),
+                },
+                ConstError::Cycle {
+                    path: vec![
+                        "D".to_string(),
+                        "E".to_string(),
+                        "F".to_string(),
+                        "D".to_string()
+                    ],
+                    span: Span::new(5, 31),
+                },
+            ]
+        );
+        assert_eq!(values.module(0), &[("G".to_string(), Value::Number(1.0))]);
+    }
+
+    #[test]
+    fn test_type_errors_are_left_to_the_type_checker() {
+        let (values, errors) = fold(
+            "module m {
+                const o = { a: 1 }
+                const s = \"a\" + 1
+                const t = \"v${o}\"
+                const u = if 1 then 2 else 3
+            }",
+        );
+
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(values.module(0).len(), 1);
+    }
+
+    #[test]
+    fn test_compile_time_errors() {
+        let (_, errors) = fold(
+            "module m {
+                let port = 80
+                const URL = port
+                const RATIO = 1 / (2 - 2)
+                const BIG = 10000000000 * 10000000000
+                const TOO_BIG = BIG * BIG * BIG * BIG * BIG * BIG * BIG * BIG * BIG * BIG * BIG * BIG * BIG * BIG * BIG * BIG
+                const BAD = \"a\" - 1
+            }",
+        );
+
+        assert_eq!(
+            errors[0],
+            ConstError::NotConstant {
+                name: "port".to_string(),
+                span: Span::new(3, 29),
+            }
+        );
+        assert_eq!(
+            errors[1],
+            ConstError::Eval(EvalError::DivisionByZero {
+                span: Span::new(4, 31),
+            })
+        );
+        assert!(matches!(
+            errors[2],
+            ConstError::Eval(EvalError::Overflow { .. })
+        ));
+        // `BAD` is a type error, reported by the type checker
+        assert_eq!(errors.len(), 3);
+    }
+}
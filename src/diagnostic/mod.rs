@@ -0,0 +1,239 @@
+use std::fmt;
+
+use crate::ast::{Program, Span};
+use crate::consteval::{ConstError, ConstEvaluator};
+use crate::mutability::MutabilityChecker;
+use crate::resolve::{ResolveError, Resolver};
+use crate::typeck::TypeChecker;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A message attached to a position in the source
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// A problem report that can be rendered against the source it refers to.
+/// The first label marks the primary position; later labels add context.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>) -> Self {
+        Diagnostic::new(Severity::Error, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Diagnostic::new(Severity::Warning, message)
+    }
+
+    fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity,
+            message: message.into(),
+            labels: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// Returns the primary position of the diagnostic
+    pub fn span(&self) -> Option<Span> {
+        self.labels.first().map(|label| label.span)
+    }
+
+    /// Formats the diagnostic with the source lines its labels point at:
+    ///
+    /// ```text
+    /// error: mismatched types
+    ///  --> config.j:4:21
+    ///   |
+    /// 4 |     let port: Number = "80"
+    ///   |                        ^ expected Number, found String
+    /// ```
+    pub fn render(&self, source: &str, filename: &str) -> String {
+        let lines: Vec<&str> = source.lines().collect();
+        let gutter = self
+            .labels
+            .iter()
+            .map(|label| label.span.line.to_string().len())
+            .max()
+            .unwrap_or(0);
+        let blank = format!("{} |", " ".repeat(gutter));
+
+        let mut out = format!("{}: {}\n", self.severity, self.message);
+        if let Some(span) = self.span() {
+            out += &format!(
+                "{}--> {}:{}:{}\n",
+                " ".repeat(gutter),
+                filename,
+                span.line,
+                span.column
+            );
+        }
+
+        let mut labels: Vec<&Label> = self.labels.iter().collect();
+        labels.sort_by_key(|label| (label.span.line, label.span.column));
+
+        if !labels.is_empty() {
+            out += &blank;
+            out += "\n";
+        }
+        for label in labels {
+            let line = lines
+                .get(label.span.line.wrapping_sub(1))
+                .copied()
+                .unwrap_or("");
+
+            // Keep tabs so the caret lines up with the source as displayed
+            let indent: String = line
+                .chars()
+                .take(label.span.column.saturating_sub(1))
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+
+            out += &format!("{:>gutter$} | {}\n", label.span.line, line);
+            out += &format!("{} {}^ {}\n", blank, indent, label.message);
+        }
+
+        for note in &self.notes {
+            out += &format!("{} = note: {}\n", " ".repeat(gutter), note);
+        }
+
+        out
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)?;
+        if let Some(span) = self.span() {
+            write!(f, " at {}", span)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs every semantic check on `program` and returns their diagnostics, in
+/// the order the checks run
+pub fn check_program(program: &Program) -> Vec<Diagnostic> {
+    let (symbols, resolve_errors) = Resolver::new(program).resolve();
+    let (_, const_errors) = ConstEvaluator::new(program).evaluate();
+
+    // A const cycle starts with a use before the definition, which the
+    // cycle error describes better
+    let cycles: Vec<Span> = const_errors
+        .iter()
+        .filter_map(|err| match err {
+            ConstError::Cycle { span, .. } => Some(*span),
+            _ => None,
+        })
+        .collect();
+    let mut diagnostics: Vec<Diagnostic> = resolve_errors
+        .iter()
+        .filter(|err| {
+            !matches!(err, ResolveError::UsedBeforeDefinition { span, .. } if cycles.contains(span))
+        })
+        .map(|err| err.diagnostic())
+        .collect();
+    diagnostics.extend(
+        TypeChecker::new(program)
+            .check()
+            .iter()
+            .map(|err| err.diagnostic()),
+    );
+    diagnostics.extend(const_errors.iter().map(|err| err.diagnostic()));
+    diagnostics.extend(
+        MutabilityChecker::new(program, &symbols)
+            .check()
+            .iter()
+            .map(|err| err.diagnostic()),
+    );
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_render() {
+        let source = "module m {\n\tlet port: Number = \"80\"\n  const PORT = 8080\n}";
+        let diagnostic = Diagnostic::error("mismatched types")
+            .with_label(Span::new(2, 21), "expected Number, found String")
+            .with_label(Span::new(3, 9), "declared here")
+            .with_note("numbers are not quoted");
+
+        assert_eq!(
+            diagnostic.render(source, "config.j"),
+            "error: mismatched types
+ --> config.j:2:21
+  |
+2 | \tlet port: Number = \"80\"
+  | \t                   ^ expected Number, found String
+3 |   const PORT = 8080
+  |         ^ declared here
+  = note: numbers are not quoted
+"
+        );
+        assert_eq!(
+            diagnostic.to_string(),
+            "error: mismatched types at line 2, column 21"
+        );
+    }
+
+    #[test]
+    fn test_check_program() {
+        let source = "module m {\n    const s = \"a\" + 1\n    let port = 80\n}";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+
+        let diagnostics = check_program(&program);
+        let severities: Vec<Severity> = diagnostics.iter().map(|d| d.severity).collect();
+        assert_eq!(severities, vec![Severity::Error, Severity::Warning]);
+        assert_eq!(diagnostics[1].message, "'port' is never reassigned");
+
+        // A const cycle is reported once, as a cycle
+        let source = "module m {\n    const A = B\n    const B = A\n}";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+
+        let diagnostics = check_program(&program);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "cyclic definition of const 'A'");
+    }
+}
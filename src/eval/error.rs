@@ -0,0 +1,151 @@
+use std::fmt;
+
+use crate::ast::Span;
+use crate::diagnostic::Diagnostic;
+
+/// Represents errors that can occur while evaluating a program
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalError {
+    /// An identifier does not refer to a binding evaluated so far
+    UndefinedVariable { name: String, span: Span },
+
+    /// A value that is not an object was spread into an object literal
+    InvalidSpread { found: &'static str, span: Span },
+
+    /// The condition of an `if` expression is not a boolean
+    InvalidCondition { found: &'static str, span: Span },
+
+    /// No arm of a match expression matches the value
+    NoMatchingArm { value: String, span: Span },
+
+    /// An object was interpolated into a template string
+    InvalidInterpolation { found: &'static str, span: Span },
+
+    /// An operator was applied to a value of the wrong type
+    InvalidOperand {
+        op: String,
+        found: &'static str,
+        span: Span,
+    },
+
+    /// A number was divided by zero, or its remainder taken
+    DivisionByZero { span: Span },
+
+    /// Arithmetic produced a number too large to represent
+    Overflow { span: Span },
+
+    /// An assignment names a field of a value that is not an object
+    InvalidFieldAccess {
+        field: String,
+        found: &'static str,
+        span: Span,
+    },
+
+    /// An assignment names a field the object does not have
+    UnknownField { field: String, span: Span },
+}
+
+impl EvalError {
+    /// Returns the position the error is reported at
+    pub fn span(&self) -> Span {
+        match self {
+            EvalError::UndefinedVariable { span, .. }
+            | EvalError::InvalidSpread { span, .. }
+            | EvalError::InvalidCondition { span, .. }
+            | EvalError::NoMatchingArm { span, .. }
+            | EvalError::InvalidInterpolation { span, .. }
+            | EvalError::InvalidOperand { span, .. }
+            | EvalError::DivisionByZero { span }
+            | EvalError::Overflow { span }
+            | EvalError::InvalidFieldAccess { span, .. }
+            | EvalError::UnknownField { span, .. } => *span,
+        }
+    }
+
+    /// Describes the error with a label at the expression that failed
+    pub fn diagnostic(&self) -> Diagnostic {
+        let span = self.span();
+        match self {
+            EvalError::UndefinedVariable { name, .. } => {
+                Diagnostic::error(format!("undefined variable '{}'", name))
+                    .with_label(span, "not defined before this point")
+            }
+            EvalError::InvalidSpread { found, .. } => {
+                Diagnostic::error("cannot spread a non-object")
+                    .with_label(span, format!("this evaluates to a {}", found))
+            }
+            EvalError::InvalidCondition { found, .. } => {
+                Diagnostic::error("condition is not a Boolean")
+                    .with_label(span, format!("this evaluates to a {}", found))
+            }
+            EvalError::NoMatchingArm { value, .. } => Diagnostic::error("no match arm matches")
+                .with_label(span, format!("no arm for the value {}", value)),
+            EvalError::InvalidInterpolation { found, .. } => {
+                Diagnostic::error("cannot interpolate value")
+                    .with_label(span, format!("this evaluates to a {}", found))
+            }
+            EvalError::InvalidOperand { op, found, .. } => {
+                Diagnostic::error(format!("cannot apply '{}'", op))
+                    .with_label(span, format!("an operand is a {}", found))
+            }
+            EvalError::DivisionByZero { .. } => {
+                Diagnostic::error("division by zero").with_label(span, "the divisor is zero")
+            }
+            EvalError::Overflow { .. } => Diagnostic::error("arithmetic overflow")
+                .with_label(span, "the result is too large to represent"),
+            EvalError::InvalidFieldAccess { field, found, .. } => {
+                Diagnostic::error(format!("cannot set field '{}'", field))
+                    .with_label(span, format!("this is a {}, not an object", found))
+            }
+            EvalError::UnknownField { field, .. } => {
+                Diagnostic::error(format!("cannot set field '{}'", field))
+                    .with_label(span, format!("the object has no field '{}'", field))
+            }
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::UndefinedVariable { name, span } => {
+                write!(f, "Undefined variable '{}' at {}", name, span)
+            }
+            EvalError::InvalidSpread { found, span } => write!(
+                f,
+                "Cannot spread a value of type {} at {}: only objects can be spread",
+                found, span
+            ),
+            EvalError::InvalidCondition { found, span } => write!(
+                f,
+                "Condition at {} must be a Boolean, found {}",
+                span, found
+            ),
+            EvalError::NoMatchingArm { value, span } => {
+                write!(f, "No match arm for value {} at {}", value, span)
+            }
+            EvalError::InvalidInterpolation { found, span } => write!(
+                f,
+                "Cannot interpolate a value of type {} at {}",
+                found, span
+            ),
+            EvalError::InvalidOperand { op, found, span } => write!(
+                f,
+                "Cannot apply '{}' to a value of type {} at {}",
+                op, found, span
+            ),
+            EvalError::DivisionByZero { span } => write!(f, "Division by zero at {}", span),
+            EvalError::Overflow { span } => write!(f, "Arithmetic overflow at {}", span),
+            EvalError::InvalidFieldAccess { field, found, span } => write!(
+                f,
+                "Cannot set field '{}' on a value of type {} at {}",
+                field, found, span
+            ),
+            EvalError::UnknownField { field, span } => {
+                write!(f, "Cannot set missing field '{}' at {}", field, span)
+            }
+        }
+    }
+}
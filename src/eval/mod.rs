@@ -0,0 +1,659 @@
+mod error;
+mod value;
+
+pub use error::EvalError;
+pub use value::Value;
+
+use crate::ast::*;
+use crate::typeck::TypeEnv;
+
+/// Evaluates the bindings of every module in declaration order
+pub struct Interpreter<'a> {
+    program: &'a Program,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        Interpreter { program }
+    }
+
+    /// Evaluates every `let` and `const`, stopping at the first runtime error
+    pub fn run(&self) -> Result<Globals, EvalError> {
+        let mut globals = Globals::default();
+        for module in &self.program.modules {
+            let bindings = self.run_module(module)?;
+            globals.push(module.name.clone(), bindings.0);
+        }
+        Ok(globals)
+    }
+
+    fn run_module(&self, module: &Module) -> Result<Bindings, EvalError> {
+        let types = TypeEnv::new(module);
+        let mut bindings = Bindings::default();
+
+        for statement in &module.statements {
+            match statement {
+                Statement::Let {
+                    name, ty, value, ..
+                }
+                | Statement::Const {
+                    name, ty, value, ..
+                } => {
+                    let value = evaluate(&types, &mut bindings, value, ty.as_ref())?;
+                    bindings.0.push((name.clone(), value));
+                }
+                Statement::TypeDef(_) => {}
+                Statement::Assign {
+                    target,
+                    value,
+                    span,
+                } => {
+                    let value = evaluate(&types, &mut bindings, value, None)?;
+                    bindings.assign(target, value, *span)?;
+                }
+            }
+        }
+
+        Ok(bindings)
+    }
+}
+
+/// The values bound at the top level of each module, with modules and their
+/// bindings in declaration order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Globals {
+    modules: Vec<(String, Vec<(String, Value)>)>,
+}
+
+impl Globals {
+    pub(crate) fn push(&mut self, module: String, bindings: Vec<(String, Value)>) {
+        self.modules.push((module, bindings));
+    }
+
+    /// Returns each module's name with its bindings
+    pub fn modules(&self) -> impl Iterator<Item = (&str, &[(String, Value)])> {
+        self.modules
+            .iter()
+            .map(|(name, bindings)| (name.as_str(), bindings.as_slice()))
+    }
+
+    /// Returns the bindings of the module called `module`
+    pub fn module(&self, module: &str) -> Option<&[(String, Value)]> {
+        self.modules()
+            .find(|(name, _)| *name == module)
+            .map(|(_, bindings)| bindings)
+    }
+
+    pub fn get(&self, module: &str, name: &str) -> Option<&Value> {
+        self.module(module)?
+            .iter()
+            .find(|(binding, _)| binding == name)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Supplies the values of the names an expression refers to
+pub trait Environment {
+    /// Errors a lookup can fail with; evaluation errors convert into them
+    type Error: From<EvalError>;
+
+    fn lookup(&mut self, name: &str, span: Span) -> Result<Value, Self::Error>;
+}
+
+/// The bindings evaluated so far, most recent last
+#[derive(Debug, Default)]
+struct Bindings(Vec<(String, Value)>);
+
+impl Environment for Bindings {
+    type Error = EvalError;
+
+    fn lookup(&mut self, name: &str, span: Span) -> Result<Value, EvalError> {
+        self.0
+            .iter()
+            .rev()
+            .find(|(binding, _)| binding == name)
+            .map(|(_, value)| value.clone())
+            .ok_or_else(|| EvalError::UndefinedVariable {
+                name: name.to_string(),
+                span,
+            })
+    }
+}
+
+impl Bindings {
+    /// Stores `value` in the binding or field named by `target`. Assigning to
+    /// a field an object lacks adds the field.
+    fn assign(&mut self, target: &Place, value: Value, span: Span) -> Result<(), EvalError> {
+        let Some(binding) = self
+            .0
+            .iter_mut()
+            .rev()
+            .find(|(binding, _)| *binding == target.name)
+        else {
+            return Err(EvalError::UndefinedVariable {
+                name: target.name.clone(),
+                span,
+            });
+        };
+
+        assign_field(&mut binding.1, &target.fields, value, span)
+    }
+}
+
+/// Stores `value` in the field of `slot` that `fields` leads to, or in `slot`
+/// itself if `fields` is empty. Every field along the way must already exist.
+pub(crate) fn assign_field(
+    mut slot: &mut Value,
+    fields: &[String],
+    value: Value,
+    span: Span,
+) -> Result<(), EvalError> {
+    for name in fields {
+        let Value::Object(fields) = slot else {
+            return Err(EvalError::InvalidFieldAccess {
+                field: name.clone(),
+                found: slot.type_name(),
+                span,
+            });
+        };
+
+        let Some((_, field)) = fields.iter_mut().find(|(field, _)| field == name) else {
+            return Err(EvalError::UnknownField {
+                field: name.clone(),
+                span,
+            });
+        };
+        slot = field;
+    }
+
+    *slot = value;
+    Ok(())
+}
+
+/// Evaluates `expr`, looking up names in `env`; `expected` is the type it is
+/// annotated with, if any
+pub fn evaluate<E: Environment>(
+    types: &TypeEnv,
+    env: &mut E,
+    expr: &Expr,
+    expected: Option<&Type>,
+) -> Result<Value, E::Error> {
+    match &expr.kind {
+        Expression::NumberLiteral(n) => Ok(Value::Number(*n)),
+        Expression::StringLiteral(s) => Ok(Value::String(s.clone())),
+        Expression::BooleanLiteral(b) => Ok(Value::Bool(*b)),
+        Expression::Identifier(name) => env.lookup(name, expr.span),
+        Expression::Object { entries } => {
+            let record = expected
+                .and_then(|ty| types.record_fields(ty))
+                .unwrap_or_default();
+
+            // Later entries override earlier ones but keep their position
+            let mut values: Vec<(String, Value)> = Vec::new();
+            let mut insert = |name: &str, value: Value| match values
+                .iter_mut()
+                .find(|(existing, _)| existing == name)
+            {
+                Some((_, existing)) => *existing = value,
+                None => values.push((name.to_string(), value)),
+            };
+
+            for entry in entries {
+                match entry {
+                    ObjectEntry::Field { name, value, .. } => {
+                        let field_type = record
+                            .iter()
+                            .find(|field| field.name == *name)
+                            .map(|field| &field.field_type);
+                        insert(name, evaluate(types, env, value, field_type)?);
+                    }
+                    ObjectEntry::Spread(expr) => match evaluate(types, env, expr, None)? {
+                        Value::Object(fields) => {
+                            for (name, value) in fields {
+                                insert(&name, value);
+                            }
+                        }
+                        other => {
+                            return Err(EvalError::InvalidSpread {
+                                found: other.type_name(),
+                                span: expr.span,
+                            }
+                            .into());
+                        }
+                    },
+                }
+            }
+
+            // Fields the literal leaves out take their declared defaults
+            for field in &record {
+                if let Some(default) = &field.default
+                    && !values.iter().any(|(name, _)| *name == field.name)
+                {
+                    let value = evaluate(types, env, default, Some(&field.field_type))?;
+                    values.push((field.name.clone(), value));
+                }
+            }
+
+            Ok(Value::Object(values))
+        }
+        Expression::List(elements) => {
+            let element_type = match expected.map(|ty| types.expand(ty)) {
+                Some(Type::List(element_type)) => Some(*element_type),
+                _ => None,
+            };
+            elements
+                .iter()
+                .map(|element| evaluate(types, env, element, element_type.as_ref()))
+                .collect::<Result<_, _>>()
+                .map(Value::List)
+        }
+        Expression::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => match evaluate(types, env, condition, None)? {
+            Value::Bool(true) => evaluate(types, env, then_branch, expected),
+            Value::Bool(false) => evaluate(types, env, else_branch, expected),
+            other => Err(EvalError::InvalidCondition {
+                found: other.type_name(),
+                span: condition.span,
+            }
+            .into()),
+        },
+        Expression::Match { scrutinee, arms } => {
+            let value = evaluate(types, env, scrutinee, None)?;
+            match arms
+                .iter()
+                .find(|arm| arm.patterns.iter().any(|pattern| value.matches(pattern)))
+            {
+                Some(arm) => evaluate(types, env, &arm.body, expected),
+                None => Err(EvalError::NoMatchingArm {
+                    value: value.to_string(),
+                    span: expr.span,
+                }
+                .into()),
+            }
+        }
+        Expression::Template(parts) => {
+            let mut text = String::new();
+            for part in parts {
+                match part {
+                    Part::Text(s) => text.push_str(s),
+                    Part::Expr(expr) => {
+                        let value = evaluate(types, env, expr, None)?;
+                        text.push_str(&interpolate(value, expr.span)?);
+                    }
+                }
+            }
+            Ok(Value::String(text))
+        }
+        Expression::Unary { op, operand } => {
+            let operand = evaluate(types, env, operand, None)?;
+            Ok(unary(*op, operand, expr.span)?)
+        }
+        Expression::Binary { op, left, right } => {
+            let left = evaluate(types, env, left, None)?;
+            let right = evaluate(types, env, right, None)?;
+            Ok(binary(*op, left, right, expr.span)?)
+        }
+    }
+}
+
+/// Formats a value inserted into a template string
+pub(crate) fn interpolate(value: Value, span: Span) -> Result<String, EvalError> {
+    match value {
+        Value::String(s) => Ok(s),
+        value @ (Value::Number(_) | Value::Bool(_)) => Ok(value.to_string()),
+        other => Err(EvalError::InvalidInterpolation {
+            found: other.type_name(),
+            span,
+        }),
+    }
+}
+
+pub(crate) fn unary(op: UnaryOp, operand: Value, span: Span) -> Result<Value, EvalError> {
+    match (op, operand) {
+        (UnaryOp::Negate, Value::Number(n)) => Ok(Value::Number(-n)),
+        (op, operand) => Err(EvalError::InvalidOperand {
+            op: op.to_string(),
+            found: operand.type_name(),
+            span,
+        }),
+    }
+}
+
+/// Applies an arithmetic operator. Numbers are 64-bit floats, so division by
+/// zero and results too large to represent are reported rather than producing
+/// infinities.
+pub(crate) fn binary(
+    op: BinaryOp,
+    left: Value,
+    right: Value,
+    span: Span,
+) -> Result<Value, EvalError> {
+    let (a, b) = match (op, left, right) {
+        (BinaryOp::Add, Value::String(a), Value::String(b)) => {
+            return Ok(Value::String(a + &b));
+        }
+        (_, Value::Number(a), Value::Number(b)) => (a, b),
+        (op, left, right) => {
+            return Err(EvalError::InvalidOperand {
+                op: op.to_string(),
+                found: if matches!(left, Value::Number(_)) {
+                    right.type_name()
+                } else {
+                    left.type_name()
+                },
+                span,
+            });
+        }
+    };
+
+    if matches!(op, BinaryOp::Divide | BinaryOp::Remainder) && b == 0.0 {
+        return Err(EvalError::DivisionByZero { span });
+    }
+
+    let result = match op {
+        BinaryOp::Add => a + b,
+        BinaryOp::Subtract => a - b,
+        BinaryOp::Multiply => a * b,
+        BinaryOp::Divide => a / b,
+        BinaryOp::Remainder => a % b,
+    };
+
+    if result.is_finite() {
+        Ok(Value::Number(result))
+    } else {
+        Err(EvalError::Overflow { span })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn run(source: &str) -> Result<Vec<(String, Value)>, EvalError> {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        let globals = Interpreter::new(&program).run()?;
+        Ok(globals.modules[0].1.clone())
+    }
+
+    #[test]
+    fn test_evaluates_bindings_in_order() {
+        let bindings = run("module m {
+            const PORT = 8080
+            let server = { host: \"localhost\", port: PORT, tls: false }
+        }")
+        .unwrap();
+
+        assert_eq!(bindings[0], ("PORT".to_string(), Value::Number(8080.0)));
+        assert_eq!(
+            bindings[1].1,
+            Value::Object(vec![
+                ("host".to_string(), Value::String("localhost".to_string())),
+                ("port".to_string(), Value::Number(8080.0)),
+                ("tls".to_string(), Value::Bool(false)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_fills_in_field_defaults() {
+        let bindings = run("module m {
+            const DEFAULT_PORT = 8080
+            type Limits => { connections: Number = 100 }
+            type Server => {
+                host: String,
+                port: Number = DEFAULT_PORT,
+                limits: Limits = { }
+            }
+            let local: Server = { host: \"localhost\" }
+            let public: Server = { host: \"example.com\", port: 443 }
+        }")
+        .unwrap();
+
+        assert_eq!(
+            bindings[1].1,
+            Value::Object(vec![
+                ("host".to_string(), Value::String("localhost".to_string())),
+                ("port".to_string(), Value::Number(8080.0)),
+                (
+                    "limits".to_string(),
+                    Value::Object(vec![("connections".to_string(), Value::Number(100.0))])
+                ),
+            ])
+        );
+        assert_eq!(bindings[2].1.get("port"), Some(&Value::Number(443.0)));
+    }
+
+    #[test]
+    fn test_lists() {
+        let bindings = run("module m {
+            type User => { name: String, admin: Boolean = false }
+            let users: User[] = [{ name: \"ada\" }, { name: \"bob\", admin: true }]
+            let grid = [[1, 2], []]
+        }")
+        .unwrap();
+
+        assert_eq!(
+            bindings[0].1.to_string(),
+            "[{ name: \"ada\", admin: false }, { name: \"bob\", admin: true }]"
+        );
+        assert_eq!(
+            bindings[1].1,
+            Value::List(vec![
+                Value::List(vec![Value::Number(1.0), Value::Number(2.0)]),
+                Value::List(vec![]),
+            ])
+        );
+
+        let err = run("module m {\n  const s = \"${[1]}\"\n}").unwrap_err();
+        assert!(matches!(
+            err,
+            EvalError::InvalidInterpolation { found: "List", .. }
+        ));
+    }
+
+    #[test]
+    fn test_spread_merges_left_to_right() {
+        let bindings = run("module m {
+            const ORIGIN = { x: 0, y: 0, z: 0 }
+            let x = 3
+            let moved = { ...ORIGIN, z: 5, x }
+            let reset = { z: 1, ...ORIGIN }
+        }")
+        .unwrap();
+
+        assert_eq!(
+            bindings[2].1,
+            Value::Object(vec![
+                ("x".to_string(), Value::Number(3.0)),
+                ("y".to_string(), Value::Number(0.0)),
+                ("z".to_string(), Value::Number(5.0)),
+            ])
+        );
+        assert_eq!(
+            bindings[3].1,
+            Value::Object(vec![
+                ("z".to_string(), Value::Number(0.0)),
+                ("x".to_string(), Value::Number(0.0)),
+                ("y".to_string(), Value::Number(0.0)),
+            ])
+        );
+
+        let err = run("module m {\n  let n = 1\n  let o = { ...n }\n}").unwrap_err();
+        assert_eq!(
+            err,
+            EvalError::InvalidSpread {
+                found: "Number",
+                span: Span::new(3, 16),
+            }
+        );
+    }
+
+    #[test]
+    fn test_conditionals_and_match() {
+        let bindings = run("module m {
+            const ENV = \"prod\"
+            let port = match ENV { \"dev\" => 3000, \"prod\" => 443 }
+            let tls = match port { 443 | 8443 => true, _ => false }
+            let server = if tls then { port } else { port: 80 }
+        }")
+        .unwrap();
+
+        assert_eq!(bindings[1].1, Value::Number(443.0));
+        assert_eq!(bindings[2].1, Value::Bool(true));
+        assert_eq!(
+            bindings[3].1,
+            Value::Object(vec![("port".to_string(), Value::Number(443.0))])
+        );
+
+        let err = run("module m {\n  let x = match 1 { 2 => 3 }\n}").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No match arm for value 1 at line 2, column 11"
+        );
+
+        let err = run("module m {\n  let x = if 1 then 2 else 3\n}").unwrap_err();
+        assert_eq!(
+            err,
+            EvalError::InvalidCondition {
+                found: "Number",
+                span: Span::new(2, 14),
+            }
+        );
+    }
+
+    #[test]
+    fn test_template_strings() {
+        let bindings = run(r#"module m {
+            const host = "localhost"
+            const port = 8080.5
+            const tls = false
+            let url = "http://${host}:${port}/api?tls=${tls}&env=${match tls { true => "prod", false => "dev" }}"
+        }"#)
+        .unwrap();
+        assert_eq!(
+            bindings[3].1,
+            Value::String("http://localhost:8080.5/api?tls=false&env=dev".to_string())
+        );
+
+        let err = run("module m {\n  let o = {}\n  let s = \"${o}\"\n}").unwrap_err();
+        assert_eq!(
+            err,
+            EvalError::InvalidInterpolation {
+                found: "Object",
+                span: Span::new(3, 14),
+            }
+        );
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let bindings = run(r#"module m {
+            let a = 1 + 2 * 3 - -4
+            let b = (10 - 4) / 4 % 1
+            let c = "local" + "host"
+        }"#)
+        .unwrap();
+        assert_eq!(bindings[0].1, Value::Number(11.0));
+        assert_eq!(bindings[1].1, Value::Number(0.5));
+        assert_eq!(bindings[2].1, Value::String("localhost".to_string()));
+
+        let err = run("module m {\n  let x = 7 % (1 - 1)\n}").unwrap_err();
+        assert_eq!(
+            err,
+            EvalError::DivisionByZero {
+                span: Span::new(2, 11),
+            }
+        );
+
+        let err = run("module m {\n  let x = 1\n  let y = \"a\" * x\n}").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cannot apply '*' to a value of type String at line 3, column 11"
+        );
+    }
+
+    #[test]
+    fn test_assignment() {
+        let bindings = run("module m {
+            let retries = 3
+            let server = { host: \"localhost\", limits: { connections: 10 } }
+            retries = 5
+            server.limits.connections = retries
+            server.host = \"example.com\"
+        }")
+        .unwrap();
+
+        assert_eq!(bindings[0].1, Value::Number(5.0));
+        assert_eq!(
+            bindings[1].1.to_string(),
+            "{ host: \"example.com\", limits: { connections: 5 } }"
+        );
+
+        // Fields are never made up, at the end of the path or along it
+        for target in ["server.tls", "server.tls.on"] {
+            let source = format!(
+                "module m {{\n  let server = {{ host: \"a\" }}\n  {} = true\n}}",
+                target
+            );
+            let err = run(&source).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "Cannot set missing field 'tls' at line 3, column 3"
+            );
+        }
+
+        let err = run("module m {\n  let n = 1\n  n.x = 2\n}").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cannot set field 'x' on a value of type Number at line 3, column 3"
+        );
+    }
+
+    #[test]
+    fn test_evaluates_every_module() {
+        let tokens = Lexer::new(
+            "module server {
+                const port = 8080
+                let host = \"localhost\"
+            }
+            module client {
+                let retries = 3
+                let port = retries * 2
+            }",
+        )
+        .tokenize()
+        .unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        let globals = Interpreter::new(&program).run().unwrap();
+
+        let modules: Vec<&str> = globals.modules().map(|(name, _)| name).collect();
+        assert_eq!(modules, vec!["server", "client"]);
+        assert_eq!(globals.get("server", "port"), Some(&Value::Number(8080.0)));
+        assert_eq!(globals.get("client", "port"), Some(&Value::Number(6.0)));
+        assert_eq!(globals.get("client", "host"), None);
+        assert_eq!(globals.module("server").unwrap().len(), 2);
+        assert!(globals.module("other").is_none());
+    }
+
+    #[test]
+    fn test_undefined_variable() {
+        let err = run("module m {\n  let x = y\n}").unwrap_err();
+        assert_eq!(
+            err,
+            EvalError::UndefinedVariable {
+                name: "y".to_string(),
+                span: Span::new(2, 11),
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Undefined variable 'y' at line 2, column 11"
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use std::fmt;
+
+use crate::ast::{Literal, Pattern};
+
+/// A runtime value produced by evaluating an expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    String(String),
+    Bool(bool),
+    Object(Vec<(String, Value)>), // Fields keep the order they were written in
+    List(Vec<Value>),
+}
+
+impl Value {
+    /// Returns the name of the jlang type this value belongs to
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Number(_) => "Number",
+            Value::String(_) => "String",
+            Value::Bool(_) => "Boolean",
+            Value::Object(_) => "Object",
+            Value::List(_) => "List",
+        }
+    }
+
+    /// Returns the value of an object field, if this is an object that has it
+    pub fn get(&self, field: &str) -> Option<&Value> {
+        match self {
+            Value::Object(fields) => fields
+                .iter()
+                .find(|(name, _)| name == field)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Returns true if this value is matched by `pattern`
+    pub fn matches(&self, pattern: &Pattern) -> bool {
+        match (pattern, self) {
+            (Pattern::Wildcard, _) => true,
+            (Pattern::Literal(Literal::Number(a)), Value::Number(b)) => a == b,
+            (Pattern::Literal(Literal::String(a)), Value::String(b)) => a == b,
+            (Pattern::Literal(Literal::Boolean(a)), Value::Bool(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(n) => write!(f, "{}", n),
+            Value::String(s) => write!(f, "\"{}\"", s),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Object(fields) if fields.is_empty() => write!(f, "{{}}"),
+            Value::Object(fields) => {
+                write!(f, "{{ ")?;
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", name, value)?;
+                }
+                write!(f, " }}")
+            }
+            Value::List(elements) => {
+                write!(f, "[")?;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_display() {
+        assert_eq!(Value::Number(42.0).to_string(), "42");
+        assert_eq!(Value::Number(0.5).to_string(), "0.5");
+        assert_eq!(Value::String("hi".to_string()).to_string(), "\"hi\"");
+        assert_eq!(Value::Object(vec![]).to_string(), "{}");
+
+        let point = Value::Object(vec![
+            ("x".to_string(), Value::Number(1.0)),
+            ("visible".to_string(), Value::Bool(true)),
+        ]);
+        assert_eq!(point.to_string(), "{ x: 1, visible: true }");
+        assert_eq!(point.get("x"), Some(&Value::Number(1.0)));
+        assert_eq!(point.get("z"), None);
+
+        let list = Value::List(vec![Value::Number(1.0), Value::List(vec![])]);
+        assert_eq!(list.to_string(), "[1, []]");
+    }
+}
@@ -0,0 +1,36 @@
+use std::fmt;
+
+/// Represents values an output format has no way to express
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportError {
+    /// A TOML document is a table, so only objects can be written as one
+    NotATable { found: &'static str },
+}
+
+impl std::error::Error for ExportError {}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::NotATable { found } => write!(
+                f,
+                "A TOML document must be a table, but the value is a {}",
+                found
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_display() {
+        let err = ExportError::NotATable { found: "Number" };
+        assert_eq!(
+            err.to_string(),
+            "A TOML document must be a table, but the value is a Number"
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use crate::ast::Program;
+use crate::eval::{EvalError, Globals, Interpreter, Value};
+use crate::json::{JsonValue, write_json};
+
+/// Evaluates `program` and serializes the bindings of each module to JSON
+pub fn eval_json(program: &Program) -> Result<String, EvalError> {
+    Ok(to_json(&Interpreter::new(program).run()?))
+}
+
+/// Serializes evaluated bindings as one JSON object per module, keyed by
+/// module name. Modules, bindings and object fields keep the order they were
+/// declared in, so the same program always produces the same text.
+pub fn to_json(globals: &Globals) -> String {
+    let modules = globals
+        .modules()
+        .map(|(name, bindings)| {
+            let bindings = bindings
+                .iter()
+                .map(|(name, value)| (name.clone(), JsonValue::from(value)))
+                .collect();
+            (name.to_string(), JsonValue::Object(bindings))
+        })
+        .collect();
+
+    write_json(&JsonValue::Object(modules)) + "\n"
+}
+
+/// Serializes a single value, indented two spaces per level
+pub fn value_to_json(value: &Value) -> String {
+    write_json(&value.into())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn eval(source: &str) -> String {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        eval_json(&program).unwrap()
+    }
+
+    #[test]
+    fn test_eval_json() {
+        let json = eval(
+            "module server {
+                type Limits => { connections: Number = 100, timeout: Number }
+                const port = 8080
+                let host = \"local\" + \"host\"
+                let limits: Limits = { timeout: 2.5 }
+                let tls = { enabled: false, options: {} }
+            }
+            module client {
+                let retries = -3
+                let backoff = [1, 2, []]
+            }",
+        );
+
+        assert_eq!(
+            json,
+            r#"{
+  "server": {
+    "port": 8080,
+    "host": "localhost",
+    "limits": {
+      "timeout": 2.5,
+      "connections": 100
+    },
+    "tls": {
+      "enabled": false,
+      "options": {}
+    }
+  },
+  "client": {
+    "retries": -3,
+    "backoff": [
+      1,
+      2,
+      []
+    ]
+  }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        let value = Value::String("tab\there\nline \\ \u{1}".to_string());
+        assert_eq!(value_to_json(&value), r#""tab\there\nline \\ \u0001""#);
+        assert_eq!(value_to_json(&Value::Object(vec![])), "{}");
+    }
+}
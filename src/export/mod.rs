@@ -0,0 +1,63 @@
+mod error;
+mod json;
+mod toml;
+mod yaml;
+
+pub use error::ExportError;
+pub use json::{eval_json, to_json, value_to_json};
+pub use toml::{to_toml, value_to_toml};
+pub use yaml::{to_yaml, value_to_yaml};
+
+use std::str::FromStr;
+
+use crate::eval::Globals;
+
+/// The text formats evaluated bindings can be exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl Format {
+    /// Writes `globals` in this format, with one top-level entry per module
+    pub fn export(self, globals: &Globals) -> String {
+        match self {
+            Format::Json => to_json(globals),
+            Format::Yaml => to_yaml(globals),
+            Format::Toml => to_toml(globals),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "json" => Ok(Format::Json),
+            "yaml" | "yml" => Ok(Format::Yaml),
+            "toml" => Ok(Format::Toml),
+            _ => Err(format!(
+                "Unknown format '{}'; expected json, yaml or toml",
+                s
+            )),
+        }
+    }
+}
+
+/// Writes a number as an integer when it has no fractional part and can be
+/// represented exactly, and as a float otherwise. Floats always carry a
+/// decimal point or exponent, which TOML requires to tell them apart.
+fn format_number(n: f64) -> String {
+    const MAX_EXACT: f64 = 9_007_199_254_740_992.0; // 2^53
+
+    if n.fract() == 0.0 && n.abs() <= MAX_EXACT {
+        format!("{}", n)
+    } else if n.fract() == 0.0 {
+        format!("{:e}", n)
+    } else {
+        format!("{:?}", n)
+    }
+}
@@ -0,0 +1,209 @@
+use crate::eval::{Globals, Value};
+
+use super::{ExportError, format_number};
+
+/// Serializes evaluated bindings as a TOML document with one table per
+/// module. Objects become tables of their own, written after the plain
+/// values of the table that contains them.
+pub fn to_toml(globals: &Globals) -> String {
+    let mut out = String::new();
+    for (name, bindings) in globals.modules() {
+        write_table(&mut out, &[name], bindings);
+    }
+    out
+}
+
+/// Serializes an object as a TOML document; any other value is an error,
+/// since a TOML document is always a table
+pub fn value_to_toml(value: &Value) -> Result<String, ExportError> {
+    let Value::Object(fields) = value else {
+        return Err(ExportError::NotATable {
+            found: value.type_name(),
+        });
+    };
+
+    let mut out = String::new();
+    write_table(&mut out, &[], fields);
+    Ok(out)
+}
+
+/// Writes the table at `path`: a header, its plain values, then each nested
+/// table. The header is left out where TOML does not need one, which is for
+/// the root table and for tables holding only other tables.
+fn write_table(out: &mut String, path: &[&str], fields: &[(String, Value)]) {
+    let (tables, values): (Vec<_>, Vec<_>) = fields
+        .iter()
+        .partition(|(_, value)| matches!(value, Value::Object(_)));
+
+    if !path.is_empty() && (!values.is_empty() || tables.is_empty()) {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push('[');
+        for (i, name) in path.iter().enumerate() {
+            if i > 0 {
+                out.push('.');
+            }
+            write_key(out, name);
+        }
+        out.push_str("]\n");
+    }
+
+    for (name, value) in values {
+        write_key(out, name);
+        out.push_str(" = ");
+        write_value(out, value);
+        out.push('\n');
+    }
+
+    for (name, value) in tables {
+        if let Value::Object(fields) = value {
+            let mut nested = path.to_vec();
+            nested.push(name);
+            write_table(out, &nested, fields);
+        }
+    }
+}
+
+/// Writes a value on one line. Objects only reach here inside lists, where
+/// they become inline tables.
+fn write_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Number(n) => out.push_str(&format_number(*n)),
+        Value::String(s) => write_string(out, s),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::List(elements) => {
+            out.push('[');
+            for (i, element) in elements.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_value(out, element);
+            }
+            out.push(']');
+        }
+        Value::Object(fields) if fields.is_empty() => out.push_str("{}"),
+        Value::Object(fields) => {
+            out.push_str("{ ");
+            for (i, (name, value)) in fields.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_key(out, name);
+                out.push_str(" = ");
+                write_value(out, value);
+            }
+            out.push_str(" }");
+        }
+    }
+}
+
+/// Writes a key bare when TOML allows it, and quoted otherwise
+fn write_key(out: &mut String, key: &str) {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        out.push_str(key);
+    } else {
+        write_string(out, key);
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::Interpreter;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_to_toml() {
+        let tokens = Lexer::new(
+            "module server {
+                let limits = { timeout: 2.5, retries: { max: 3 } }
+                const port = 8080
+                let host = \"localhost\"
+                let tls = { options: {} }
+                let ratio = 1 / 3
+            }
+            module client {
+                let big = 10000000000 * 10000000000
+                let hosts = [{ name: \"a\", port: 80 }, {}]
+                let matrix = [[1], []]
+            }",
+        )
+        .tokenize()
+        .unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        let globals = Interpreter::new(&program).run().unwrap();
+
+        assert_eq!(
+            to_toml(&globals),
+            "[server]
+port = 8080
+host = \"localhost\"
+ratio = 0.3333333333333333
+
+[server.limits]
+timeout = 2.5
+
+[server.limits.retries]
+max = 3
+
+[server.tls.options]
+
+[client]
+big = 1e20
+hosts = [{ name = \"a\", port = 80 }, {}]
+matrix = [[1], []]
+"
+        );
+    }
+
+    #[test]
+    fn test_value_to_toml() {
+        let value = Value::Object(vec![
+            (
+                "name".to_string(),
+                Value::String("say \"hi\"\n".to_string()),
+            ),
+            ("api key".to_string(), Value::Bool(true)),
+            (
+                "nested".to_string(),
+                Value::Object(vec![("x".to_string(), Value::Number(-0.5))]),
+            ),
+        ]);
+        assert_eq!(
+            value_to_toml(&value).unwrap(),
+            "name = \"say \\\"hi\\\"\\n\"
+\"api key\" = true
+
+[nested]
+x = -0.5
+"
+        );
+
+        assert_eq!(
+            value_to_toml(&Value::Number(1.0)),
+            Err(ExportError::NotATable { found: "Number" })
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use crate::eval::{Globals, Value};
+
+use super::format_number;
+
+/// Serializes evaluated bindings as a YAML mapping from module names to
+/// their bindings, in declaration order, using block style throughout
+pub fn to_yaml(globals: &Globals) -> String {
+    let modules: Vec<(String, Value)> = globals
+        .modules()
+        .map(|(name, bindings)| (name.to_string(), Value::Object(bindings.to_vec())))
+        .collect();
+    value_to_yaml(&Value::Object(modules))
+}
+
+/// Serializes a single value as a YAML document
+pub fn value_to_yaml(value: &Value) -> String {
+    let mut out = String::new();
+    match value {
+        Value::Object(fields) if !fields.is_empty() => write_mapping(&mut out, fields, 0),
+        Value::List(elements) if !elements.is_empty() => write_sequence(&mut out, elements, 0),
+        scalar => {
+            write_scalar(&mut out, scalar);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn write_mapping(out: &mut String, fields: &[(String, Value)], depth: usize) {
+    for (name, value) in fields {
+        out.push_str(&"  ".repeat(depth));
+        write_string(out, name);
+        out.push(':');
+        write_node(out, value, depth);
+    }
+}
+
+fn write_sequence(out: &mut String, elements: &[Value], depth: usize) {
+    for element in elements {
+        out.push_str(&"  ".repeat(depth));
+        out.push('-');
+        write_node(out, element, depth);
+    }
+}
+
+/// Writes the value after a key or a `-` at `depth`: scalars on the same
+/// line, nonempty collections as a block on the lines below
+fn write_node(out: &mut String, value: &Value, depth: usize) {
+    match value {
+        Value::Object(fields) if !fields.is_empty() => {
+            out.push('\n');
+            write_mapping(out, fields, depth + 1);
+        }
+        Value::List(elements) if !elements.is_empty() => {
+            out.push('\n');
+            write_sequence(out, elements, depth + 1);
+        }
+        scalar => {
+            out.push(' ');
+            write_scalar(out, scalar);
+            out.push('\n');
+        }
+    }
+}
+
+/// Writes a value that fits on one line; nonempty collections never reach
+/// here
+fn write_scalar(out: &mut String, value: &Value) {
+    match value {
+        Value::Number(n) => write_number(out, *n),
+        Value::String(s) => write_string(out, s),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::List(_) => out.push_str("[]"),
+        Value::Object(_) => out.push_str("{}"),
+    }
+}
+
+/// Writes a number so that YAML 1.1 readers also load it as one: they only
+/// take exponents after a mantissa with a dot, and with a sign, as in
+/// `1.0e-06`
+fn write_number(out: &mut String, n: f64) {
+    let number = format_number(n);
+    let Some((mantissa, exponent)) = number.split_once('e') else {
+        out.push_str(&number);
+        return;
+    };
+
+    out.push_str(mantissa);
+    if !mantissa.contains('.') {
+        out.push_str(".0");
+    }
+    let (sign, digits) = match exponent.strip_prefix('-') {
+        Some(digits) => ('-', digits),
+        None => ('+', exponent),
+    };
+    out.push_str(&format!("e{}{:0>2}", sign, digits));
+}
+
+/// Writes a string plainly when a YAML reader would read it back as the same
+/// string, and double-quoted otherwise
+fn write_string(out: &mut String, s: &str) {
+    if is_plain(s) {
+        out.push_str(s);
+        return;
+    }
+
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\x{:02x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn is_plain(s: &str) -> bool {
+    // Words that YAML 1.1 or 1.2 readers turn into booleans or null
+    const RESERVED: &[&str] = &[
+        "true", "false", "yes", "no", "on", "off", "y", "n", "null", "~",
+    ];
+
+    let Some(first) = s.chars().next() else {
+        return false;
+    };
+
+    let ambiguous = RESERVED.contains(&s.to_lowercase().as_str())
+        // Anything that could read as a number, including `.inf` and `0x1f`
+        || first.is_ascii_digit()
+        || matches!(first, '-' | '+' | '.')
+        || "?:,[]{}#&*!|>'\"%@`".contains(first)
+        || s.starts_with(char::is_whitespace)
+        || s.ends_with(char::is_whitespace)
+        || s.ends_with(':')
+        || s.contains(": ")
+        || s.contains(" #")
+        || s.contains(char::is_control);
+    !ambiguous
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::Interpreter;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_to_yaml() {
+        let tokens = Lexer::new(
+            "module server {
+                const port = 8080
+                let host = \"localhost\"
+                let ratio = 1 / 3
+                let limits = { timeout: 2.5, retries: { max: 3 } }
+                let tls = { enabled: false, options: {} }
+            }
+            module client {
+                let mode = \"on\"
+                let hosts = [{ name: \"a\", ports: [80, 443] }, {}]
+                let matrix = [[1], []]
+            }",
+        )
+        .tokenize()
+        .unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        let globals = Interpreter::new(&program).run().unwrap();
+
+        assert_eq!(
+            to_yaml(&globals),
+            "server:
+  port: 8080
+  host: localhost
+  ratio: 0.3333333333333333
+  limits:
+    timeout: 2.5
+    retries:
+      max: 3
+  tls:
+    enabled: false
+    options: {}
+client:
+  mode: \"on\"
+  hosts:
+    -
+      name: a
+      ports:
+        - 80
+        - 443
+    - {}
+  matrix:
+    -
+      - 1
+    - []
+"
+        );
+    }
+
+    #[test]
+    fn test_string_quoting() {
+        let yaml = |s: &str| value_to_yaml(&Value::String(s.to_string()));
+
+        assert_eq!(yaml("hello world"), "hello world\n");
+        assert_eq!(yaml("http://localhost:8080"), "http://localhost:8080\n");
+        assert_eq!(yaml(""), "\"\"\n");
+        assert_eq!(yaml("8080"), "\"8080\"\n");
+        assert_eq!(yaml("-1"), "\"-1\"\n");
+        assert_eq!(yaml(".inf"), "\".inf\"\n");
+        assert_eq!(yaml("Yes"), "\"Yes\"\n");
+        assert_eq!(yaml("null"), "\"null\"\n");
+        assert_eq!(yaml("key: value"), "\"key: value\"\n");
+        assert_eq!(yaml("a # comment"), "\"a # comment\"\n");
+        assert_eq!(yaml("*ref"), "\"*ref\"\n");
+        assert_eq!(yaml(" padded"), "\" padded\"\n");
+        assert_eq!(yaml("say \"hi\"\n"), "\"say \\\"hi\\\"\\n\"\n");
+
+        assert_eq!(value_to_yaml(&Value::Object(vec![])), "{}\n");
+    }
+
+    #[test]
+    fn test_exponent_floats() {
+        let yaml = |n: f64| value_to_yaml(&Value::Number(n));
+
+        assert_eq!(yaml(1e-6), "1.0e-06\n");
+        assert_eq!(yaml(-2.5e-7), "-2.5e-07\n");
+        assert_eq!(yaml(12345678901234567000.0), "1.2345678901234567e+19\n");
+        assert_eq!(yaml(1e300), "1.0e+300\n");
+        assert_eq!(yaml(0.001), "0.001\n");
+        assert_eq!(yaml(8080.0), "8080\n");
+    }
+}
@@ -0,0 +1,103 @@
+use std::fmt;
+
+use crate::json::JsonError;
+
+/// Represents JSON documents that have no jlang equivalent
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportError {
+    /// The input is not valid JSON
+    InvalidJson(JsonError),
+
+    /// The document is not an object, so it has no names to bind
+    NotAnObject { found: &'static str },
+
+    /// A null, which jlang has no value for
+    Unsupported {
+        found: &'static str,
+        pointer: String,
+    },
+
+    /// An array that is empty or mixes types, so no `T[]` describes it
+    UntypedArray { pointer: String },
+
+    /// A key that cannot be written as a jlang identifier
+    InvalidName { name: String, pointer: String },
+
+    /// A string containing `"`, which jlang strings cannot hold
+    UnrepresentableString { pointer: String },
+}
+
+impl From<JsonError> for ImportError {
+    fn from(err: JsonError) -> Self {
+        ImportError::InvalidJson(err)
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::InvalidJson(err) => write!(f, "{}", err),
+            ImportError::NotAnObject { found } => write!(
+                f,
+                "Cannot import a JSON {}: the document must be an object",
+                found
+            ),
+            ImportError::Unsupported { found, pointer } => write!(
+                f,
+                "Cannot import the {} at '{}': jlang has no {} values",
+                found, pointer, found
+            ),
+            ImportError::UntypedArray { pointer } => write!(
+                f,
+                "Cannot infer an element type for the array at '{}'",
+                pointer
+            ),
+            ImportError::InvalidName { name, pointer } => write!(
+                f,
+                "Cannot import the key '{}' at '{}': it is not a valid identifier",
+                name, pointer
+            ),
+            ImportError::UnrepresentableString { pointer } => write!(
+                f,
+                "Cannot import the string at '{}': jlang strings cannot contain '\"'",
+                pointer
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_display() {
+        let err = ImportError::Unsupported {
+            found: "null",
+            pointer: "/server/tags".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Cannot import the null at '/server/tags': jlang has no null values"
+        );
+
+        let err = ImportError::UntypedArray {
+            pointer: "/server/tags".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Cannot infer an element type for the array at '/server/tags'"
+        );
+
+        let err = ImportError::InvalidName {
+            name: "api-key".to_string(),
+            pointer: "/api-key".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Cannot import the key 'api-key' at '/api-key': it is not a valid identifier"
+        );
+    }
+}
@@ -0,0 +1,491 @@
+mod error;
+
+pub use error::ImportError;
+
+use crate::ast::*;
+use crate::json::{JsonValue, parse_json, pointer_join};
+
+/// Words the lexer reserves, which cannot name bindings or fields
+const KEYWORDS: &[&str] = &[
+    "module", "type", "const", "let", "extends", "if", "then", "else", "match", "Number", "String",
+    "Boolean", "true", "false",
+];
+
+/// Turns a JSON document into a module that binds each of its top-level keys
+/// as a `const`. With type inference on, every object also gets a `type`
+/// definition describing its shape, and top-level objects, or lists of them,
+/// are annotated with theirs.
+pub struct JsonImporter {
+    module: String,
+    infer_types: bool,
+}
+
+impl JsonImporter {
+    pub fn new(module: impl Into<String>) -> Self {
+        JsonImporter {
+            module: module.into(),
+            infer_types: false,
+        }
+    }
+
+    pub fn infer_types(mut self, infer_types: bool) -> Self {
+        self.infer_types = infer_types;
+        self
+    }
+
+    /// Parses `json` and converts it into a module
+    pub fn import(&self, json: &str) -> Result<Module, ImportError> {
+        self.import_value(&parse_json(json)?)
+    }
+
+    pub fn import_value(&self, document: &JsonValue) -> Result<Module, ImportError> {
+        let JsonValue::Object(members) = document else {
+            return Err(ImportError::NotAnObject {
+                found: document.type_name(),
+            });
+        };
+
+        let mut builder = Builder {
+            infer_types: self.infer_types,
+            next_expr_id: 0,
+            objects_seen: 0,
+            definitions: Vec::new(),
+        };
+
+        let mut bindings = Vec::new();
+        for (name, value) in members {
+            let pointer = pointer_join("", name);
+            check_name(name, &pointer)?;
+            let (value, ty) = builder.expression(value, &pointer, name)?;
+            let ty = ty.filter(names_definition);
+            bindings.push(Statement::Const {
+                name: name.clone(),
+                ty,
+                value,
+                span: Span::default(),
+            });
+        }
+
+        // Definitions are listed in the order their first object appears
+        builder.definitions.sort_by_key(|(position, _)| *position);
+        let mut statements: Vec<Statement> = builder
+            .definitions
+            .into_iter()
+            .map(|(_, def)| Statement::TypeDef(def))
+            .collect();
+        statements.extend(bindings);
+
+        Ok(Module {
+            name: self.module.clone(),
+            statements,
+            span: Span::new(1, 1),
+        })
+    }
+}
+
+struct Builder {
+    infer_types: bool,
+    next_expr_id: usize,
+    objects_seen: usize,
+    definitions: Vec<(usize, TypeDefinition)>, // With the position of the object that introduced them
+}
+
+impl Builder {
+    /// Converts a JSON value into an expression, along with its type when
+    /// types are inferred. `hint` names the type of an object.
+    fn expression(
+        &mut self,
+        value: &JsonValue,
+        pointer: &str,
+        hint: &str,
+    ) -> Result<(Expr, Option<Type>), ImportError> {
+        let (kind, ty) = match value {
+            JsonValue::Bool(b) => (Expression::BooleanLiteral(*b), Type::Boolean),
+            JsonValue::Number(n) if n.is_sign_negative() => {
+                let operand = self.expr(Expression::NumberLiteral(-n));
+                let kind = Expression::Unary {
+                    op: UnaryOp::Negate,
+                    operand: Box::new(operand),
+                };
+                (kind, Type::Number)
+            }
+            JsonValue::Number(n) => (Expression::NumberLiteral(*n), Type::Number),
+            JsonValue::String(s) if s.contains('"') => {
+                return Err(ImportError::UnrepresentableString {
+                    pointer: pointer.to_string(),
+                });
+            }
+            JsonValue::String(s) => (Expression::StringLiteral(s.clone()), Type::String),
+            JsonValue::Object(members) => {
+                let position = self.objects_seen;
+                self.objects_seen += 1;
+
+                let mut entries = Vec::new();
+                let mut fields = Vec::new();
+                for (name, value) in members {
+                    let pointer = pointer_join(pointer, name);
+                    check_name(name, &pointer)?;
+                    let (value, ty) = self.expression(value, &pointer, name)?;
+                    entries.push(ObjectEntry::Field {
+                        name: name.clone(),
+                        value,
+                        span: Span::default(),
+                    });
+                    fields.extend(ty.map(|ty| (name.clone(), ty)));
+                }
+
+                let ty = if self.infer_types {
+                    Type::Custom(self.define(position, hint, fields), Vec::new())
+                } else {
+                    Type::Object(fields)
+                };
+                (Expression::Object { entries }, ty)
+            }
+            JsonValue::Array(elements) => {
+                let mut values = Vec::new();
+                let mut types = Vec::new();
+                for (i, element) in elements.iter().enumerate() {
+                    let pointer = pointer_join(pointer, &i.to_string());
+                    let (value, ty) = self.expression(element, &pointer, hint)?;
+                    values.push(value);
+                    types.extend(ty);
+                }
+
+                // Types are only looked at when they are inferred
+                let element_type = match types.split_first() {
+                    Some((first, rest)) if rest.iter().all(|ty| ty == first) => first.clone(),
+                    _ if self.infer_types => {
+                        return Err(ImportError::UntypedArray {
+                            pointer: pointer.to_string(),
+                        });
+                    }
+                    _ => Type::Union(Vec::new()),
+                };
+                (Expression::List(values), Type::List(Box::new(element_type)))
+            }
+            JsonValue::Null => {
+                return Err(ImportError::Unsupported {
+                    found: value.type_name(),
+                    pointer: pointer.to_string(),
+                });
+            }
+        };
+
+        Ok((self.expr(kind), self.infer_types.then_some(ty)))
+    }
+
+    fn expr(&mut self, kind: Expression) -> Expr {
+        self.next_expr_id += 1;
+        Expr::new(ExprId(self.next_expr_id - 1), kind, Span::default())
+    }
+
+    /// Returns the name of a type definition with exactly these fields,
+    /// adding one named after `hint` if there is none yet
+    fn define(&mut self, position: usize, hint: &str, fields: Vec<(String, Type)>) -> String {
+        let fields: Vec<TypeField> = fields
+            .into_iter()
+            .map(|(name, field_type)| TypeField {
+                name,
+                field_type,
+                default: None,
+                docs: None,
+                span: Span::default(),
+            })
+            .collect();
+
+        if let Some((_, def)) = self
+            .definitions
+            .iter()
+            .find(|(_, def)| def.fields == fields)
+        {
+            return def.name.clone();
+        }
+
+        let base = type_name(hint);
+        let mut name = base.clone();
+        let mut suffix = 1;
+        while self.definitions.iter().any(|(_, def)| def.name == name) {
+            suffix += 1;
+            name = format!("{}{}", base, suffix);
+        }
+
+        self.definitions.push((
+            position,
+            TypeDefinition {
+                name: name.clone(),
+                params: Vec::new(),
+                extends: Vec::new(),
+                fields,
+                alias: None,
+                docs: None,
+                span: Span::default(),
+            },
+        ));
+        name
+    }
+}
+
+/// Returns true if `ty` is a generated definition, or a list of them
+fn names_definition(ty: &Type) -> bool {
+    match ty {
+        Type::Custom(..) => true,
+        Type::List(element) => names_definition(element),
+        _ => false,
+    }
+}
+
+fn check_name(name: &str, pointer: &str) -> Result<(), ImportError> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !KEYWORDS.contains(&name);
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ImportError::InvalidName {
+            name: name.to_string(),
+            pointer: pointer.to_string(),
+        })
+    }
+}
+
+/// Turns a key such as `tls_options` into a type name such as `TlsOptions`
+fn type_name(key: &str) -> String {
+    let mut name: String = key
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, 'T');
+    }
+    if KEYWORDS.contains(&name.as_str()) {
+        name.push_str("Type");
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::Interpreter;
+    use crate::export::to_json;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::printer::print_module;
+    use crate::typeck::TypeChecker;
+
+    const CONFIG: &str = r#"{
+  "server": {
+    "host": "localhost",
+    "port": 8080,
+    "tls_options": {
+      "enabled": false
+    },
+    "limits": {
+      "timeout": -2.5
+    }
+  },
+  "replica": {
+    "host": "replica.local",
+    "port": 8081,
+    "tls_options": {
+      "enabled": true
+    },
+    "limits": {}
+  },
+  "retries": 3,
+  "template": "${name}"
+}
+"#;
+
+    fn parse(source: &str) -> Program {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        Parser::new(tokens).parse().unwrap()
+    }
+
+    #[test]
+    fn test_import_with_types() {
+        let module = JsonImporter::new("config")
+            .infer_types(true)
+            .import(CONFIG)
+            .unwrap();
+        let source = print_module(&module);
+
+        assert_eq!(
+            source,
+            "module config {
+    type Server => {
+        host: String,
+        port: Number,
+        tls_options: TlsOptions,
+        limits: Limits
+    }
+
+    type TlsOptions => {
+        enabled: Boolean
+    }
+
+    type Limits => {
+        timeout: Number
+    }
+
+    type Replica => {
+        host: String,
+        port: Number,
+        tls_options: TlsOptions,
+        limits: Limits2
+    }
+
+    type Limits2 => {}
+
+    const server: Server = {
+        host: \"localhost\",
+        port: 8080,
+        tls_options: {
+            enabled: false
+        },
+        limits: {
+            timeout: -2.5
+        }
+    }
+
+    const replica: Replica = {
+        host: \"replica.local\",
+        port: 8081,
+        tls_options: {
+            enabled: true
+        },
+        limits: {}
+    }
+
+    const retries = 3
+    const template = \"\\${name}\"
+}
+"
+        );
+
+        // The generated module checks cleanly and evaluates back to the input
+        let program = parse(&source);
+        assert!(TypeChecker::new(&program).check().is_empty());
+        let globals = Interpreter::new(&program).run().unwrap();
+        let json = to_json(&globals);
+        assert_eq!(json, format!("{{\n  \"config\": {}}}\n", indent(CONFIG)));
+    }
+
+    /// Indents every line after the first by two spaces, to nest a document
+    fn indent(json: &str) -> String {
+        json.trim_end().replace('\n', "\n  ") + "\n"
+    }
+
+    #[test]
+    fn test_import_without_types() {
+        let module = JsonImporter::new("m")
+            .import(r#"{ "a": { "b": 1 }, "c": true, "d": [1, -2], "e": [] }"#)
+            .unwrap();
+        assert_eq!(
+            print_module(&module),
+            "module m {
+    const a = {
+        b: 1
+    }
+
+    const c = true
+    const d = [1, -2]
+    const e = []
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_import_lists() {
+        let import = |json: &str| JsonImporter::new("m").infer_types(true).import(json);
+
+        let module = import(r#"{ "servers": [{ "host": "a" }, { "host": "b" }] }"#).unwrap();
+        let source = print_module(&module);
+        assert_eq!(
+            source,
+            "module m {
+    type Servers => {
+        host: String
+    }
+
+    const servers: Servers[] = [
+        {
+            host: \"a\"
+        },
+        {
+            host: \"b\"
+        }
+    ]
+}
+"
+        );
+        let program = parse(&source);
+        assert!(TypeChecker::new(&program).check().is_empty());
+
+        for json in [r#"{ "a": [] }"#, r#"{ "a": [1, "x"] }"#] {
+            assert_eq!(
+                import(json).unwrap_err(),
+                ImportError::UntypedArray {
+                    pointer: "/a".to_string(),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_import_errors() {
+        let import = |json: &str| JsonImporter::new("m").import(json).unwrap_err();
+
+        assert_eq!(import("[1]"), ImportError::NotAnObject { found: "array" });
+        assert_eq!(
+            import(r#"{ "a": null }"#),
+            ImportError::Unsupported {
+                found: "null",
+                pointer: "/a".to_string(),
+            }
+        );
+        assert_eq!(
+            import(r#"{ "a": { "api/key": 1 } }"#),
+            ImportError::InvalidName {
+                name: "api/key".to_string(),
+                pointer: "/a/api~1key".to_string(),
+            }
+        );
+        assert_eq!(
+            import(r#"{ "type": 1 }"#),
+            ImportError::InvalidName {
+                name: "type".to_string(),
+                pointer: "/type".to_string(),
+            }
+        );
+        assert_eq!(
+            import(r#"{ "quote": "say \"hi\"" }"#),
+            ImportError::UnrepresentableString {
+                pointer: "/quote".to_string(),
+            }
+        );
+        assert!(matches!(import("{"), ImportError::InvalidJson(_)));
+    }
+
+    #[test]
+    fn test_type_names() {
+        assert_eq!(type_name("tls_options"), "TlsOptions");
+        assert_eq!(type_name("apiKey"), "ApiKey");
+        assert_eq!(type_name("_private"), "Private");
+        assert_eq!(type_name("_"), "T");
+        assert_eq!(type_name("number"), "NumberType");
+    }
+}
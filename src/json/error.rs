@@ -0,0 +1,39 @@
+use std::fmt;
+
+/// Represents malformed JSON text
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl std::error::Error for JsonError {}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid JSON at line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_display() {
+        let err = JsonError {
+            message: "expected ':' after object key".to_string(),
+            line: 2,
+            column: 9,
+        };
+        assert_eq!(
+            err.to_string(),
+            "Invalid JSON at line 2, column 9: expected ':' after object key"
+        );
+    }
+}
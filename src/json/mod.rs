@@ -0,0 +1,474 @@
+mod error;
+mod syntax;
+
+pub use error::JsonError;
+
+use crate::eval::Value;
+
+/// A JSON document. Object members keep the order they were written in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Returns the name JSON gives to the kind of this value
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            JsonValue::Null => "null",
+            JsonValue::Bool(_) => "boolean",
+            JsonValue::Number(_) => "number",
+            JsonValue::String(_) => "string",
+            JsonValue::Array(_) => "array",
+            JsonValue::Object(_) => "object",
+        }
+    }
+}
+
+impl From<&Value> for JsonValue {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Number(n) => JsonValue::Number(*n),
+            Value::String(s) => JsonValue::String(s.clone()),
+            Value::Bool(b) => JsonValue::Bool(*b),
+            Value::Object(fields) => JsonValue::Object(
+                fields
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.into()))
+                    .collect(),
+            ),
+            Value::List(elements) => JsonValue::Array(elements.iter().map(Into::into).collect()),
+        }
+    }
+}
+
+/// Formats a value as JSON text, indented two spaces per level, with object
+/// members in their stored order
+pub fn write_json(value: &JsonValue) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value, 0);
+    out
+}
+
+fn write_value(out: &mut String, value: &JsonValue, depth: usize) {
+    match value {
+        JsonValue::Null => out.push_str("null"),
+        JsonValue::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        // Finite numbers only; neither the parser nor evaluation produces others
+        JsonValue::Number(n) => out.push_str(&n.to_string()),
+        JsonValue::String(s) => write_string(out, s),
+        JsonValue::Array(elements) if elements.is_empty() => out.push_str("[]"),
+        JsonValue::Array(elements) => {
+            out.push('[');
+            for (i, element) in elements.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                newline(out, depth + 1);
+                write_value(out, element, depth + 1);
+            }
+            newline(out, depth);
+            out.push(']');
+        }
+        JsonValue::Object(members) if members.is_empty() => out.push_str("{}"),
+        JsonValue::Object(members) => {
+            out.push('{');
+            for (i, (name, value)) in members.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                newline(out, depth + 1);
+                write_string(out, name);
+                out.push_str(": ");
+                write_value(out, value, depth + 1);
+            }
+            newline(out, depth);
+            out.push('}');
+        }
+    }
+}
+
+fn newline(out: &mut String, depth: usize) {
+    out.push('\n');
+    out.push_str(&"  ".repeat(depth));
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Appends `key` to a JSON pointer (RFC 6901), escaping `~` and `/`
+pub fn pointer_join(pointer: &str, key: &str) -> String {
+    format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"))
+}
+
+/// Parses a complete JSON document. When an object repeats a key, the last
+/// value wins but keeps the position of the first.
+pub fn parse_json(source: &str) -> Result<JsonValue, JsonError> {
+    let mut reader = Reader {
+        chars: source.chars().collect(),
+        current: 0,
+        line: 1,
+        column: 1,
+    };
+
+    reader.skip_whitespace();
+    let value = reader.value()?;
+    reader.skip_whitespace();
+    if reader.current < reader.chars.len() {
+        return Err(reader.error("unexpected text after the document"));
+    }
+    Ok(value)
+}
+
+struct Reader {
+    chars: Vec<char>,
+    current: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Reader {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.current).copied()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.current += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, message: impl Into<String>) -> JsonError {
+        JsonError {
+            message: message.into(),
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn expect(&mut self, expected: char, message: &str) -> Result<(), JsonError> {
+        if self.peek() == Some(expected) {
+            self.advance();
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.advance();
+        }
+    }
+
+    fn value(&mut self) -> Result<JsonValue, JsonError> {
+        match self.peek() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => Ok(JsonValue::String(self.string()?)),
+            Some('-' | '0'..='9') => self.number(),
+            Some('t') => self.keyword("true", JsonValue::Bool(true)),
+            Some('f') => self.keyword("false", JsonValue::Bool(false)),
+            Some('n') => self.keyword("null", JsonValue::Null),
+            Some(c) => Err(self.error(format!("unexpected character '{}'", c))),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn keyword(&mut self, word: &str, value: JsonValue) -> Result<JsonValue, JsonError> {
+        let error = self.error(format!("expected '{}'", word));
+        for expected in word.chars() {
+            if self.advance() != Some(expected) {
+                return Err(error);
+            }
+        }
+        Ok(value)
+    }
+
+    fn object(&mut self) -> Result<JsonValue, JsonError> {
+        self.advance();
+        let mut members: Vec<(String, JsonValue)> = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.advance();
+            return Ok(JsonValue::Object(members));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.error("expected a string key"));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(':', "expected ':' after object key")?;
+            self.skip_whitespace();
+            let value = self.value()?;
+
+            match members.iter_mut().find(|(existing, _)| *existing == key) {
+                Some((_, existing)) => *existing = value,
+                None => members.push((key, value)),
+            }
+
+            self.skip_whitespace();
+            match self.advance() {
+                Some(',') => continue,
+                Some('}') => return Ok(JsonValue::Object(members)),
+                _ => return Err(self.error("expected ',' or '}' in object")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<JsonValue, JsonError> {
+        self.advance();
+        let mut elements = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.advance();
+            return Ok(JsonValue::Array(elements));
+        }
+
+        loop {
+            self.skip_whitespace();
+            elements.push(self.value()?);
+            self.skip_whitespace();
+            match self.advance() {
+                Some(',') => continue,
+                Some(']') => return Ok(JsonValue::Array(elements)),
+                _ => return Err(self.error("expected ',' or ']' in array")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.advance();
+        let mut text = String::new();
+
+        loop {
+            let error = self.error("unterminated string");
+            match self.advance().ok_or(error)? {
+                '"' => return Ok(text),
+                '\\' => {
+                    let escape = self.error("invalid escape sequence");
+                    let c = match self.advance() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.unicode_escape()?,
+                        _ => return Err(escape),
+                    };
+                    text.push(c);
+                }
+                c if c.is_control() => {
+                    return Err(self.error("control characters must be escaped in strings"));
+                }
+                c => text.push(c),
+            }
+        }
+    }
+
+    /// Reads the digits of a `\u` escape, combining surrogate pairs
+    fn unicode_escape(&mut self) -> Result<char, JsonError> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            let error = self.error("unpaired surrogate in \\u escape");
+            if self.advance() != Some('\\') || self.advance() != Some('u') {
+                return Err(error);
+            }
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(error);
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+
+        char::from_u32(code).ok_or_else(|| self.error("unpaired surrogate in \\u escape"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, JsonError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let error = self.error("expected four hex digits after \\u");
+            let digit = self.advance().and_then(|c| c.to_digit(16)).ok_or(error)?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn number(&mut self) -> Result<JsonValue, JsonError> {
+        let start = self.current;
+        let error = self.error("invalid number");
+
+        if self.peek() == Some('-') {
+            self.advance();
+        }
+        match self.peek() {
+            Some('0') => {
+                self.advance();
+            }
+            Some('1'..='9') => self.digits(),
+            _ => return Err(error),
+        }
+        if self.peek() == Some('.') {
+            self.advance();
+            if !matches!(self.peek(), Some('0'..='9')) {
+                return Err(error);
+            }
+            self.digits();
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            self.advance();
+            if matches!(self.peek(), Some('+' | '-')) {
+                self.advance();
+            }
+            if !matches!(self.peek(), Some('0'..='9')) {
+                return Err(error);
+            }
+            self.digits();
+        }
+
+        let text: String = self.chars[start..self.current].iter().collect();
+        match text.parse::<f64>() {
+            Ok(n) if n.is_finite() => Ok(JsonValue::Number(n)),
+            _ => Err(error),
+        }
+    }
+
+    fn digits(&mut self) {
+        while matches!(self.peek(), Some('0'..='9')) {
+            self.advance();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json() {
+        let value = parse_json(
+            r#" {
+                "name": "caf\u00e9 \"\ud83d\ude00\"\n",
+                "port": -80.5e1,
+                "tags": [true, false, null, []],
+                "nested": { "a": 1, "a": 2, "b": {} }
+            } "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            value,
+            JsonValue::Object(vec![
+                (
+                    "name".to_string(),
+                    JsonValue::String("café \"😀\"\n".to_string())
+                ),
+                ("port".to_string(), JsonValue::Number(-805.0)),
+                (
+                    "tags".to_string(),
+                    JsonValue::Array(vec![
+                        JsonValue::Bool(true),
+                        JsonValue::Bool(false),
+                        JsonValue::Null,
+                        JsonValue::Array(vec![]),
+                    ])
+                ),
+                (
+                    "nested".to_string(),
+                    JsonValue::Object(vec![
+                        ("a".to_string(), JsonValue::Number(2.0)),
+                        ("b".to_string(), JsonValue::Object(vec![])),
+                    ])
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        let error = |source: &str| {
+            let err = parse_json(source).unwrap_err();
+            (err.message, err.line, err.column)
+        };
+
+        assert_eq!(
+            error("{\n  \"a\" 1\n}"),
+            ("expected ':' after object key".to_string(), 2, 7)
+        );
+        assert_eq!(
+            error("[1, 2"),
+            ("expected ',' or ']' in array".to_string(), 1, 6)
+        );
+        assert_eq!(
+            error("01"),
+            ("unexpected text after the document".to_string(), 1, 2)
+        );
+        assert_eq!(error("-"), ("invalid number".to_string(), 1, 1));
+        assert_eq!(error("\"abc"), ("unterminated string".to_string(), 1, 5));
+        assert_eq!(
+            error("\"\\x\""),
+            ("invalid escape sequence".to_string(), 1, 3)
+        );
+        assert_eq!(error("nul"), ("expected 'null'".to_string(), 1, 1));
+        assert_eq!(
+            error("{\"a\": 1,}"),
+            ("expected a string key".to_string(), 1, 9)
+        );
+        assert_eq!(error(""), ("unexpected end of input".to_string(), 1, 1));
+    }
+
+    #[test]
+    fn test_write_json() {
+        let source = r#"{
+  "name": "café \"quoted\"\n",
+  "tags": [
+    true,
+    null,
+    [],
+    {}
+  ],
+  "port": -805.5
+}"#;
+        assert_eq!(write_json(&parse_json(source).unwrap()), source);
+    }
+
+    #[test]
+    fn test_pointer_join() {
+        assert_eq!(pointer_join("", "servers"), "/servers");
+        assert_eq!(pointer_join("/servers", "a/b~c"), "/servers/a~1b~0c");
+    }
+}
@@ -0,0 +1,322 @@
+//! JSON forms of tokens, syntax trees and diagnostics, for tools that read
+//! what the compiler finds. Positions are `line` and `column` members of the
+//! object they belong to.
+
+use crate::ast::*;
+use crate::diagnostic::Diagnostic;
+use crate::lexer::{Token, TokenType};
+
+use super::JsonValue;
+
+/// Builds an object from members, leaving out those that are `None`
+fn object<const N: usize>(members: [(&str, Option<JsonValue>); N]) -> JsonValue {
+    JsonValue::Object(
+        members
+            .into_iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value?)))
+            .collect(),
+    )
+}
+
+fn string(s: impl ToString) -> Option<JsonValue> {
+    Some(JsonValue::String(s.to_string()))
+}
+
+fn number(n: usize) -> Option<JsonValue> {
+    Some(JsonValue::Number(n as f64))
+}
+
+fn array<T>(items: &[T], f: impl Fn(&T) -> JsonValue) -> Option<JsonValue> {
+    Some(JsonValue::Array(items.iter().map(f).collect()))
+}
+
+impl From<&Token> for JsonValue {
+    /// `{ "kind": "Identifier", "text": "port", "line": 2, "column": 9 }`
+    fn from(token: &Token) -> Self {
+        let text = match &token.token_type {
+            TokenType::EOF => None,
+            token_type => string(token_type),
+        };
+        object([
+            ("kind", string(token.token_type.kind())),
+            ("text", text),
+            ("line", number(token.line)),
+            ("column", number(token.column)),
+        ])
+    }
+}
+
+impl From<&Diagnostic> for JsonValue {
+    /// The severity, message, primary position, labels and notes
+    fn from(diagnostic: &Diagnostic) -> Self {
+        let span = diagnostic.span();
+        object([
+            ("severity", string(diagnostic.severity)),
+            ("message", string(&diagnostic.message)),
+            ("line", span.and_then(|span| number(span.line))),
+            ("column", span.and_then(|span| number(span.column))),
+            (
+                "labels",
+                array(&diagnostic.labels, |label| {
+                    object([
+                        ("message", string(&label.message)),
+                        ("line", number(label.span.line)),
+                        ("column", number(label.span.column)),
+                    ])
+                }),
+            ),
+            (
+                "notes",
+                array(&diagnostic.notes, |note| JsonValue::String(note.clone())),
+            ),
+        ])
+    }
+}
+
+impl From<&Program> for JsonValue {
+    /// The syntax tree. Nodes are objects whose `kind` tells them apart,
+    /// and types are written as they are in source.
+    fn from(program: &Program) -> Self {
+        object([(
+            "modules",
+            array(&program.modules, |module| {
+                object([
+                    ("name", string(&module.name)),
+                    ("statements", array(&module.statements, statement)),
+                ])
+            }),
+        )])
+    }
+}
+
+fn statement(statement: &Statement) -> JsonValue {
+    match statement {
+        Statement::Let {
+            name,
+            ty,
+            value,
+            span,
+        }
+        | Statement::Const {
+            name,
+            ty,
+            value,
+            span,
+        } => object([
+            (
+                "kind",
+                string(if matches!(statement, Statement::Let { .. }) {
+                    "let"
+                } else {
+                    "const"
+                }),
+            ),
+            ("name", string(name)),
+            ("type", ty.as_ref().and_then(string)),
+            ("value", Some(expression(value))),
+            ("line", number(span.line)),
+            ("column", number(span.column)),
+        ]),
+        Statement::Assign {
+            target,
+            value,
+            span,
+        } => object([
+            ("kind", string("assign")),
+            ("target", string(target)),
+            ("value", Some(expression(value))),
+            ("line", number(span.line)),
+            ("column", number(span.column)),
+        ]),
+        Statement::TypeDef(def) => object([
+            ("kind", string("type")),
+            ("name", string(&def.name)),
+            (
+                "params",
+                array(&def.params, |param| JsonValue::String(param.clone())),
+            ),
+            (
+                "extends",
+                array(&def.extends, |base| JsonValue::String(base.to_string())),
+            ),
+            (
+                "fields",
+                array(&def.fields, |field| {
+                    object([
+                        ("name", string(&field.name)),
+                        ("type", string(&field.field_type)),
+                        ("default", field.default.as_ref().map(expression)),
+                        ("docs", field.docs.as_ref().and_then(string)),
+                        ("line", number(field.span.line)),
+                        ("column", number(field.span.column)),
+                    ])
+                }),
+            ),
+            ("alias", def.alias.as_ref().and_then(string)),
+            ("docs", def.docs.as_ref().and_then(string)),
+            ("line", number(def.span.line)),
+            ("column", number(def.span.column)),
+        ]),
+    }
+}
+
+fn expression(expr: &Expr) -> JsonValue {
+    let position = [
+        ("line", number(expr.span.line)),
+        ("column", number(expr.span.column)),
+    ];
+    let members: Vec<(&str, Option<JsonValue>)> = match &expr.kind {
+        Expression::NumberLiteral(n) => vec![
+            ("kind", string("number")),
+            ("value", Some(JsonValue::Number(*n))),
+        ],
+        Expression::StringLiteral(s) => vec![("kind", string("string")), ("value", string(s))],
+        Expression::BooleanLiteral(b) => vec![
+            ("kind", string("boolean")),
+            ("value", Some(JsonValue::Bool(*b))),
+        ],
+        Expression::Identifier(name) => {
+            vec![("kind", string("identifier")), ("name", string(name))]
+        }
+        Expression::Object { entries } => vec![
+            ("kind", string("object")),
+            (
+                "entries",
+                array(entries, |entry| match entry {
+                    ObjectEntry::Field { name, value, span } => object([
+                        ("kind", string("field")),
+                        ("name", string(name)),
+                        ("value", Some(expression(value))),
+                        ("line", number(span.line)),
+                        ("column", number(span.column)),
+                    ]),
+                    ObjectEntry::Spread(value) => object([
+                        ("kind", string("spread")),
+                        ("value", Some(expression(value))),
+                    ]),
+                }),
+            ),
+        ],
+        Expression::List(elements) => vec![
+            ("kind", string("list")),
+            ("elements", array(elements, expression)),
+        ],
+        Expression::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => vec![
+            ("kind", string("if")),
+            ("condition", Some(expression(condition))),
+            ("then", Some(expression(then_branch))),
+            ("else", Some(expression(else_branch))),
+        ],
+        Expression::Match { scrutinee, arms } => vec![
+            ("kind", string("match")),
+            ("scrutinee", Some(expression(scrutinee))),
+            (
+                "arms",
+                array(arms, |arm| {
+                    object([
+                        ("patterns", array(&arm.patterns, pattern)),
+                        ("body", Some(expression(&arm.body))),
+                        ("line", number(arm.span.line)),
+                        ("column", number(arm.span.column)),
+                    ])
+                }),
+            ),
+        ],
+        Expression::Template(parts) => vec![
+            ("kind", string("template")),
+            (
+                "parts",
+                array(parts, |part| match part {
+                    Part::Text(text) => object([("kind", string("text")), ("value", string(text))]),
+                    Part::Expr(expr) => expression(expr),
+                }),
+            ),
+        ],
+        Expression::Unary { op, operand } => vec![
+            ("kind", string("unary")),
+            ("op", string(op)),
+            ("operand", Some(expression(operand))),
+        ],
+        Expression::Binary { op, left, right } => vec![
+            ("kind", string("binary")),
+            ("op", string(op)),
+            ("left", Some(expression(left))),
+            ("right", Some(expression(right))),
+        ],
+    };
+
+    JsonValue::Object(
+        members
+            .into_iter()
+            .chain(position)
+            .filter_map(|(name, value)| Some((name.to_string(), value?)))
+            .collect(),
+    )
+}
+
+fn pattern(pattern: &Pattern) -> JsonValue {
+    match pattern {
+        Pattern::Wildcard => object([("kind", string("wildcard"))]),
+        Pattern::Literal(literal) => object([
+            ("kind", string("literal")),
+            (
+                "value",
+                Some(match literal {
+                    Literal::Number(n) => JsonValue::Number(*n),
+                    Literal::String(s) => JsonValue::String(s.clone()),
+                    Literal::Boolean(b) => JsonValue::Bool(*b),
+                }),
+            ),
+        ]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::write_json;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_token_json() {
+        let tokens = Lexer::new("let port").tokenize().unwrap();
+        let json: Vec<String> = tokens
+            .iter()
+            .map(|token| write_json(&token.into()).replace(['\n', ' '], ""))
+            .collect();
+
+        assert_eq!(
+            json,
+            vec![
+                r#"{"kind":"Let","text":"let","line":1,"column":1}"#,
+                r#"{"kind":"Identifier","text":"port","line":1,"column":5}"#,
+                r#"{"kind":"EOF","line":1,"column":9}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_program_json() {
+        let tokens = Lexer::new("module m {\n  let x: Number = match y { 1 | _ => -2 }\n}")
+            .tokenize()
+            .unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        let json = write_json(&(&program).into()).replace(['\n', ' '], "");
+
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"modules":[{"name":"m","statements":[{"kind":"let","name":"x","#,
+                r#""type":"Number","value":{"kind":"match","scrutinee":{"kind":"identifier","#,
+                r#""name":"y","line":2,"column":25},"arms":[{"patterns":[{"kind":"literal","#,
+                r#""value":1},{"kind":"wildcard"}],"body":{"kind":"unary","op":"-","operand":"#,
+                r#"{"kind":"number","value":2,"line":2,"column":39},"line":2,"column":38},"#,
+                r#""line":2,"column":29}],"line":2,"column":19},"line":2,"column":7}]}]}"#
+            )
+        );
+    }
+}
@@ -47,9 +47,6 @@ impl fmt::Display for LexerError {
     }
 }
 
-/// Result type for lexer operations
-pub type Result<T> = std::result::Result<T, LexerError>;
-
 #[cfg(test)]
 mod tests {
     use super::*;
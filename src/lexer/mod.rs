@@ -2,7 +2,9 @@ mod error;
 mod token;
 
 pub use error::LexerError;
-pub use token::{Token, TokenType};
+pub use token::{Comment, StringSegment, Token, TokenType};
+
+use crate::ast::Span;
 
 /// Lexer for tokenizing source code.
 /// Tracks position and handles error reporting with line and column information.
@@ -13,6 +15,7 @@ pub struct Lexer {
     current: usize,
     line: usize,
     column: usize,
+    comments: Vec<Comment>, // The ordinary comments skipped so far
 }
 
 impl Lexer {
@@ -24,9 +27,16 @@ impl Lexer {
             current: 0,
             line: 1,
             column: 1,
+            comments: Vec::new(),
         }
     }
 
+    /// Returns each `//` comment skipped so far. They are not tokens, unlike
+    /// doc comments, so they have no place in the syntax tree.
+    pub fn comments(&self) -> &[Comment] {
+        &self.comments
+    }
+
     /// Returns true if we've reached the end of input
     fn is_at_end(&self) -> bool {
         self.current >= self.input.len()
@@ -83,15 +93,22 @@ impl Lexer {
                     self.advance().unwrap();
                 }
                 '\n' => {
+                    self.advance().unwrap();
                     self.line += 1;
                     self.column = 1;
-                    self.advance().unwrap();
                 }
-                '/' if self.peek_next() == '/' => {
+                '/' if self.peek_next() == '/' && !self.at_doc_comment() => {
                     // Skip comments until end of line
+                    let span = Span::new(self.line, self.column);
+                    let start = self.current;
                     while !self.is_at_end() && self.peek() != '\n' {
                         self.advance().unwrap();
                     }
+                    let text: String = self.input[start..self.current].iter().collect();
+                    self.comments.push(Comment {
+                        text: text.trim_end().to_string(),
+                        span,
+                    });
                 }
                 _ => break,
             }
@@ -99,6 +116,30 @@ impl Lexer {
         self.start = self.current;
     }
 
+    /// Returns true if a `///` doc comment starts here. Four or more slashes
+    /// make an ordinary comment, such as a divider line.
+    fn at_doc_comment(&self) -> bool {
+        let at = |offset: usize| self.input.get(self.current + offset).copied();
+        at(0) == Some('/') && at(1) == Some('/') && at(2) == Some('/') && at(3) != Some('/')
+    }
+
+    /// Handles a `///` doc comment, which runs to the end of the line
+    fn doc_comment(&mut self) -> Result<Token, LexerError> {
+        for _ in 0..3 {
+            self.advance()?;
+        }
+        if self.peek() == ' ' {
+            self.advance()?;
+        }
+
+        let mut text = String::new();
+        while !self.is_at_end() && self.peek() != '\n' {
+            text.push(self.advance()?);
+        }
+
+        Ok(self.make_token(TokenType::DocComment(text.trim_end().to_string())))
+    }
+
     /// Creates a token of the given type at current position
     fn make_token(&self, token_type: TokenType) -> Token {
         Token {
@@ -108,14 +149,40 @@ impl Lexer {
         }
     }
 
-    /// Handles string literals
+    /// Handles string literals, splitting out any `${expression}` segments.
+    /// `\${` is the one escape, and stands for a literal `${`.
     fn string(&mut self) -> Result<Token, LexerError> {
+        // Position of the opening quote, which has already been consumed
+        let (line, column) = (self.line, self.column - 1);
+
+        let mut segments = Vec::new();
+        let mut text = String::new();
         while !self.is_at_end() && self.peek() != '"' {
-            if self.peek() == '\n' {
+            if self.peek() == '$' && self.peek_next() == '{' {
+                self.advance()?;
+                self.advance()?;
+                if !text.is_empty() {
+                    segments.push(StringSegment::Text(std::mem::take(&mut text)));
+                }
+                segments.push(StringSegment::Interpolation(self.interpolation()?));
+                continue;
+            }
+            if self.peek() == '\\'
+                && self.peek_next() == '$'
+                && self.input.get(self.current + 2) == Some(&'{')
+            {
+                self.advance()?;
+                text.push(self.advance()?);
+                text.push(self.advance()?);
+                continue;
+            }
+
+            let c = self.advance()?;
+            if c == '\n' {
                 self.line += 1;
                 self.column = 1;
             }
-            self.advance()?;
+            text.push(c);
         }
 
         if self.is_at_end() {
@@ -128,12 +195,43 @@ impl Lexer {
         // Consume the closing quote
         self.advance()?;
 
-        // Get string content (excluding quotes)
-        let content: String = self.input[self.start + 1..self.current - 1]
-            .iter()
-            .collect();
+        let token_type = if segments.is_empty() {
+            TokenType::StringLiteral(text)
+        } else {
+            if !text.is_empty() {
+                segments.push(StringSegment::Text(text));
+            }
+            TokenType::TemplateLiteral(segments)
+        };
+
+        Ok(Token::new(token_type, line, column))
+    }
 
-        Ok(self.make_token(TokenType::StringLiteral(content)))
+    /// Lexes the tokens of a `${...}` segment up to its closing brace, which
+    /// is replaced by an EOF token so the segment can be parsed on its own
+    fn interpolation(&mut self) -> Result<Vec<Token>, LexerError> {
+        let mut tokens = Vec::new();
+        let mut depth = 0;
+
+        loop {
+            let token = self.next_token()?;
+            match token.token_type {
+                TokenType::LeftBrace => depth += 1,
+                TokenType::RightBrace if depth == 0 => {
+                    tokens.push(Token::new(TokenType::EOF, token.line, token.column));
+                    return Ok(tokens);
+                }
+                TokenType::RightBrace => depth -= 1,
+                TokenType::EOF => {
+                    return Err(LexerError::UnterminatedString {
+                        line: self.line,
+                        column: self.column,
+                    });
+                }
+                _ => {}
+            }
+            tokens.push(token);
+        }
     }
 
     /// Handles number literals (both integer and float)
@@ -176,6 +274,11 @@ impl Lexer {
             "type" => TokenType::Type,
             "const" => TokenType::Const,
             "let" => TokenType::Let,
+            "extends" => TokenType::Extends,
+            "if" => TokenType::If,
+            "then" => TokenType::Then,
+            "else" => TokenType::Else,
+            "match" => TokenType::Match,
             "Number" => TokenType::Number,
             "String" => TokenType::String,
             "Boolean" => TokenType::Boolean,
@@ -196,6 +299,9 @@ impl Lexer {
         }
 
         self.start = self.current;
+        if self.at_doc_comment() {
+            return self.doc_comment();
+        }
         let c = self.advance()?;
 
         match c {
@@ -203,6 +309,10 @@ impl Lexer {
             '}' => Ok(self.make_token(TokenType::RightBrace)),
             '(' => Ok(self.make_token(TokenType::LeftParen)),
             ')' => Ok(self.make_token(TokenType::RightParen)),
+            '[' => Ok(self.make_token(TokenType::LeftBracket)),
+            ']' => Ok(self.make_token(TokenType::RightBracket)),
+            '<' => Ok(self.make_token(TokenType::LessThan)),
+            '>' => Ok(self.make_token(TokenType::GreaterThan)),
             ':' => Ok(self.make_token(TokenType::Colon)),
             '=' => {
                 if self.match_char('>') {
@@ -211,8 +321,23 @@ impl Lexer {
                     Ok(self.make_token(TokenType::Equals))
                 }
             }
-            '.' => Ok(self.make_token(TokenType::Dot)),
+            '.' => {
+                if self.peek() == '.' && self.peek_next() == '.' {
+                    self.advance()?;
+                    self.advance()?;
+                    Ok(self.make_token(TokenType::Ellipsis))
+                } else {
+                    Ok(self.make_token(TokenType::Dot))
+                }
+            }
             ',' => Ok(self.make_token(TokenType::Comma)),
+            '&' => Ok(self.make_token(TokenType::Ampersand)),
+            '|' => Ok(self.make_token(TokenType::Pipe)),
+            '+' => Ok(self.make_token(TokenType::Plus)),
+            '-' => Ok(self.make_token(TokenType::Minus)),
+            '*' => Ok(self.make_token(TokenType::Star)),
+            '/' => Ok(self.make_token(TokenType::Slash)),
+            '%' => Ok(self.make_token(TokenType::Percent)),
             '"' => self.string(),
             c if c.is_ascii_digit() => self.number(),
             c if c.is_ascii_alphabetic() || c == '_' => self.identifier(),
@@ -257,15 +382,27 @@ mod tests {
 
     #[test]
     fn test_single_character_tokens() {
-        let mut lexer = Lexer::new("{}():.,");
+        let mut lexer = Lexer::new("{}()[]<>:.,&...|+-*/%");
         let expected = vec![
             TokenType::LeftBrace,
             TokenType::RightBrace,
             TokenType::LeftParen,
             TokenType::RightParen,
+            TokenType::LeftBracket,
+            TokenType::RightBracket,
+            TokenType::LessThan,
+            TokenType::GreaterThan,
             TokenType::Colon,
             TokenType::Dot,
             TokenType::Comma,
+            TokenType::Ampersand,
+            TokenType::Ellipsis,
+            TokenType::Pipe,
+            TokenType::Plus,
+            TokenType::Minus,
+            TokenType::Star,
+            TokenType::Slash,
+            TokenType::Percent,
             TokenType::EOF,
         ];
 
@@ -276,12 +413,17 @@ mod tests {
 
     #[test]
     fn test_keywords() {
-        let mut lexer = Lexer::new("module type const let");
+        let mut lexer = Lexer::new("module type const let extends if then else match");
         let expected = vec![
             TokenType::Module,
             TokenType::Type,
             TokenType::Const,
             TokenType::Let,
+            TokenType::Extends,
+            TokenType::If,
+            TokenType::Then,
+            TokenType::Else,
+            TokenType::Match,
             TokenType::EOF,
         ];
 
@@ -300,6 +442,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_number_literal() {
         let mut lexer = Lexer::new("42 3.14");
 
@@ -323,6 +466,84 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_template_literal() {
+        let mut lexer = Lexer::new(r#""http://${host}:${ports.http}/" "${"${x}"}""#);
+
+        match lexer.next_token().unwrap().token_type {
+            TokenType::TemplateLiteral(segments) => {
+                assert_eq!(segments.len(), 5);
+                assert_eq!(segments[0], StringSegment::Text("http://".to_string()));
+                match &segments[1] {
+                    StringSegment::Interpolation(tokens) => {
+                        assert_eq!(tokens.len(), 2);
+                        assert_eq!(
+                            tokens[0].token_type,
+                            TokenType::Identifier("host".to_string())
+                        );
+                        assert_eq!((tokens[0].line, tokens[0].column), (1, 11));
+                        assert_eq!(tokens[1].token_type, TokenType::EOF);
+                    }
+                    _ => panic!("Expected interpolation"),
+                }
+                assert_eq!(segments[2], StringSegment::Text(":".to_string()));
+                match &segments[3] {
+                    StringSegment::Interpolation(tokens) => assert_eq!(tokens.len(), 4),
+                    _ => panic!("Expected interpolation"),
+                }
+                assert_eq!(segments[4], StringSegment::Text("/".to_string()));
+            }
+            _ => panic!("Expected template literal"),
+        }
+
+        // Strings nest inside interpolations
+        let token = lexer.next_token().unwrap();
+        assert_eq!(token.column, 33);
+        match token.token_type {
+            TokenType::TemplateLiteral(segments) => match &segments[0] {
+                StringSegment::Interpolation(tokens) => {
+                    assert!(matches!(
+                        tokens[0].token_type,
+                        TokenType::TemplateLiteral(_)
+                    ))
+                }
+                _ => panic!("Expected interpolation"),
+            },
+            _ => panic!("Expected template literal"),
+        }
+        assert_eq!(lexer.next_token().unwrap().token_type, TokenType::EOF);
+    }
+
+    #[test]
+    fn test_escaped_interpolation() {
+        let mut lexer = Lexer::new(r#""a\${x}" "\${a}${b}" "C:\ \$" "\\${c}""#);
+
+        assert_eq!(
+            lexer.next_token().unwrap().token_type,
+            TokenType::StringLiteral("a${x}".to_string())
+        );
+        match lexer.next_token().unwrap().token_type {
+            TokenType::TemplateLiteral(segments) => {
+                assert_eq!(segments.len(), 2);
+                assert_eq!(segments[0], StringSegment::Text("${a}".to_string()));
+            }
+            _ => panic!("Expected template literal"),
+        }
+        // Other backslashes are kept as they are
+        assert_eq!(
+            lexer.next_token().unwrap().token_type,
+            TokenType::StringLiteral("C:\\ \\$".to_string())
+        );
+        assert_eq!(
+            lexer.next_token().unwrap().token_type,
+            TokenType::StringLiteral("\\${c}".to_string())
+        );
+        assert_eq!(
+            TokenType::StringLiteral("a${x}".to_string()).to_string(),
+            r#""a\${x}""#
+        );
+    }
+
     #[test]
     fn test_unterminated_string() {
         let mut lexer = Lexer::new("\"unterminated");
@@ -330,6 +551,22 @@ mod tests {
             lexer.next_token().unwrap_err(),
             LexerError::UnterminatedString { .. }
         ));
+
+        let mut lexer = Lexer::new("\"${host\"");
+        assert!(matches!(
+            lexer.next_token().unwrap_err(),
+            LexerError::UnterminatedString { .. }
+        ));
+    }
+
+    #[test]
+    fn test_positions_after_newline() {
+        let mut lexer = Lexer::new("module m {\n  let x = 1\n}");
+        let tokens = lexer.tokenize().unwrap();
+
+        assert_eq!((tokens[3].line, tokens[3].column), (2, 3));
+        assert_eq!((tokens[4].line, tokens[4].column), (2, 7));
+        assert_eq!((tokens[7].line, tokens[7].column), (3, 1));
     }
 
     #[test]
@@ -350,5 +587,34 @@ mod tests {
         for expected_type in expected {
             assert_eq!(lexer.next_token().unwrap().token_type, expected_type);
         }
+        assert_eq!(
+            lexer.comments(),
+            &[Comment {
+                text: "// This is a comment".to_string(),
+                span: Span::new(1, 12),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_doc_comments() {
+        let tokens = Lexer::new("/// A user\n  ///   indented  \n//// divider\n///\ntype")
+            .tokenize()
+            .unwrap();
+        let found: Vec<(TokenType, usize, usize)> = tokens
+            .into_iter()
+            .map(|token| (token.token_type, token.line, token.column))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                (TokenType::DocComment("A user".to_string()), 1, 1),
+                (TokenType::DocComment("  indented".to_string()), 2, 3),
+                (TokenType::DocComment(String::new()), 4, 1),
+                (TokenType::Type, 5, 1),
+                (TokenType::EOF, 5, 5),
+            ]
+        );
     }
 }
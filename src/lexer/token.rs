@@ -1,3 +1,5 @@
+use crate::ast::Span;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenType {
     // Keywords
@@ -5,6 +7,11 @@ pub enum TokenType {
     Type,
     Const,
     Let,
+    Extends,
+    If,
+    Then,
+    Else,
+    Match,
 
     // Types
     Number,
@@ -12,25 +19,60 @@ pub enum TokenType {
     Boolean,
 
     // Symbols
-    LeftBrace,  // {
-    RightBrace, // }
-    LeftParen,  // (
-    RightParen, // )
-    Colon,      // :
-    Arrow,      // =>
-    Equals,     // =
-    Dot,        // .
-    Comma,      // ,
+    LeftBrace,    // {
+    RightBrace,   // }
+    LeftParen,    // (
+    RightParen,   // )
+    LeftBracket,  // [
+    RightBracket, // ]
+    LessThan,     // <
+    GreaterThan,  // >
+    Colon,        // :
+    Arrow,        // =>
+    Equals,       // =
+    Dot,          // .
+    Ellipsis,     // ...
+    Comma,        // ,
+    Ampersand,    // &
+    Pipe,         // |
+    Plus,         // +
+    Minus,        // -
+    Star,         // *
+    Slash,        // /
+    Percent,      // %
 
     // Values
     Identifier(String),
     NumberLiteral(f64),
     StringLiteral(String),
+    TemplateLiteral(Vec<StringSegment>), // A string with `${...}` segments
     BooleanLiteral(bool),
 
+    DocComment(String), // One `///` line, without the slashes and the space after them
+
     EOF,
 }
 
+/// A piece of a template literal
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringSegment {
+    Text(String),
+    /// The tokens between `${` and `}`, ending with an EOF token
+    Interpolation(Vec<Token>),
+}
+
+impl TokenType {
+    /// Returns the name of the variant, such as `Identifier`, without the
+    /// value it holds
+    pub fn kind(&self) -> String {
+        let debug = format!("{:?}", self);
+        match debug.split_once('(') {
+            Some((kind, _)) => kind.to_string(),
+            None => debug,
+        }
+    }
+}
+
 impl std::fmt::Display for TokenType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -38,6 +80,11 @@ impl std::fmt::Display for TokenType {
             TokenType::Type => write!(f, "type"),
             TokenType::Const => write!(f, "const"),
             TokenType::Let => write!(f, "let"),
+            TokenType::Extends => write!(f, "extends"),
+            TokenType::If => write!(f, "if"),
+            TokenType::Then => write!(f, "then"),
+            TokenType::Else => write!(f, "else"),
+            TokenType::Match => write!(f, "match"),
             TokenType::Number => write!(f, "Number"),
             TokenType::String => write!(f, "String"),
             TokenType::Boolean => write!(f, "Boolean"),
@@ -45,22 +92,65 @@ impl std::fmt::Display for TokenType {
             TokenType::RightBrace => write!(f, "}}"),
             TokenType::LeftParen => write!(f, "("),
             TokenType::RightParen => write!(f, ")"),
+            TokenType::LeftBracket => write!(f, "["),
+            TokenType::RightBracket => write!(f, "]"),
+            TokenType::LessThan => write!(f, "<"),
+            TokenType::GreaterThan => write!(f, ">"),
             TokenType::Colon => write!(f, ":"),
             TokenType::Arrow => write!(f, "=>"),
             TokenType::Equals => write!(f, "="),
             TokenType::Dot => write!(f, "."),
+            TokenType::Ellipsis => write!(f, "..."),
             TokenType::Comma => write!(f, ","),
+            TokenType::Ampersand => write!(f, "&"),
+            TokenType::Pipe => write!(f, "|"),
+            TokenType::Plus => write!(f, "+"),
+            TokenType::Minus => write!(f, "-"),
+            TokenType::Star => write!(f, "*"),
+            TokenType::Slash => write!(f, "/"),
+            TokenType::Percent => write!(f, "%"),
             TokenType::Identifier(s) => write!(f, "{}", s),
             TokenType::NumberLiteral(n) => write!(f, "{}", n),
-            TokenType::StringLiteral(s) => write!(f, "\"{}\"", s),
+            TokenType::StringLiteral(s) => write!(f, "\"{}\"", s.replace("${", "\\${")),
+            TokenType::TemplateLiteral(segments) => {
+                write!(f, "\"")?;
+                for segment in segments {
+                    match segment {
+                        StringSegment::Text(text) => write!(f, "{}", text.replace("${", "\\${"))?,
+                        StringSegment::Interpolation(tokens) => {
+                            write!(f, "${{")?;
+                            let inner = tokens
+                                .iter()
+                                .filter(|token| token.token_type != TokenType::EOF);
+                            for (i, token) in inner.enumerate() {
+                                if i > 0 {
+                                    write!(f, " ")?;
+                                }
+                                write!(f, "{}", token.token_type)?;
+                            }
+                            write!(f, "}}")?;
+                        }
+                    }
+                }
+                write!(f, "\"")
+            }
             TokenType::BooleanLiteral(b) => write!(f, "{}", b),
+            TokenType::DocComment(text) => write!(f, "///{}", text),
             TokenType::EOF => write!(f, "EOF"),
         }
     }
 }
 
+/// An ordinary `//` comment, which the lexer skips rather than turning into
+/// a token
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub text: String, // Including the slashes, without trailing whitespace
+    pub span: Span,
+}
+
 /// Represents a token with its type and position information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Token {
     pub token_type: TokenType,
     pub line: usize,
@@ -77,6 +167,11 @@ impl Token {
         }
     }
 
+    /// Returns the source position of this token
+    pub fn span(&self) -> Span {
+        Span::new(self.line, self.column)
+    }
+
     /// Returns true if this token is of the given type
     pub fn is_type(&self, token_type: TokenType) -> bool {
         self.token_type == token_type
@@ -88,6 +183,7 @@ impl Token {
             self.token_type,
             TokenType::NumberLiteral(_)
                 | TokenType::StringLiteral(_)
+                | TokenType::TemplateLiteral(_)
                 | TokenType::BooleanLiteral(_)
         )
     }
@@ -96,7 +192,15 @@ impl Token {
     pub fn is_keyword(&self) -> bool {
         matches!(
             self.token_type,
-            TokenType::Module | TokenType::Type | TokenType::Const | TokenType::Let
+            TokenType::Module
+                | TokenType::Type
+                | TokenType::Const
+                | TokenType::Let
+                | TokenType::Extends
+                | TokenType::If
+                | TokenType::Then
+                | TokenType::Else
+                | TokenType::Match
         )
     }
 }
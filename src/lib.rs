@@ -1,7 +1,37 @@
 pub mod ast;
+pub mod bytecode;
+pub mod codegen;
+pub mod consteval;
+pub mod diagnostic;
+pub mod eval;
+pub mod export;
+pub mod import;
+pub mod json;
 pub mod lexer;
+pub mod mutability;
 pub mod parser;
+pub mod printer;
+pub mod repl;
+pub mod resolve;
+pub mod schema;
+pub mod typeck;
+pub mod validate;
 
 pub use ast::*;
+pub use bytecode::*;
+pub use codegen::*;
+pub use consteval::*;
+pub use diagnostic::*;
+pub use eval::*;
+pub use export::*;
+pub use import::*;
+pub use json::*;
 pub use lexer::*;
+pub use mutability::*;
 pub use parser::*;
+pub use printer::*;
+pub use repl::*;
+pub use resolve::*;
+pub use schema::*;
+pub use typeck::*;
+pub use validate::*;
@@ -1,47 +1,660 @@
 use jlang::*;
 use std::env;
 use std::fs;
+use std::io::{self, Read};
 use std::process;
+use std::str::FromStr;
+
+/// Exit statuses, distinct for each stage so scripts can tell failures apart
+mod status {
+    /// Any other failure, such as an unreadable file or an invalid document
+    pub const FAILURE: i32 = 1;
+    /// Unknown commands, missing arguments or bad flag values
+    pub const USAGE: i32 = 2;
+    pub const LEX: i32 = 3;
+    pub const PARSE: i32 = 4;
+    /// Resolve, type, constant or mutability errors
+    pub const SEMANTIC: i32 = 5;
+    pub const RUNTIME: i32 = 6;
+}
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
-    if args.len() != 2 {
-        eprintln!("Usage: {} <source_file>", args[0]);
-        process::exit(1);
+    match args.get(1).map(String::as_str) {
+        Some("build") => build(&args),
+        Some("check") => check(&args),
+        Some("disasm") if args.len() == 3 => disasm(&args[2]),
+        Some("eval") => eval(&args),
+        Some("fmt") => fmt(&args),
+        Some("import") => import(&args),
+        Some("parse") => parse(&args),
+        Some("repl") if args.len() == 2 => repl(),
+        Some("schema") => schema(&args),
+        Some("tokens") => tokens(&args),
+        Some("validate") => validate(&args),
+        Some("help" | "-h" | "--help") => {
+            print!("{}", usage_text(&args[0]));
+        }
+        _ => usage(&args[0]),
     }
+}
 
-    let filename = &args[1];
-    let source = fs::read_to_string(filename).unwrap_or_else(|err| {
-        eprintln!("Error reading file '{}': {}", filename, err);
-        process::exit(1);
-    });
+fn usage_text(program: &str) -> String {
+    let commands = [
+        "tokens [--format debug|json|pretty] <source_file>",
+        "parse [--format debug|json|pretty] <source_file>",
+        "check [--format debug|json|pretty] <source_file>",
+        "eval [--format debug|json|pretty|yaml|toml] <source_file>",
+        "fmt [--check] <source_file>",
+        "build --target js|rs|ts [--out <dir>] <source_file>",
+        "disasm <source_file>",
+        "import [--types] [--module <name>] <json_file>",
+        "repl",
+        "schema [--module <name>] [--type <Type>] <source_file>",
+        "validate --type <module>.<Type> [--schema <source_file>] <json_file>",
+    ];
+    let mut text = String::new();
+    for (i, command) in commands.iter().enumerate() {
+        let prefix = if i == 0 { "Usage:" } else { "" };
+        text += &format!("{:<6} {} {}\n", prefix, program, command);
+    }
+    text += "\nA file named '-' is read from standard input.\n";
+    text += "\nExit status: 0 success, 1 other failure, 2 usage error, 3 lexer error,\n";
+    text += "4 parse error, 5 semantic error, 6 runtime error\n";
+    text
+}
+
+fn usage(program: &str) -> ! {
+    eprint!("{}", usage_text(program));
+    process::exit(status::USAGE);
+}
+
+/// How `tokens`, `parse`, `check` and `eval` print what they find
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Output {
+    /// Rust's `{:#?}` form of the internal data structures
+    Debug,
+    Json,
+    /// A listing meant for people
+    Pretty,
+}
+
+impl FromStr for Output {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, String> {
+        match s {
+            "debug" => Ok(Output::Debug),
+            "json" => Ok(Output::Json),
+            "pretty" => Ok(Output::Pretty),
+            _ => Err(format!(
+                "Unknown output format '{}'; expected debug, json or pretty",
+                s
+            )),
+        }
+    }
+}
+
+/// Reads the `[--format <output>] <source_file>` arguments the inspecting
+/// commands share
+fn output_args(args: &[String]) -> (Output, &str) {
+    let mut output = Output::Pretty;
+    let mut filename = None;
 
-    // Lexical analysis
+    let mut rest = args[2..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--format" => match rest.next() {
+                Some(value) => output = value.parse().unwrap_or_else(|err| bad_flag(err)),
+                None => usage(&args[0]),
+            },
+            _ if filename.is_none() => filename = Some(arg.as_str()),
+            _ => usage(&args[0]),
+        }
+    }
+    match filename {
+        Some(filename) => (output, filename),
+        None => usage(&args[0]),
+    }
+}
+
+fn bad_flag(err: String) -> ! {
+    eprintln!("{}", err);
+    process::exit(status::USAGE);
+}
+
+/// Prints the tokens of a file
+fn tokens(args: &[String]) {
+    let (output, filename) = output_args(args);
+    let tokens = tokenize(&read(filename));
+
+    match output {
+        Output::Debug => {
+            for token in &tokens {
+                println!("{:?}", token);
+            }
+        }
+        Output::Json => {
+            let tokens = tokens.iter().map(JsonValue::from).collect();
+            println!("{}", write_json(&JsonValue::Array(tokens)));
+        }
+        Output::Pretty => {
+            for token in &tokens {
+                let position = format!("{}:{}", token.line, token.column);
+                let text = match token.token_type {
+                    TokenType::EOF => String::new(),
+                    ref token_type => token_type.to_string(),
+                };
+                let line = format!("{:<8} {:<16} {}", position, token.token_type.kind(), text);
+                println!("{}", line.trim_end());
+            }
+        }
+    }
+}
+
+/// Prints the syntax tree of a file
+fn parse(args: &[String]) {
+    let (output, filename) = output_args(args);
+    let ast = parse_program(tokenize(&read(filename)));
+
+    match output {
+        Output::Debug => println!("{:#?}", ast),
+        Output::Json => println!("{}", write_json(&(&ast).into())),
+        Output::Pretty => print!("{}", print_tree(&ast)),
+    }
+}
+
+/// Runs the semantic checks on a file. Pretty diagnostics go to stderr, the
+/// other formats print them all to stdout.
+fn check(args: &[String]) {
+    let (output, filename) = output_args(args);
+    let source = read(filename);
+    let ast = parse_program(tokenize(&source));
+    let diagnostics = check_program(&ast);
+
+    match output {
+        Output::Debug => println!("{:#?}", diagnostics),
+        Output::Json => {
+            let diagnostics = diagnostics.iter().map(JsonValue::from).collect();
+            println!("{}", write_json(&JsonValue::Array(diagnostics)));
+        }
+        Output::Pretty => {
+            for diagnostic in &diagnostics {
+                eprint!("{}", diagnostic.render(&source, display_name(filename)));
+            }
+        }
+    }
+    if has_errors(&diagnostics) {
+        process::exit(status::SEMANTIC);
+    }
+}
+
+/// Prints a file in the canonical layout, or with `--check`, only tells
+/// whether it already is. `//` comments are kept.
+fn fmt(args: &[String]) {
+    let mut check = false;
+    let mut filename = None;
+
+    for arg in &args[2..] {
+        match arg.as_str() {
+            "--check" => check = true,
+            _ if filename.is_none() => filename = Some(arg.as_str()),
+            _ => usage(&args[0]),
+        }
+    }
+    let Some(filename) = filename else {
+        usage(&args[0]);
+    };
+
+    let source = read(filename);
     let mut lexer = Lexer::new(&source);
-    let tokens = match lexer.tokenize() {
-        Ok(tokens) => tokens,
+    let tokens = lexer.tokenize().unwrap_or_else(|err| {
+        eprintln!("Lexer error: {}", err);
+        process::exit(status::LEX);
+    });
+
+    let formatted = print_program_with_comments(&parse_program(tokens), lexer.comments());
+    if !check {
+        print!("{}", formatted);
+    } else if formatted != source {
+        eprintln!("'{}' is not formatted", display_name(filename));
+        process::exit(status::FAILURE);
+    }
+}
+
+/// Reads entries from the terminal and prints what they evaluate to. An
+/// entry continues over several lines while it has unclosed braces.
+fn repl() {
+    let mut editor = LineEditor::new();
+    let mut session = Session::new();
+    let mut entry = String::new();
+
+    loop {
+        let prompt = if entry.is_empty() {
+            "jlang> "
+        } else {
+            "  ...> "
+        };
+        match editor.read_line(prompt) {
+            Ok(Some(line)) => {
+                editor.add_history(&line);
+                entry += &line;
+                entry.push('\n');
+            }
+            Ok(None) => break,
+            // Ctrl-C abandons the entry
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {
+                entry.clear();
+                continue;
+            }
+            Err(err) => {
+                eprintln!("Error reading input: {}", err);
+                process::exit(status::FAILURE);
+            }
+        }
+        if !Session::is_complete(&entry) {
+            continue;
+        }
+
+        let reply = session.eval(&entry);
+        entry.clear();
+        eprint!("{}", reply.diagnostics);
+        print!("{}", reply.output);
+        if reply.quit {
+            break;
+        }
+    }
+}
+
+/// Checks a file and prints the bytecode it compiles to
+fn disasm(filename: &str) {
+    let source = read(filename);
+    let ast = parse_program(tokenize(&source));
+    check_or_exit(&ast, &source, filename);
+
+    let bytecode = Compiler::new(&ast).compile();
+    for (i, chunk) in bytecode.chunks.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        print!("{}", disassemble(chunk));
+    }
+}
+
+/// Checks and evaluates a file, printing its bindings in the chosen format
+fn eval(args: &[String]) {
+    let mut format = None;
+    let mut filename = None;
+
+    let mut rest = args[2..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--format" => match rest.next() {
+                Some(value) => format = Some(value.as_str()),
+                None => usage(&args[0]),
+            },
+            _ if filename.is_none() => filename = Some(arg.as_str()),
+            _ => usage(&args[0]),
+        }
+    }
+    let Some(filename) = filename else {
+        usage(&args[0]);
+    };
+    // The export formats, plus the debug and pretty forms of the bindings
+    let output = match format {
+        Some("debug") => Some(Output::Debug),
+        Some("pretty") => Some(Output::Pretty),
+        _ => None,
+    };
+    let format = match format {
+        Some(value) if output.is_none() => value.parse().unwrap_or_else(|err| bad_flag(err)),
+        _ => Format::Json,
+    };
+
+    let source = read(filename);
+    let ast = parse_program(tokenize(&source));
+    check_or_exit(&ast, &source, filename);
+    let globals = run(&ast, &source, filename);
+
+    match output {
+        Some(Output::Debug) => println!("{:#?}", globals),
+        Some(Output::Pretty) => {
+            for (module, bindings) in globals.modules() {
+                println!("module {}", module);
+                for (name, value) in bindings {
+                    println!("  {} = {}", name, value);
+                }
+            }
+        }
+        _ => print!("{}", format.export(&globals)),
+    }
+}
+
+/// Compiles a file to another language, writing the files into the `--out`
+/// directory or printing the code
+fn build(args: &[String]) {
+    let mut target = None;
+    let mut out = None;
+    let mut filename = None;
+
+    let mut rest = args[2..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--target" => match rest.next() {
+                Some(value) => {
+                    target = Some(value.parse::<Target>().unwrap_or_else(|err| bad_flag(err)))
+                }
+                None => usage(&args[0]),
+            },
+            "--out" => match rest.next() {
+                Some(value) => out = Some(value),
+                None => usage(&args[0]),
+            },
+            _ if filename.is_none() => filename = Some(arg.as_str()),
+            _ => usage(&args[0]),
+        }
+    }
+    let (Some(target), Some(filename)) = (target, filename) else {
+        usage(&args[0]);
+    };
+
+    let source = read(filename);
+    let ast = parse_program(tokenize(&source));
+    check_or_exit(&ast, &source, filename);
+
+    let name = display_name(filename);
+    let files = target.generate(&ast, name, &source).unwrap_or_else(|err| {
+        eprintln!("{}: {}", name, err);
+        process::exit(status::FAILURE);
+    });
+    match out {
+        Some(out) => {
+            let written = fs::create_dir_all(out).and_then(|()| {
+                files.iter().try_for_each(|file| {
+                    fs::write(std::path::Path::new(out).join(&file.name), &file.contents)
+                })
+            });
+            if let Err(err) = written {
+                eprintln!("Error writing to '{}': {}", out, err);
+                process::exit(status::FAILURE);
+            }
+        }
+        // Source maps are only of use next to the code they describe, so
+        // neither they nor the comments pointing at them are printed
+        None => {
+            for file in files.iter().filter(|file| !file.name.ends_with(".map")) {
+                let link = format!("//# sourceMappingURL={}.map\n", file.name);
+                print!(
+                    "{}",
+                    file.contents.strip_suffix(&link).unwrap_or(&file.contents)
+                );
+            }
+        }
+    }
+}
+
+/// Converts a JSON document into a jlang module and prints its source
+fn import(args: &[String]) {
+    let mut infer_types = false;
+    let mut module = None;
+    let mut filename = None;
+
+    let mut rest = args[2..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--types" => infer_types = true,
+            "--module" => match rest.next() {
+                Some(name) => module = Some(name.clone()),
+                None => usage(&args[0]),
+            },
+            _ if filename.is_none() => filename = Some(arg.as_str()),
+            _ => usage(&args[0]),
+        }
+    }
+    let Some(filename) = filename else {
+        usage(&args[0]);
+    };
+
+    // Name the module after the file unless told otherwise
+    let module = module.unwrap_or_else(|| {
+        let stem = std::path::Path::new(filename)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("");
+        let valid = stem.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && stem.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if valid { stem } else { "imported" }.to_string()
+    });
+
+    let json = read(filename);
+    match JsonImporter::new(module)
+        .infer_types(infer_types)
+        .import(&json)
+    {
+        Ok(module) => print!("{}", print_module(&module)),
         Err(err) => {
-            eprintln!("Lexer error: {}", err);
-            process::exit(1);
+            eprintln!("{}", err);
+            process::exit(status::FAILURE);
+        }
+    }
+}
+
+/// Prints a JSON Schema for the types of a module, or for a single type
+fn schema(args: &[String]) {
+    let mut module_name = None;
+    let mut type_name = None;
+    let mut filename = None;
+
+    let mut rest = args[2..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--module" => match rest.next() {
+                Some(value) => module_name = Some(value),
+                None => usage(&args[0]),
+            },
+            "--type" => match rest.next() {
+                Some(value) => type_name = Some(value),
+                None => usage(&args[0]),
+            },
+            _ if filename.is_none() => filename = Some(arg.as_str()),
+            _ => usage(&args[0]),
         }
+    }
+    let Some(filename) = filename else {
+        usage(&args[0]);
     };
 
-    println!("Tokens:");
-    for token in &tokens {
-        println!("{:?}", token);
+    let source = read(filename);
+    let ast = parse_program(tokenize(&source));
+    check_or_exit(&ast, &source, filename);
+
+    let module = match module_name {
+        Some(name) => ast.modules.iter().find(|module| module.name == *name),
+        None if ast.modules.len() == 1 => ast.modules.first(),
+        None => {
+            eprintln!(
+                "'{}' has {} modules; choose one with --module",
+                filename,
+                ast.modules.len()
+            );
+            process::exit(status::FAILURE);
+        }
+    };
+    let Some(module) = module else {
+        eprintln!(
+            "No module '{}' in '{}'",
+            module_name.map_or("", String::as_str),
+            filename
+        );
+        process::exit(status::FAILURE);
+    };
+
+    let generator = SchemaGenerator::new(module);
+    let document = match type_name {
+        Some(type_name) => match TypeEnv::new(module).lookup(type_name) {
+            Some(def) if def.params.is_empty() => generator.type_schema(def),
+            Some(_) => {
+                eprintln!(
+                    "Type '{}' is generic and has no schema of its own",
+                    type_name
+                );
+                process::exit(status::FAILURE);
+            }
+            None => {
+                eprintln!("No type '{}' in module '{}'", type_name, module.name);
+                process::exit(status::FAILURE);
+            }
+        },
+        None => generator.module_schema(),
+    };
+    println!("{}", write_json(&document));
+}
+
+/// Checks a JSON document against a type definition. The definition is
+/// looked up in the schema file, which defaults to `<module>.j`.
+fn validate(args: &[String]) {
+    let mut target = None;
+    let mut schema = None;
+    let mut filename = None;
+
+    let mut rest = args[2..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--type" => match rest.next() {
+                Some(value) => target = Some(value),
+                None => usage(&args[0]),
+            },
+            "--schema" => match rest.next() {
+                Some(value) => schema = Some(value.clone()),
+                None => usage(&args[0]),
+            },
+            _ if filename.is_none() => filename = Some(arg.as_str()),
+            _ => usage(&args[0]),
+        }
     }
+    let (Some(target), Some(filename)) = (target, filename) else {
+        usage(&args[0]);
+    };
+    let Some((module_name, type_name)) = target.split_once('.') else {
+        eprintln!(
+            "Expected a type of the form <module>.<Type>, found '{}'",
+            target
+        );
+        process::exit(status::USAGE);
+    };
 
-    // Parsing
+    let schema = schema.unwrap_or_else(|| format!("{}.j", module_name));
+    let source = read(&schema);
+    let ast = parse_program(tokenize(&source));
+    check_or_exit(&ast, &source, &schema);
+
+    let Some(module) = ast.modules.iter().find(|module| module.name == module_name) else {
+        eprintln!("No module '{}' in '{}'", module_name, schema);
+        process::exit(status::FAILURE);
+    };
+    let validator = Validator::new(module);
+    let def = match TypeEnv::new(module).lookup(type_name) {
+        Some(def) if def.params.is_empty() => def,
+        Some(_) => {
+            eprintln!(
+                "Type '{}' is generic and cannot be validated against",
+                target
+            );
+            process::exit(status::FAILURE);
+        }
+        None => {
+            eprintln!("No type '{}' in module '{}'", type_name, module_name);
+            process::exit(status::FAILURE);
+        }
+    };
+
+    let document = match parse_json(&read(filename)) {
+        Ok(document) => document,
+        Err(err) => {
+            eprintln!("{}: {}", display_name(filename), err);
+            process::exit(status::FAILURE);
+        }
+    };
+
+    let errors = validator.validate(def, &document);
+    for err in &errors {
+        eprintln!("{}: {}", display_name(filename), err);
+    }
+    if !errors.is_empty() {
+        process::exit(status::FAILURE);
+    }
+}
+
+/// Reads a file, or all of stdin when the name is `-`
+fn read(filename: &str) -> String {
+    let result = if filename == "-" {
+        let mut source = String::new();
+        io::stdin().read_to_string(&mut source).map(|_| source)
+    } else {
+        fs::read_to_string(filename)
+    };
+    result.unwrap_or_else(|err| {
+        eprintln!("Error reading file '{}': {}", display_name(filename), err);
+        process::exit(status::FAILURE);
+    })
+}
+
+/// The name diagnostics use for a file
+fn display_name(filename: &str) -> &str {
+    if filename == "-" { "<stdin>" } else { filename }
+}
+
+fn tokenize(source: &str) -> Vec<Token> {
+    let mut lexer = Lexer::new(source);
+    match lexer.tokenize() {
+        Ok(tokens) => tokens,
+        Err(err) => {
+            eprintln!("Lexer error: {}", err);
+            process::exit(status::LEX);
+        }
+    }
+}
+
+fn parse_program(tokens: Vec<Token>) -> Program {
     let mut parser = Parser::new(tokens);
-    let ast = match parser.parse() {
+    match parser.parse() {
         Ok(ast) => ast,
         Err(err) => {
-            eprintln!("Parser error: {:?}", err);
-            process::exit(1);
+            eprintln!("Parser error: {}", err);
+            process::exit(status::PARSE);
         }
-    };
+    }
+}
 
-    println!("\nAST:");
-    println!("{:#?}", ast);
+/// Runs the semantic checks, printing their diagnostics and exiting if any
+/// of them is an error
+fn check_or_exit(ast: &Program, source: &str, filename: &str) {
+    let diagnostics = check_program(ast);
+    for diagnostic in &diagnostics {
+        eprint!("{}", diagnostic.render(source, display_name(filename)));
+    }
+    if has_errors(&diagnostics) {
+        process::exit(status::SEMANTIC);
+    }
+}
+
+fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+}
+
+fn run(ast: &Program, source: &str, filename: &str) -> Globals {
+    let bytecode = Compiler::new(ast).compile();
+    match Vm::new(&bytecode).run() {
+        Ok(globals) => globals,
+        Err(err) => {
+            eprint!(
+                "{}",
+                err.diagnostic().render(source, display_name(filename))
+            );
+            process::exit(status::RUNTIME);
+        }
+    }
 }
@@ -0,0 +1,134 @@
+use std::fmt;
+
+use crate::ast::Span;
+use crate::diagnostic::Diagnostic;
+
+/// Represents writes that break a binding's declared mutability
+#[derive(Debug, Clone, PartialEq)]
+pub enum MutabilityError {
+    /// A `const` binding is assigned a new value
+    ConstReassignment {
+        name: String,
+        declared: Span,
+        span: Span,
+    },
+
+    /// A field of a `const` binding is assigned a new value
+    ConstFieldMutation {
+        name: String,
+        target: String, // The full path written to, such as `server.port`
+        declared: Span,
+        span: Span,
+    },
+
+    /// Warning: a `let` binding is never written to and could be a `const`
+    NeverReassigned { name: String, span: Span },
+}
+
+impl MutabilityError {
+    /// Returns the position the error is reported at
+    pub fn span(&self) -> Span {
+        match self {
+            MutabilityError::ConstReassignment { span, .. }
+            | MutabilityError::ConstFieldMutation { span, .. }
+            | MutabilityError::NeverReassigned { span, .. } => *span,
+        }
+    }
+
+    /// Returns true for problems that do not stop the program from running
+    pub fn is_warning(&self) -> bool {
+        matches!(self, MutabilityError::NeverReassigned { .. })
+    }
+
+    /// Describes the error with labels on both the write and the declaration
+    pub fn diagnostic(&self) -> Diagnostic {
+        match self {
+            MutabilityError::ConstReassignment {
+                name,
+                declared,
+                span,
+            } => Diagnostic::error(format!("cannot assign to const '{}'", name))
+                .with_label(*span, "cannot assign twice to a const")
+                .with_label(*declared, format!("'{}' is declared as const here", name))
+                .with_note(format!(
+                    "declare '{}' with 'let' if it needs to change",
+                    name
+                )),
+            MutabilityError::ConstFieldMutation {
+                name,
+                target,
+                declared,
+                span,
+            } => Diagnostic::error(format!("cannot assign to '{}'", target))
+                .with_label(*span, format!("'{}' is a field of a const", target))
+                .with_label(*declared, format!("'{}' is declared as const here", name)),
+            MutabilityError::NeverReassigned { name, span } => {
+                Diagnostic::warning(format!("'{}' is never reassigned", name))
+                    .with_label(*span, "declared with 'let'")
+                    .with_note("declare it with 'const' instead")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MutabilityError {}
+
+impl fmt::Display for MutabilityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MutabilityError::ConstReassignment {
+                name,
+                declared,
+                span,
+            } => write!(
+                f,
+                "Cannot assign to const '{}' at {}; declared at {}",
+                name, span, declared
+            ),
+            MutabilityError::ConstFieldMutation {
+                target,
+                declared,
+                span,
+                ..
+            } => write!(
+                f,
+                "Cannot assign to '{}' at {}: it belongs to a const declared at {}",
+                target, span, declared
+            ),
+            MutabilityError::NeverReassigned { name, span } => write!(
+                f,
+                "Binding '{}' at {} is never reassigned and could be a const",
+                name, span
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_display() {
+        let err = MutabilityError::ConstReassignment {
+            name: "PORT".to_string(),
+            declared: Span::new(2, 11),
+            span: Span::new(5, 5),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Cannot assign to const 'PORT' at line 5, column 5; declared at line 2, column 11"
+        );
+
+        let err = MutabilityError::ConstFieldMutation {
+            name: "server".to_string(),
+            target: "server.port".to_string(),
+            declared: Span::new(2, 11),
+            span: Span::new(3, 5),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Cannot assign to 'server.port' at line 3, column 5: it belongs to a const declared at line 2, column 11"
+        );
+    }
+}
@@ -0,0 +1,168 @@
+mod error;
+
+pub use error::MutabilityError;
+
+use std::collections::HashSet;
+
+use crate::ast::*;
+use crate::resolve::{DefId, DefKind, Namespace, SymbolTable};
+
+/// Checks assignments against the `let` or `const` their target was declared with
+pub struct MutabilityChecker<'a> {
+    program: &'a Program,
+    symbols: &'a SymbolTable,
+}
+
+impl<'a> MutabilityChecker<'a> {
+    /// `symbols` must come from resolving `program`
+    pub fn new(program: &'a Program, symbols: &'a SymbolTable) -> Self {
+        MutabilityChecker { program, symbols }
+    }
+
+    /// Reports writes to consts, then `let` bindings that are never written
+    pub fn check(&self) -> Vec<MutabilityError> {
+        let mut errors = Vec::new();
+        let mut written: HashSet<DefId> = HashSet::new();
+
+        for module in &self.program.modules {
+            for statement in &module.statements {
+                let Statement::Assign { target, span, .. } = statement else {
+                    continue;
+                };
+                // Unresolved targets are reported by name resolution
+                let Some(id) = self.symbols.reference_at(*span) else {
+                    continue;
+                };
+
+                let definition = self.symbols.definition(id);
+                match definition.kind {
+                    DefKind::Const if target.fields.is_empty() => {
+                        errors.push(MutabilityError::ConstReassignment {
+                            name: target.name.clone(),
+                            declared: definition.span,
+                            span: *span,
+                        })
+                    }
+                    DefKind::Const => errors.push(MutabilityError::ConstFieldMutation {
+                        name: target.name.clone(),
+                        target: target.to_string(),
+                        declared: definition.span,
+                        span: *span,
+                    }),
+                    _ => {
+                        written.insert(id);
+                    }
+                }
+            }
+        }
+
+        for (index, module) in self.program.modules.iter().enumerate() {
+            for statement in &module.statements {
+                let Statement::Let { name, span, .. } = statement else {
+                    continue;
+                };
+                // Duplicates of an earlier definition are reported by name resolution
+                let Some(id) = self.symbols.scope(index).lookup(Namespace::Value, name) else {
+                    continue;
+                };
+
+                if self.symbols.definition(id).span == *span && !written.contains(&id) {
+                    errors.push(MutabilityError::NeverReassigned {
+                        name: name.clone(),
+                        span: *span,
+                    });
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::resolve::Resolver;
+
+    fn check(source: &str) -> Vec<MutabilityError> {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        let (symbols, errors) = Resolver::new(&program).resolve();
+        assert!(errors.is_empty(), "{:?}", errors);
+        MutabilityChecker::new(&program, &symbols).check()
+    }
+
+    #[test]
+    fn test_const_writes() {
+        let errors = check(
+            "module m {
+                const PORT = 8080
+                const server = { host: \"localhost\", port: PORT }
+                let retries = 3
+                let config = { debug: false }
+                PORT = 443
+                server.port = 443
+                retries = 5
+                config.debug = true
+            }",
+        );
+
+        assert_eq!(
+            errors,
+            vec![
+                MutabilityError::ConstReassignment {
+                    name: "PORT".to_string(),
+                    declared: Span::new(2, 23),
+                    span: Span::new(6, 17),
+                },
+                MutabilityError::ConstFieldMutation {
+                    name: "server".to_string(),
+                    target: "server.port".to_string(),
+                    declared: Span::new(3, 23),
+                    span: Span::new(7, 17),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_never_reassigned() {
+        let errors = check(
+            "module m {
+                let host = \"localhost\"
+                let port = 80
+                port = 443
+            }",
+        );
+
+        assert_eq!(
+            errors,
+            vec![MutabilityError::NeverReassigned {
+                name: "host".to_string(),
+                span: Span::new(2, 21),
+            }]
+        );
+        assert!(errors[0].is_warning());
+    }
+
+    #[test]
+    fn test_diagnostic_points_at_declaration_and_write() {
+        let source = "module m {\n    const PORT = 8080\n    PORT = 443\n}";
+        let errors = check(source);
+
+        assert_eq!(
+            errors[0].diagnostic().render(source, "m.j"),
+            "error: cannot assign to const 'PORT'
+ --> m.j:3:5
+  |
+2 |     const PORT = 8080
+  |           ^ 'PORT' is declared as const here
+3 |     PORT = 443
+  |     ^ cannot assign twice to a const
+  = note: declare 'PORT' with 'let' if it needs to change
+"
+        );
+    }
+}
@@ -1,17 +1,44 @@
 mod error;
 pub use error::{ParseError, Result};
 
+use std::collections::HashMap;
+
 use crate::ast::*;
-use crate::lexer::{Token, TokenType};
+use crate::lexer::{StringSegment, Token, TokenType};
 
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    next_expr_id: usize, // Expressions are numbered in the order they start
+    docs: HashMap<usize, String>, // Doc comments, keyed by the index of the token they precede
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Parser { tokens, current: 0 }
+        // Doc comments can sit between any two tokens, so they are set aside
+        // here and picked up by the definitions they document
+        let mut docs = HashMap::new();
+        let mut pending: Vec<String> = Vec::new();
+        let mut kept = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            match token.token_type {
+                TokenType::DocComment(text) => pending.push(text),
+                _ => {
+                    if !pending.is_empty() {
+                        docs.insert(kept.len(), pending.join("\n"));
+                        pending.clear();
+                    }
+                    kept.push(token);
+                }
+            }
+        }
+
+        Parser {
+            tokens: kept,
+            current: 0,
+            next_expr_id: 0,
+            docs,
+        }
     }
 
     pub fn parse(&mut self) -> Result<Program> {
@@ -35,6 +62,8 @@ impl Parser {
     }
 
     fn parse_module(&mut self) -> Result<Module> {
+        let span = self.previous().span();
+
         // Parse module name
         let name = match &self.advance().token_type {
             TokenType::Identifier(name) => name.clone(),
@@ -49,7 +78,7 @@ impl Parser {
         };
 
         // Expect opening brace
-        self.consume(&TokenType::LeftBrace, "Expected '{' after module name")?;
+        self.consume(&TokenType::LeftBrace)?;
 
         let mut statements = Vec::new();
 
@@ -59,9 +88,13 @@ impl Parser {
         }
 
         // Consume the closing brace
-        self.consume(&TokenType::RightBrace, "Expected '}' after module body")?;
+        self.consume(&TokenType::RightBrace)?;
 
-        Ok(Module { name, statements })
+        Ok(Module {
+            name,
+            statements,
+            span,
+        })
     }
 
     fn parse_statement(&mut self) -> Result<Statement> {
@@ -71,9 +104,11 @@ impl Parser {
             self.parse_const_statement()
         } else if self.match_token(&TokenType::Type) {
             self.parse_type_definition()
+        } else if matches!(self.peek().token_type, TokenType::Identifier(_)) {
+            self.parse_assignment()
         } else {
             Err(ParseError::UnexpectedToken {
-                expected: "let, const, or type".to_string(),
+                expected: "let, const, type, or assignment".to_string(),
                 found: format!("{:?}", self.peek().token_type),
                 line: self.peek().line,
                 column: self.peek().column,
@@ -82,6 +117,7 @@ impl Parser {
     }
 
     fn parse_let_statement(&mut self) -> Result<Statement> {
+        let span = self.peek().span();
         let name = match &self.advance().token_type {
             TokenType::Identifier(name) => name.clone(),
             _ => {
@@ -94,14 +130,45 @@ impl Parser {
             }
         };
 
-        self.consume(&TokenType::Equals, "Expected '=' after variable name")?;
+        let ty = if self.match_token(&TokenType::Colon) {
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+
+        self.consume(&TokenType::Equals)?;
 
-        let value = Box::new(self.parse_expression()?);
+        let value = self.parse_expression()?;
 
-        Ok(Statement::Let { name, value })
+        Ok(Statement::Let {
+            name,
+            ty,
+            value,
+            span,
+        })
+    }
+
+    fn parse_assignment(&mut self) -> Result<Statement> {
+        let span = self.peek().span();
+        let name = self.parse_identifier()?;
+
+        let mut fields = Vec::new();
+        while self.match_token(&TokenType::Dot) {
+            fields.push(self.parse_identifier()?);
+        }
+
+        self.consume(&TokenType::Equals)?;
+        let value = self.parse_expression()?;
+
+        Ok(Statement::Assign {
+            target: Place { name, fields },
+            value,
+            span,
+        })
     }
 
     fn parse_const_statement(&mut self) -> Result<Statement> {
+        let span = self.peek().span();
         let name = match &self.advance().token_type {
             TokenType::Identifier(name) => name.clone(),
             _ => {
@@ -114,14 +181,28 @@ impl Parser {
             }
         };
 
-        self.consume(&TokenType::Equals, "Expected '=' after constant name")?;
+        let ty = if self.match_token(&TokenType::Colon) {
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+
+        self.consume(&TokenType::Equals)?;
 
-        let value = Box::new(self.parse_expression()?);
+        let value = self.parse_expression()?;
 
-        Ok(Statement::Const { name, value })
+        Ok(Statement::Const {
+            name,
+            ty,
+            value,
+            span,
+        })
     }
 
     fn parse_type_definition(&mut self) -> Result<Statement> {
+        // The `type` keyword has been consumed, and any docs precede it
+        let docs = self.docs.remove(&(self.current - 1));
+        let span = self.peek().span();
         let name = match &self.advance().token_type {
             TokenType::Identifier(name) => name.clone(),
             _ => {
@@ -134,9 +215,83 @@ impl Parser {
             }
         };
 
-        self.consume(&TokenType::Arrow, "Expected '=>' after type name")?;
-        self.consume(&TokenType::LeftBrace, "Expected '{' after '=>'")?;
+        let mut params = Vec::new();
+        if self.match_token(&TokenType::LessThan) {
+            while !self.check(&TokenType::GreaterThan) && !self.is_at_end() {
+                params.push(self.parse_identifier()?);
+                if !self.match_token(&TokenType::Comma) {
+                    break;
+                }
+            }
+            self.consume(&TokenType::GreaterThan)?;
+        }
+
+        // `type A extends B, C => { ... }`
+        let mut extends = Vec::new();
+        if self.match_token(&TokenType::Extends) {
+            loop {
+                extends.push(self.parse_type()?);
+                if !self.match_token(&TokenType::Comma) {
+                    break;
+                }
+            }
+        }
+
+        self.consume(&TokenType::Arrow)?;
+
+        // The body is a list of `&`-joined record types: `B & C & { ... }`,
+        // or any other type, which the definition then names: `"a" | "b"`
+        let extends_clause = !extends.is_empty();
+        let mut fields = Vec::new();
+        let mut alias = None;
+        let mut terms = 0;
+        loop {
+            terms += 1;
+            let term_span = self.peek().span();
+            if self.match_token(&TokenType::LeftBrace) {
+                fields.extend(self.parse_type_fields()?);
+            } else if self.starts_type() {
+                match self.parse_type()? {
+                    base @ Type::Custom(..) => extends.push(base),
+                    other => alias = Some((other, term_span)),
+                }
+            } else {
+                self.consume(&TokenType::LeftBrace)?;
+            }
+
+            if !self.match_token(&TokenType::Ampersand) {
+                break;
+            }
+        }
+
+        // A lone named type is renamed, not extended: `type Stage => Env`
+        if terms == 1 && !extends_clause && fields.is_empty() && extends.len() == 1 {
+            alias = extends.pop().map(|base| (base, span));
+        }
+
+        if let Some((_, term_span)) = &alias
+            && (!fields.is_empty() || !extends.is_empty())
+        {
+            return Err(ParseError::InvalidExpression {
+                message: "only record types can be combined with '&'".to_string(),
+                line: term_span.line,
+                column: term_span.column,
+            });
+        }
+
+        Ok(Statement::TypeDef(TypeDefinition {
+            name,
+            params,
+            extends,
+            fields,
+            alias: alias.map(|(ty, _)| ty),
+            docs,
+            span,
+        }))
+    }
 
+    /// Parses type fields up to and including the closing brace
+    fn parse_type_fields(&mut self) -> Result<Vec<TypeField>> {
         let mut fields = Vec::new();
         while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
             fields.push(self.parse_type_field()?);
@@ -145,12 +300,14 @@ impl Parser {
             }
         }
 
-        self.consume(&TokenType::RightBrace, "Expected '}' after type fields")?;
+        self.consume(&TokenType::RightBrace)?;
 
-        Ok(Statement::TypeDef(TypeDefinition { name, fields }))
+        Ok(fields)
     }
 
     fn parse_type_field(&mut self) -> Result<TypeField> {
+        let docs = self.docs.remove(&self.current);
+        let span = self.peek().span();
         let name = match &self.advance().token_type {
             TokenType::Identifier(name) => name.clone(),
             _ => {
@@ -163,42 +320,212 @@ impl Parser {
             }
         };
 
-        self.consume(&TokenType::Colon, "Expected ':' after field name")?;
+        self.consume(&TokenType::Colon)?;
 
         let field_type = self.parse_type()?;
 
-        Ok(TypeField { name, field_type })
+        let default = if self.match_token(&TokenType::Equals) {
+            Some(self.parse_expression()?)
+        } else {
+            None
+        };
+
+        Ok(TypeField {
+            name,
+            field_type,
+            default,
+            docs,
+            span,
+        })
     }
 
     fn parse_type(&mut self) -> Result<Type> {
-        match &self.advance().token_type {
-            TokenType::Number => Ok(Type::Number),
-            TokenType::String => Ok(Type::String),
-            TokenType::Boolean => Ok(Type::Boolean),
-            TokenType::Identifier(name) => Ok(Type::Custom(name.clone())),
-            _ => Err(ParseError::UnexpectedToken {
-                expected: "type".to_string(),
-                found: format!("{:?}", self.previous().token_type),
-                line: self.previous().line,
-                column: self.previous().column,
-            }),
+        let first = self.parse_type_member()?;
+        if !self.check(&TokenType::Pipe) {
+            return Ok(first);
+        }
+
+        let mut members = vec![first];
+        while self.match_token(&TokenType::Pipe) {
+            members.push(self.parse_type_member()?);
+        }
+
+        Ok(Type::Union(members))
+    }
+
+    /// Returns true if the next token can begin a type
+    fn starts_type(&self) -> bool {
+        matches!(
+            self.peek().token_type,
+            TokenType::Number
+                | TokenType::String
+                | TokenType::Boolean
+                | TokenType::Identifier(_)
+                | TokenType::NumberLiteral(_)
+                | TokenType::StringLiteral(_)
+                | TokenType::BooleanLiteral(_)
+        )
+    }
+
+    /// Parses a single member of a union type
+    fn parse_type_member(&mut self) -> Result<Type> {
+        let token = self.advance().clone();
+        let mut ty = match token.token_type {
+            TokenType::Number => Type::Number,
+            TokenType::String => Type::String,
+            TokenType::Boolean => Type::Boolean,
+            TokenType::NumberLiteral(n) => Type::Literal(Literal::Number(n)),
+            TokenType::StringLiteral(s) => Type::Literal(Literal::String(s)),
+            TokenType::BooleanLiteral(b) => Type::Literal(Literal::Boolean(b)),
+            TokenType::LeftBrace => {
+                let fields = self.parse_type_fields()?;
+                if let Some(field) = fields.iter().find(|field| field.default.is_some()) {
+                    return Err(ParseError::InvalidExpression {
+                        message: "default values are only allowed in type definitions".to_string(),
+                        line: field.span.line,
+                        column: field.span.column,
+                    });
+                }
+                Type::Object(
+                    fields
+                        .into_iter()
+                        .map(|field| (field.name, field.field_type))
+                        .collect(),
+                )
+            }
+            TokenType::Identifier(name) => {
+                let mut args = Vec::new();
+                if self.match_token(&TokenType::LessThan) {
+                    while !self.check(&TokenType::GreaterThan) && !self.is_at_end() {
+                        args.push(self.parse_type()?);
+                        if !self.match_token(&TokenType::Comma) {
+                            break;
+                        }
+                    }
+                    self.consume(&TokenType::GreaterThan)?;
+                }
+                Type::Custom(name, args)
+            }
+            _ => {
+                return Err(ParseError::UnexpectedToken {
+                    expected: "type".to_string(),
+                    found: format!("{:?}", token.token_type),
+                    line: token.line,
+                    column: token.column,
+                });
+            }
+        };
+
+        // `T[]` is a list of `T`; suffixes may be repeated for nested lists
+        while self.match_token(&TokenType::LeftBracket) {
+            self.consume(&TokenType::RightBracket)?;
+            ty = Type::List(Box::new(ty));
+        }
+
+        Ok(ty)
+    }
+
+    fn parse_expression(&mut self) -> Result<Expr> {
+        self.parse_additive()
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr> {
+        let mut left = self.parse_multiplicative()?;
+        loop {
+            let op = match self.peek().token_type {
+                TokenType::Plus => BinaryOp::Add,
+                TokenType::Minus => BinaryOp::Subtract,
+                _ => return Ok(left),
+            };
+            self.advance();
+
+            let right = self.parse_multiplicative()?;
+            left = self.binary(op, left, right);
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr> {
+        let mut left = self.parse_unary()?;
+        loop {
+            let op = match self.peek().token_type {
+                TokenType::Star => BinaryOp::Multiply,
+                TokenType::Slash => BinaryOp::Divide,
+                TokenType::Percent => BinaryOp::Remainder,
+                _ => return Ok(left),
+            };
+            self.advance();
+
+            let right = self.parse_unary()?;
+            left = self.binary(op, left, right);
         }
     }
 
-    fn parse_expression(&mut self) -> Result<Expression> {
-        match &self.peek().token_type {
+    /// Builds a binary expression, which starts where its left operand does
+    fn binary(&mut self, op: BinaryOp, left: Expr, right: Expr) -> Expr {
+        let span = left.span;
+        let kind = Expression::Binary {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+        };
+        Expr::new(self.expr_id(), kind, span)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        if self.check(&TokenType::Minus) {
+            let id = self.expr_id();
+            let span = self.advance().span();
+            let operand = Box::new(self.parse_unary()?);
+            return Ok(Expr::new(
+                id,
+                Expression::Unary {
+                    op: UnaryOp::Negate,
+                    operand,
+                },
+                span,
+            ));
+        }
+
+        self.parse_operand()
+    }
+
+    fn parse_operand(&mut self) -> Result<Expr> {
+        // Parentheses only group, so the inner expression stands in for them
+        if self.peek().token_type == TokenType::LeftParen {
+            self.advance();
+            let inner = self.parse_expression()?;
+            self.consume(&TokenType::RightParen)?;
+            return Ok(inner);
+        }
+
+        let id = self.expr_id();
+        let span = self.peek().span();
+        let kind = match &self.peek().token_type {
             TokenType::NumberLiteral(_)
             | TokenType::StringLiteral(_)
             | TokenType::BooleanLiteral(_)
-            | TokenType::Identifier(_) => Ok(self.parse_primary()?),
-            TokenType::LeftBrace => self.parse_object_expression(),
-            _ => Err(ParseError::UnexpectedToken {
-                expected: "expression".to_string(),
-                found: format!("{:?}", self.peek().token_type),
-                line: self.peek().line,
-                column: self.peek().column,
-            }),
-        }
+            | TokenType::Identifier(_) => self.parse_primary()?,
+            TokenType::LeftBrace => self.parse_object_expression()?,
+            TokenType::LeftBracket => self.parse_list_expression()?,
+            TokenType::TemplateLiteral(_) => self.parse_template()?,
+            TokenType::If => self.parse_if_expression()?,
+            TokenType::Match => self.parse_match_expression()?,
+            _ => {
+                return Err(ParseError::UnexpectedToken {
+                    expected: "expression".to_string(),
+                    found: format!("{:?}", self.peek().token_type),
+                    line: self.peek().line,
+                    column: self.peek().column,
+                });
+            }
+        };
+
+        Ok(Expr::new(id, kind, span))
+    }
+
+    fn expr_id(&mut self) -> ExprId {
+        self.next_expr_id += 1;
+        ExprId(self.next_expr_id - 1)
     }
 
     fn parse_primary(&mut self) -> Result<Expression> {
@@ -218,35 +545,165 @@ impl Parser {
     }
 
     fn parse_object_expression(&mut self) -> Result<Expression> {
-        self.consume(&TokenType::LeftBrace, "Expected '{' for object literal")?;
+        self.consume(&TokenType::LeftBrace)?;
 
-        let mut fields = Vec::new();
+        let mut entries = Vec::new();
         while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
-            let name = match &self.advance().token_type {
-                TokenType::Identifier(name) => name.clone(),
-                _ => {
-                    return Err(ParseError::UnexpectedToken {
-                        expected: "identifier".to_string(),
-                        found: format!("{:?}", self.previous().token_type),
-                        line: self.previous().line,
-                        column: self.previous().column,
-                    });
+            if self.match_token(&TokenType::Ellipsis) {
+                entries.push(ObjectEntry::Spread(self.parse_expression()?));
+            } else {
+                let span = self.peek().span();
+                let name = match &self.advance().token_type {
+                    TokenType::Identifier(name) => name.clone(),
+                    _ => {
+                        return Err(ParseError::UnexpectedToken {
+                            expected: "identifier".to_string(),
+                            found: format!("{:?}", self.previous().token_type),
+                            line: self.previous().line,
+                            column: self.previous().column,
+                        });
+                    }
+                };
+
+                // `{ x }` is shorthand for `{ x: x }`
+                let value = if self.match_token(&TokenType::Colon) {
+                    self.parse_expression()?
+                } else {
+                    Expr::new(self.expr_id(), Expression::Identifier(name.clone()), span)
+                };
+                entries.push(ObjectEntry::Field { name, value, span });
+            }
+
+            if self.check(&TokenType::Comma) {
+                self.advance();
+            }
+        }
+
+        self.consume(&TokenType::RightBrace)?;
+
+        Ok(Expression::Object { entries })
+    }
+
+    fn parse_list_expression(&mut self) -> Result<Expression> {
+        self.consume(&TokenType::LeftBracket)?;
+
+        let mut elements = Vec::new();
+        while !self.check(&TokenType::RightBracket) && !self.is_at_end() {
+            elements.push(self.parse_expression()?);
+
+            if self.check(&TokenType::Comma) {
+                self.advance();
+            }
+        }
+
+        self.consume(&TokenType::RightBracket)?;
+
+        Ok(Expression::List(elements))
+    }
+
+    fn parse_template(&mut self) -> Result<Expression> {
+        let TokenType::TemplateLiteral(segments) = self.advance().token_type.clone() else {
+            unreachable!("parse_template is only called on template literals");
+        };
+
+        let mut parts = Vec::new();
+        for segment in segments {
+            match segment {
+                StringSegment::Text(text) => parts.push(Part::Text(text)),
+                StringSegment::Interpolation(tokens) => {
+                    // Each segment is parsed on its own, up to its closing EOF token
+                    let mut parser = Parser::new(tokens);
+                    parser.next_expr_id = self.next_expr_id;
+                    let expr = parser.parse_expression()?;
+                    self.next_expr_id = parser.next_expr_id;
+                    if !parser.is_at_end() {
+                        return Err(ParseError::UnexpectedToken {
+                            expected: "'}'".to_string(),
+                            found: format!("{:?}", parser.peek().token_type),
+                            line: parser.peek().line,
+                            column: parser.peek().column,
+                        });
+                    }
+                    parts.push(Part::Expr(expr));
                 }
-            };
+            }
+        }
+
+        Ok(Expression::Template(parts))
+    }
+
+    fn parse_if_expression(&mut self) -> Result<Expression> {
+        self.consume(&TokenType::If)?;
+        let condition = Box::new(self.parse_expression()?);
+        self.consume(&TokenType::Then)?;
+        let then_branch = Box::new(self.parse_expression()?);
+        self.consume(&TokenType::Else)?;
+        let else_branch = Box::new(self.parse_expression()?);
+
+        Ok(Expression::If {
+            condition,
+            then_branch,
+            else_branch,
+        })
+    }
+
+    fn parse_match_expression(&mut self) -> Result<Expression> {
+        self.consume(&TokenType::Match)?;
+        let scrutinee = Box::new(self.parse_expression()?);
+        self.consume(&TokenType::LeftBrace)?;
 
-            self.consume(&TokenType::Colon, "Expected ':' after field name")?;
+        let mut arms = Vec::new();
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            let span = self.peek().span();
+            let mut patterns = vec![self.parse_pattern()?];
+            while self.match_token(&TokenType::Pipe) {
+                patterns.push(self.parse_pattern()?);
+            }
 
-            let value = self.parse_expression()?;
-            fields.push((name, value));
+            self.consume(&TokenType::Arrow)?;
+            let body = self.parse_expression()?;
+            arms.push(MatchArm {
+                patterns,
+                body,
+                span,
+            });
 
             if self.check(&TokenType::Comma) {
                 self.advance();
             }
         }
 
-        self.consume(&TokenType::RightBrace, "Expected '}' after object fields")?;
+        self.consume(&TokenType::RightBrace)?;
+
+        Ok(Expression::Match { scrutinee, arms })
+    }
 
-        Ok(Expression::Object { fields })
+    fn parse_pattern(&mut self) -> Result<Pattern> {
+        let token = self.advance();
+        match &token.token_type {
+            TokenType::NumberLiteral(n) => Ok(Pattern::Literal(Literal::Number(*n))),
+            TokenType::StringLiteral(s) => Ok(Pattern::Literal(Literal::String(s.clone()))),
+            TokenType::BooleanLiteral(b) => Ok(Pattern::Literal(Literal::Boolean(*b))),
+            TokenType::Identifier(name) if name == "_" => Ok(Pattern::Wildcard),
+            _ => Err(ParseError::UnexpectedToken {
+                expected: "literal or '_'".to_string(),
+                found: format!("{:?}", token.token_type),
+                line: token.line,
+                column: token.column,
+            }),
+        }
+    }
+
+    fn parse_identifier(&mut self) -> Result<String> {
+        match &self.advance().token_type {
+            TokenType::Identifier(name) => Ok(name.clone()),
+            _ => Err(ParseError::UnexpectedToken {
+                expected: "identifier".to_string(),
+                found: format!("{:?}", self.previous().token_type),
+                line: self.previous().line,
+                column: self.previous().column,
+            }),
+        }
     }
 
     // Helper methods
@@ -286,7 +743,7 @@ impl Parser {
         }
     }
 
-    fn consume(&mut self, token_type: &TokenType, message: &str) -> Result<&Token> {
+    fn consume(&mut self, token_type: &TokenType) -> Result<&Token> {
         if self.check(token_type) {
             Ok(self.advance())
         } else {
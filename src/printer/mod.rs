@@ -0,0 +1,740 @@
+mod tree;
+
+pub use tree::print_tree;
+
+use std::collections::VecDeque;
+
+use crate::ast::*;
+use crate::lexer::Comment;
+
+const INDENT: &str = "    ";
+
+/// Formats a program as jlang source, one module after another
+pub fn print_program(program: &Program) -> String {
+    print_program_with_comments(program, &[])
+}
+
+/// Formats a program as jlang source, keeping the `//` comments the lexer
+/// skipped over. A comment stays at the end of its line when that line is
+/// kept, and otherwise goes on a line of its own before what followed it.
+pub fn print_program_with_comments(program: &Program, comments: &[Comment]) -> String {
+    let mut comments: VecDeque<Comment> = comments.iter().cloned().collect();
+    let mut out = Vec::new();
+    for (i, module) in program.modules.iter().enumerate() {
+        let next = program.modules.get(i + 1).map(|module| module.span);
+        out.push(module_with_comments(module, &mut comments, next));
+    }
+    out.join("\n")
+}
+
+/// Formats a module as jlang source. Statements are indented four spaces,
+/// and a blank line separates any statement spanning several lines from its
+/// neighbours, as well as definitions from bindings.
+pub fn print_module(module: &Module) -> String {
+    module_with_comments(module, &mut VecDeque::new(), None)
+}
+
+/// Formats a module along with the comments before `next`, the start of the
+/// following module. Unindented comments after the last statement are taken
+/// to be about the following module rather than this one.
+fn module_with_comments(
+    module: &Module,
+    comments: &mut VecDeque<Comment>,
+    next: Option<Span>,
+) -> String {
+    let mut printer = Printer::new(0);
+    printer.comments = std::mem::take(comments);
+    printer.mark(module.span);
+    printer.out.push_str(&format!("module {} {{", module.name));
+    printer.trailing(None);
+    printer.out.push('\n');
+    printer.depth = 1;
+    let mut out = std::mem::take(&mut printer.out);
+
+    let mut previous: Option<(String, bool)> = None;
+    for statement in &module.statements {
+        printer.mark(statement.span());
+        let leading = std::mem::take(&mut printer.out);
+        printer.statement(statement);
+        printer.trailing(None);
+        let text = std::mem::take(&mut printer.out);
+        let is_type = matches!(statement, Statement::TypeDef(_));
+
+        if let Some((previous, was_type)) = &previous
+            && (previous.contains('\n') || text.contains('\n') || *was_type || is_type)
+        {
+            out.push('\n');
+        }
+        out.push_str(INDENT);
+        out.push_str(&leading);
+        out.push_str(&text);
+        out.push('\n');
+        previous = Some((text, is_type));
+    }
+
+    let mut rest = printer.comments;
+    while let Some(comment) = rest.front()
+        && comment.span.column > 1
+        && next.is_none_or(|next| comment.span < next)
+    {
+        out.push_str(INDENT);
+        out.push_str(&comment.text);
+        out.push('\n');
+        rest.pop_front();
+    }
+    out.push_str("}\n");
+    if next.is_none() {
+        for comment in rest.drain(..) {
+            out.push_str(&comment.text);
+            out.push('\n');
+        }
+    }
+    *comments = rest;
+    out
+}
+
+/// Formats an expression as jlang source, laying out nonempty objects and
+/// match expressions over several lines
+pub fn print_expression(expr: &Expr) -> String {
+    let mut printer = Printer::new(0);
+    printer.expression(expr);
+    printer.out
+}
+
+/// How tightly an expression binds: operands that bind more loosely than
+/// their operator need parentheses
+fn precedence(expr: &Expression) -> u8 {
+    match expr {
+        Expression::If { .. } => 0,
+        Expression::Binary {
+            op: BinaryOp::Add | BinaryOp::Subtract,
+            ..
+        } => 1,
+        Expression::Binary { .. } => 2,
+        Expression::Unary { .. } => 3,
+        _ => 4,
+    }
+}
+
+/// Returns true if `expr` is printed over several lines outside of strings
+fn spans_lines(expr: &Expr) -> bool {
+    match &expr.kind {
+        Expression::Object { entries } => !entries.is_empty(),
+        Expression::Match { .. } => true,
+        Expression::List(elements) => elements.iter().any(spans_lines),
+        _ => false,
+    }
+}
+
+struct Printer {
+    out: String,
+    depth: usize,
+    inline: bool, // Set inside template strings, which must stay on one line
+    comments: VecDeque<Comment>, // Not yet written, in source order
+    line: usize,  // The last source line output has reached
+}
+
+impl Printer {
+    fn new(depth: usize) -> Self {
+        Printer {
+            out: String::new(),
+            depth,
+            inline: false,
+            comments: VecDeque::new(),
+            line: 0,
+        }
+    }
+
+    fn newline(&mut self) {
+        self.newline_before(None);
+    }
+
+    /// Ends the line with its trailing comments, leaving any that come after
+    /// `next`, the node the new line starts with, for the end of its line
+    fn newline_before(&mut self, next: Option<Span>) {
+        self.trailing(next);
+        self.break_line();
+    }
+
+    /// Starts a new line without writing any comments
+    fn break_line(&mut self) {
+        self.out.push('\n');
+        for _ in 0..self.depth {
+            self.out.push_str(INDENT);
+        }
+    }
+
+    /// Notes that output has reached the node at `span`. At the start of a
+    /// line, the comments before the node are written first, each followed by
+    /// a new line. Elsewhere they wait for the end of the line.
+    fn mark(&mut self, span: Span) {
+        if self.inline {
+            return;
+        }
+        while self.at_line_start()
+            && let Some(comment) = self.comments.front()
+            && comment.span < span
+        {
+            let text = comment.text.clone();
+            self.comments.pop_front();
+            self.out.push_str(&text);
+            self.break_line();
+        }
+        self.line = self.line.max(span.line);
+    }
+
+    /// Returns true if nothing but indentation has been written on this line
+    fn at_line_start(&self) -> bool {
+        let line_start = self.out.rfind('\n').map_or(0, |i| i + 1);
+        self.out[line_start..].trim_start().is_empty()
+    }
+
+    /// Writes the comments that end the source line output has reached,
+    /// up to `next` if it is given
+    fn trailing(&mut self, next: Option<Span>) {
+        while let Some(comment) = self.comments.front()
+            && comment.span.line <= self.line
+            && next.is_none_or(|next| comment.span < next)
+        {
+            self.out.push(' ');
+            self.out.push_str(&comment.text);
+            self.comments.pop_front();
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Let {
+                name, ty, value, ..
+            } => self.binding("let", name, ty.as_ref(), value),
+            Statement::Const {
+                name, ty, value, ..
+            } => self.binding("const", name, ty.as_ref(), value),
+            Statement::TypeDef(def) => self.type_definition(def),
+            Statement::Assign { target, value, .. } => {
+                self.out.push_str(&format!("{} = ", target));
+                self.expression(value);
+            }
+        }
+    }
+
+    fn binding(&mut self, keyword: &str, name: &str, ty: Option<&Type>, value: &Expr) {
+        self.out.push_str(&format!("{} {}", keyword, name));
+        if let Some(ty) = ty {
+            self.out.push_str(": ");
+            self.ty(ty);
+        }
+        self.out.push_str(" = ");
+        self.expression(value);
+    }
+
+    fn type_definition(&mut self, def: &TypeDefinition) {
+        self.docs(def.docs.as_deref());
+        self.out.push_str(&format!("type {}", def.name));
+        if !def.params.is_empty() {
+            self.out.push_str(&format!("<{}>", def.params.join(", ")));
+        }
+        if !def.extends.is_empty() {
+            self.out.push_str(" extends ");
+            for (i, base) in def.extends.iter().enumerate() {
+                if i > 0 {
+                    self.out.push_str(", ");
+                }
+                self.ty(base);
+            }
+        }
+        self.out.push_str(" => ");
+
+        match &def.alias {
+            Some(alias) => self.ty(alias),
+            None => self.record(def.fields.iter().map(|field| {
+                (
+                    &field.name,
+                    &field.field_type,
+                    field.default.as_ref(),
+                    field.docs.as_deref(),
+                    Some(field.span),
+                )
+            })),
+        }
+    }
+
+    fn record<'t>(
+        &mut self,
+        fields: impl ExactSizeIterator<
+            Item = (
+                &'t String,
+                &'t Type,
+                Option<&'t Expr>,
+                Option<&'t str>,
+                Option<Span>,
+            ),
+        >,
+    ) {
+        if fields.len() == 0 {
+            self.out.push_str("{}");
+            return;
+        }
+
+        self.out.push('{');
+        self.depth += 1;
+        for (i, (name, field_type, default, docs, span)) in fields.enumerate() {
+            if i > 0 {
+                self.out.push(',');
+            }
+            self.newline_before(span);
+            if let Some(span) = span {
+                self.mark(span);
+            }
+            self.docs(docs);
+            self.out.push_str(&format!("{}: ", name));
+            self.ty(field_type);
+            if let Some(default) = default {
+                self.out.push_str(" = ");
+                self.expression(default);
+            }
+        }
+        self.depth -= 1;
+        self.newline();
+        self.out.push('}');
+    }
+
+    /// Writes one `///` line per line of `docs`, each followed by a new line
+    fn docs(&mut self, docs: Option<&str>) {
+        for line in docs.into_iter().flat_map(str::lines) {
+            if line.is_empty() {
+                self.out.push_str("///");
+            } else {
+                self.out.push_str(&format!("/// {}", line));
+            }
+            // A comment would become part of the docs
+            self.break_line();
+        }
+    }
+
+    /// Writes a type, laying out a record type over several lines when it is
+    /// not part of a larger type
+    fn ty(&mut self, ty: &Type) {
+        match ty {
+            Type::Object(fields) if !self.inline => {
+                self.record(fields.iter().map(|(name, ty)| (name, ty, None, None, None)))
+            }
+            other => self.out.push_str(&other.to_string()),
+        }
+    }
+
+    fn expression(&mut self, expr: &Expr) {
+        self.mark(expr.span);
+        match &expr.kind {
+            Expression::NumberLiteral(n) => self.out.push_str(&n.to_string()),
+            Expression::StringLiteral(s) => {
+                self.out.push('"');
+                self.text(s);
+                self.out.push('"');
+            }
+            Expression::BooleanLiteral(b) => self.out.push_str(&b.to_string()),
+            Expression::Identifier(name) => self.out.push_str(name),
+            Expression::Object { entries } => self.object(entries),
+            Expression::List(elements) => self.list(elements),
+            Expression::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.out.push_str("if ");
+                self.expression(condition);
+                self.out.push_str(" then ");
+                self.expression(then_branch);
+                self.out.push_str(" else ");
+                self.expression(else_branch);
+            }
+            Expression::Match { scrutinee, arms } => self.match_expression(scrutinee, arms),
+            Expression::Template(parts) => {
+                self.out.push('"');
+                for part in parts {
+                    match part {
+                        Part::Text(text) => self.text(text),
+                        Part::Expr(expr) => {
+                            // A backslash right before `${` would escape it
+                            if self.out.ends_with('\\') {
+                                self.out.pop();
+                                self.out.push_str("${\"\\\"}");
+                            }
+                            let inline = std::mem::replace(&mut self.inline, true);
+                            self.out.push_str("${");
+                            self.expression(expr);
+                            self.out.push('}');
+                            self.inline = inline;
+                        }
+                    }
+                }
+                self.out.push('"');
+            }
+            Expression::Unary { op, operand } => {
+                self.out.push_str(&op.to_string());
+                self.operand(operand, precedence(&operand.kind) < 4);
+            }
+            Expression::Binary { op, left, right } => {
+                let own = precedence(&expr.kind);
+                self.operand(left, precedence(&left.kind) < own);
+                self.out.push_str(&format!(" {} ", op));
+                // Operators associate to the left, so an equal right operand is grouped
+                self.operand(right, precedence(&right.kind) <= own);
+            }
+        }
+    }
+
+    fn operand(&mut self, expr: &Expr, parenthesize: bool) {
+        if parenthesize {
+            self.out.push('(');
+            self.expression(expr);
+            self.out.push(')');
+        } else {
+            self.expression(expr);
+        }
+    }
+
+    /// Writes the contents of a string, escaping each literal `${` as `\${`
+    fn text(&mut self, text: &str) {
+        self.out.push_str(&text.replace("${", "\\${"));
+    }
+
+    fn object(&mut self, entries: &[ObjectEntry]) {
+        if entries.is_empty() {
+            self.out.push_str("{}");
+            return;
+        }
+
+        self.out.push('{');
+        self.depth += 1;
+        for (i, entry) in entries.iter().enumerate() {
+            if i > 0 {
+                self.out.push(',');
+            }
+            let span = match entry {
+                ObjectEntry::Field { span, .. } => *span,
+                ObjectEntry::Spread(expr) => expr.span,
+            };
+            if self.inline {
+                self.out.push(' ');
+            } else {
+                self.newline_before(Some(span));
+            }
+
+            match entry {
+                ObjectEntry::Field { name, value, span } => {
+                    self.mark(*span);
+                    match &value.kind {
+                        Expression::Identifier(value) if value == name => self.out.push_str(name),
+                        _ => {
+                            self.out.push_str(&format!("{}: ", name));
+                            self.expression(value);
+                        }
+                    }
+                }
+                ObjectEntry::Spread(expr) => {
+                    self.out.push_str("...");
+                    self.expression(expr);
+                }
+            }
+        }
+        self.depth -= 1;
+        if self.inline {
+            self.out.push(' ');
+        } else {
+            self.newline();
+        }
+        self.out.push('}');
+    }
+
+    /// Writes a list on one line, unless an element is laid out over several
+    /// lines, in which case each element gets its own
+    fn list(&mut self, elements: &[Expr]) {
+        self.out.push('[');
+        if self.inline || !elements.iter().any(spans_lines) {
+            for (i, element) in elements.iter().enumerate() {
+                if i > 0 {
+                    self.out.push_str(", ");
+                }
+                self.expression(element);
+            }
+            self.out.push(']');
+            return;
+        }
+
+        self.depth += 1;
+        for (i, element) in elements.iter().enumerate() {
+            if i > 0 {
+                self.out.push(',');
+            }
+            self.newline_before(Some(element.span));
+            self.expression(element);
+        }
+        self.depth -= 1;
+        self.newline();
+        self.out.push(']');
+    }
+
+    fn match_expression(&mut self, scrutinee: &Expr, arms: &[MatchArm]) {
+        self.out.push_str("match ");
+        self.expression(scrutinee);
+        self.out.push_str(" {");
+
+        self.depth += 1;
+        for (i, arm) in arms.iter().enumerate() {
+            if i > 0 {
+                self.out.push(',');
+            }
+            if self.inline {
+                self.out.push(' ');
+            } else {
+                self.newline_before(Some(arm.span));
+            }
+            self.mark(arm.span);
+
+            for (j, pattern) in arm.patterns.iter().enumerate() {
+                if j > 0 {
+                    self.out.push_str(" | ");
+                }
+                match pattern {
+                    Pattern::Literal(literal) => self.out.push_str(&literal.to_string()),
+                    Pattern::Wildcard => self.out.push('_'),
+                }
+            }
+            self.out.push_str(" => ");
+            self.expression(&arm.body);
+        }
+        self.depth -= 1;
+        if self.inline {
+            self.out.push(' ');
+        } else {
+            self.newline();
+        }
+        self.out.push('}');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn parse(source: &str) -> Program {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        Parser::new(tokens).parse().unwrap()
+    }
+
+    /// Checks that `source` prints as itself, and that printing is stable
+    fn assert_round_trip(source: &str) {
+        let printed = print_program(&parse(source));
+        assert_eq!(printed, source);
+        assert_eq!(print_program(&parse(&printed)), printed);
+    }
+
+    #[test]
+    fn test_print_definitions() {
+        assert_round_trip(
+            "module shapes {
+    type Env => \"dev\" | \"prod\"
+
+    /// A point on a plane
+    ///
+    /// Coordinates may be any type
+    type Point<T> => {
+        /// Horizontal position
+        x: T,
+        y: T
+    }
+
+    type Server extends Base, Named => {
+        host: String = \"localhost\",
+        tags: String[],
+        metadata: {
+            created: String,
+            modified: String
+        },
+        shape: { kind: Env } | Number
+    }
+
+    type Empty => {}
+}
+",
+        );
+    }
+
+    #[test]
+    fn test_print_bindings() {
+        assert_round_trip(
+            "module config {
+    const port = 8080
+    let retries: Number = -3
+    const ratio = (1 + 2) * 3 - (4 - 5) / -(6 % 7)
+    let url = \"http://${host}:${port}/${match env { \"dev\" => { a: 1, b: 2 }, _ => {} }}\"
+
+    let server = {
+        host,
+        port: if tls then 443 else 80,
+        ...defaults,
+        limits: {}
+    }
+
+    const mode = match env {
+        \"dev\" | \"test\" => 1,
+        _ => 2
+    }
+
+    server.limits.connections = (if tls then 1 else 2) + 3
+}
+
+module other {
+    let literal = \"costs \\${price}\"
+    const ports = [80, 443, [], [if tls then 1 else 2]]
+
+    const servers: Server[] = [
+        {
+            host: \"a\"
+        },
+        [1]
+    ]
+}
+",
+        );
+    }
+
+    #[test]
+    fn test_print_comments() {
+        let source = "// Settings
+module m { // main
+    const a = 1 +
+        // not kept here
+        2
+
+    // Before
+    let server = {
+        // Host
+        host: \"a\", // trailing
+        port: match a {
+            // First
+            1 => 2,
+            _ => 3
+        }
+    }
+    // Last
+}
+// After
+";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        assert_eq!(
+            print_program_with_comments(&program, lexer.comments()),
+            "// Settings
+module m { // main
+    const a = 1 + 2 // not kept here
+
+    // Before
+    let server = {
+        // Host
+        host: \"a\", // trailing
+        port: match a {
+            // First
+            1 => 2,
+            _ => 3
+        }
+    }
+    // Last
+}
+// After
+"
+        );
+    }
+
+    #[test]
+    fn test_print_comments_beside_docs() {
+        let source = "module m {
+    /// Doc for T
+    type T<A> extends Base => { x: A, // x comment
+        /// Doc for y
+        y: A }
+    let o = { x: 1, // one
+        y: 2 }
+}
+";
+        let print = |source: &str| {
+            let mut lexer = Lexer::new(source);
+            let tokens = lexer.tokenize().unwrap();
+            let program = Parser::new(tokens).parse().unwrap();
+            (
+                print_program_with_comments(&program, lexer.comments()),
+                program,
+            )
+        };
+        let (printed, program) = print(source);
+        assert_eq!(
+            printed,
+            "module m {
+    /// Doc for T
+    type T<A> extends Base => {
+        x: A, // x comment
+        /// Doc for y
+        y: A
+    }
+
+    let o = {
+        x: 1, // one
+        y: 2
+    }
+}
+"
+        );
+
+        // The comments stay out of the docs
+        let (reprinted, reparsed) = print(&printed);
+        assert_eq!(reprinted, printed);
+        assert_eq!(print_program(&reparsed), print_program(&program));
+    }
+
+    #[test]
+    fn test_print_normalizes_layout() {
+        let program = parse(
+            "module m { type A=>{x:Number} let a:A={x:1} let b = ((1)) const c = 1 - (2 + 3) }",
+        );
+        assert_eq!(
+            print_program(&program),
+            "module m {
+    type A => {
+        x: Number
+    }
+
+    let a: A = {
+        x: 1
+    }
+
+    let b = 1
+    const c = 1 - (2 + 3)
+}
+"
+        );
+
+        let expr = Expr::new(
+            ExprId(0),
+            Expression::StringLiteral("${price}".to_string()),
+            Span::default(),
+        );
+        assert_eq!(print_expression(&expr), "\"\\${price}\"");
+
+        // A backslash ending the text before an interpolation is not an escape
+        let name = Expr::new(
+            ExprId(1),
+            Expression::Identifier("name".to_string()),
+            Span::default(),
+        );
+        let expr = Expr::new(
+            ExprId(2),
+            Expression::Template(vec![Part::Text("C:\\".to_string()), Part::Expr(name)]),
+            Span::default(),
+        );
+        assert_eq!(print_expression(&expr), r#""C:${"\"}${name}""#);
+    }
+}
@@ -0,0 +1,230 @@
+use crate::ast::*;
+
+/// Outlines the syntax tree of a program, one node per line, indented two
+/// spaces below its parent and followed by the position it starts at:
+///
+/// ```text
+/// Module app
+///   Const port @2:11
+///     Binary + @2:18
+///       Number 8000 @2:18
+///       Number 80 @2:25
+/// ```
+pub fn print_tree(program: &Program) -> String {
+    let mut tree = Tree::default();
+    for module in &program.modules {
+        tree.line(0, format!("Module {}", module.name), None);
+        for statement in &module.statements {
+            tree.statement(1, statement);
+        }
+    }
+    tree.out
+}
+
+#[derive(Default)]
+struct Tree {
+    out: String,
+}
+
+impl Tree {
+    fn line(&mut self, depth: usize, text: String, span: Option<Span>) {
+        self.out.push_str(&"  ".repeat(depth));
+        self.out.push_str(&text);
+        if let Some(span) = span {
+            self.out += &format!(" @{}:{}", span.line, span.column);
+        }
+        self.out.push('\n');
+    }
+
+    fn statement(&mut self, depth: usize, statement: &Statement) {
+        match statement {
+            Statement::Let {
+                name,
+                ty,
+                value,
+                span,
+            }
+            | Statement::Const {
+                name,
+                ty,
+                value,
+                span,
+            } => {
+                let keyword = match statement {
+                    Statement::Let { .. } => "Let",
+                    _ => "Const",
+                };
+                let annotation = ty
+                    .as_ref()
+                    .map(|ty| format!(": {}", ty))
+                    .unwrap_or_default();
+                self.line(
+                    depth,
+                    format!("{} {}{}", keyword, name, annotation),
+                    Some(*span),
+                );
+                self.expression(depth + 1, value);
+            }
+            Statement::Assign {
+                target,
+                value,
+                span,
+            } => {
+                self.line(depth, format!("Assign {}", target), Some(*span));
+                self.expression(depth + 1, value);
+            }
+            Statement::TypeDef(def) => {
+                let mut header = format!("Type {}", def.name);
+                if !def.params.is_empty() {
+                    header += &format!("<{}>", def.params.join(", "));
+                }
+                if let Some(alias) = &def.alias {
+                    header += &format!(" = {}", alias);
+                }
+                self.line(depth, header, Some(def.span));
+                for base in &def.extends {
+                    self.line(depth + 1, format!("Extends {}", base), None);
+                }
+                for field in &def.fields {
+                    self.line(
+                        depth + 1,
+                        format!("Field {}: {}", field.name, field.field_type),
+                        Some(field.span),
+                    );
+                    if let Some(default) = &field.default {
+                        self.expression(depth + 2, default);
+                    }
+                }
+            }
+        }
+    }
+
+    fn expression(&mut self, depth: usize, expr: &Expr) {
+        let span = Some(expr.span);
+        match &expr.kind {
+            Expression::NumberLiteral(n) => self.line(depth, format!("Number {}", n), span),
+            Expression::StringLiteral(s) => self.line(depth, format!("String {:?}", s), span),
+            Expression::BooleanLiteral(b) => self.line(depth, format!("Boolean {}", b), span),
+            Expression::Identifier(name) => self.line(depth, format!("Identifier {}", name), span),
+            Expression::Object { entries } => {
+                self.line(depth, "Object".to_string(), span);
+                for entry in entries {
+                    match entry {
+                        ObjectEntry::Field { name, value, span } => {
+                            self.line(depth + 1, format!("Field {}", name), Some(*span));
+                            self.expression(depth + 2, value);
+                        }
+                        ObjectEntry::Spread(value) => {
+                            self.line(depth + 1, "Spread".to_string(), None);
+                            self.expression(depth + 2, value);
+                        }
+                    }
+                }
+            }
+            Expression::List(elements) => {
+                self.line(depth, "List".to_string(), span);
+                for element in elements {
+                    self.expression(depth + 1, element);
+                }
+            }
+            Expression::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.line(depth, "If".to_string(), span);
+                self.expression(depth + 1, condition);
+                self.expression(depth + 1, then_branch);
+                self.expression(depth + 1, else_branch);
+            }
+            Expression::Match { scrutinee, arms } => {
+                self.line(depth, "Match".to_string(), span);
+                self.expression(depth + 1, scrutinee);
+                for arm in arms {
+                    let patterns: Vec<String> = arm
+                        .patterns
+                        .iter()
+                        .map(|pattern| match pattern {
+                            Pattern::Wildcard => "_".to_string(),
+                            Pattern::Literal(Literal::String(s)) => format!("{:?}", s),
+                            Pattern::Literal(Literal::Number(n)) => n.to_string(),
+                            Pattern::Literal(Literal::Boolean(b)) => b.to_string(),
+                        })
+                        .collect();
+                    self.line(
+                        depth + 1,
+                        format!("Arm {}", patterns.join(" | ")),
+                        Some(arm.span),
+                    );
+                    self.expression(depth + 2, &arm.body);
+                }
+            }
+            Expression::Template(parts) => {
+                self.line(depth, "Template".to_string(), span);
+                for part in parts {
+                    match part {
+                        Part::Text(text) => self.line(depth + 1, format!("Text {:?}", text), None),
+                        Part::Expr(expr) => self.expression(depth + 1, expr),
+                    }
+                }
+            }
+            Expression::Unary { op, operand } => {
+                self.line(depth, format!("Unary {}", op), span);
+                self.expression(depth + 1, operand);
+            }
+            Expression::Binary { op, left, right } => {
+                self.line(depth, format!("Binary {}", op), span);
+                self.expression(depth + 1, left);
+                self.expression(depth + 1, right);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_print_tree() {
+        let source = "module app {
+    type Server => { host: String, port: Number = 80 }
+    let server: Server = { host: \"${name}.local\", ...base }
+    server.port = match env { \"dev\" | _ => 8000 + 80 }
+    const ports = [80, []]
+}";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+
+        assert_eq!(
+            print_tree(&program),
+            "Module app
+  Type Server @2:10
+    Field host: String @2:22
+    Field port: Number @2:36
+      Number 80 @2:51
+  Let server: Server @3:9
+    Object @3:26
+      Field host @3:28
+        Template @3:34
+          Identifier name @3:37
+          Text \".local\"
+      Spread
+        Identifier base @3:54
+  Assign server.port @4:5
+    Match @4:19
+      Identifier env @4:25
+      Arm \"dev\" | _ @4:31
+        Binary + @4:44
+          Number 8000 @4:44
+          Number 80 @4:51
+  Const ports @5:11
+    List @5:19
+      Number 80 @5:20
+      List @5:24
+"
+        );
+    }
+}
@@ -0,0 +1,414 @@
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::process::{Command, Stdio};
+
+/// Reads lines from the terminal with cursor movement, history and the
+/// usual emacs-style shortcuts. The terminal is switched to raw mode with
+/// `stty` while a line is read; when that is not possible, or the input is
+/// not a terminal, lines are read as they come.
+#[derive(Debug)]
+pub struct LineEditor {
+    history: Vec<String>,
+    terminal: bool, // Whether the input is a terminal, which gets prompts
+    raw: bool,      // Whether raw mode is worth trying
+}
+
+impl LineEditor {
+    pub fn new() -> Self {
+        let terminal = io::stdin().is_terminal();
+        LineEditor {
+            history: Vec::new(),
+            terminal,
+            raw: terminal && io::stdout().is_terminal(),
+        }
+    }
+
+    /// Adds a line that up and down arrows bring back
+    pub fn add_history(&mut self, line: &str) {
+        if !line.trim().is_empty() && self.history.last().map(String::as_str) != Some(line) {
+            self.history.push(line.to_string());
+        }
+    }
+
+    /// Reads a line without its line ending. Returns `None` at the end of
+    /// the input, and an `Interrupted` error if ctrl-C abandons the line.
+    pub fn read_line(&mut self, prompt: &str) -> io::Result<Option<String>> {
+        if self.raw {
+            match RawMode::enable() {
+                Ok(_raw) => return self.edit(prompt),
+                Err(_) => self.raw = false,
+            }
+        }
+
+        if self.terminal {
+            print!("{}", prompt);
+            io::stdout().flush()?;
+        }
+        let mut line = String::new();
+        if io::stdin().lock().read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line.trim_end_matches(['\n', '\r']).to_string()))
+    }
+
+    fn edit(&mut self, prompt: &str) -> io::Result<Option<String>> {
+        let mut stdin = io::stdin().lock();
+        let mut stdout = io::stdout().lock();
+        let mut line = Line::new(&self.history);
+
+        line.render(&mut stdout, prompt)?;
+        loop {
+            let Some(key) = read_key(&mut stdin)? else {
+                write!(stdout, "\r\n")?;
+                return Ok(None);
+            };
+            match line.apply(key) {
+                Some(Outcome::Done) => {
+                    write!(stdout, "\r\n")?;
+                    stdout.flush()?;
+                    return Ok(Some(line.text()));
+                }
+                Some(Outcome::End) => {
+                    write!(stdout, "\r\n")?;
+                    stdout.flush()?;
+                    return Ok(None);
+                }
+                Some(Outcome::Interrupted) => {
+                    write!(stdout, "^C\r\n")?;
+                    stdout.flush()?;
+                    return Err(io::ErrorKind::Interrupted.into());
+                }
+                None => {
+                    if key == Key::ClearScreen {
+                        write!(stdout, "\x1b[H\x1b[2J")?;
+                    }
+                    line.render(&mut stdout, prompt)?;
+                }
+            }
+        }
+    }
+}
+
+impl Default for LineEditor {
+    fn default() -> Self {
+        LineEditor::new()
+    }
+}
+
+/// Keeps the terminal in raw mode until dropped
+struct RawMode {
+    saved: String, // The settings to restore, as printed by `stty -g`
+}
+
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        let saved = stty(&["-g"])?;
+        stty(&["raw", "-echo"])?;
+        Ok(RawMode {
+            saved: saved.trim().to_string(),
+        })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other("stty failed"));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Key {
+    Char(char),
+    Enter,
+    Tab,
+    Backspace,
+    Delete,      // The delete key, or ctrl-D within a line
+    Left,        // Or ctrl-B
+    Right,       // Or ctrl-F
+    Home,        // Or ctrl-A
+    End,         // Or ctrl-E
+    Up,          // Or ctrl-P
+    Down,        // Or ctrl-N
+    KillToEnd,   // Ctrl-K
+    KillLine,    // Ctrl-U
+    KillWord,    // Ctrl-W
+    ClearScreen, // Ctrl-L
+    Interrupt,   // Ctrl-C
+    Other,       // Keys and sequences that do nothing
+}
+
+/// Reads one key press, decoding UTF-8 characters and the escape sequences
+/// terminals send for arrows and the like. Returns `None` at the end of the
+/// input.
+fn read_key(input: &mut impl Read) -> io::Result<Option<Key>> {
+    let Some(byte) = read_byte(input)? else {
+        return Ok(None);
+    };
+
+    let key = match byte {
+        b'\r' | b'\n' => Key::Enter,
+        b'\t' => Key::Tab,
+        0x7f | 0x08 => Key::Backspace,
+        0x01 => Key::Home,
+        0x02 => Key::Left,
+        0x03 => Key::Interrupt,
+        0x04 => Key::Delete,
+        0x05 => Key::End,
+        0x06 => Key::Right,
+        0x0b => Key::KillToEnd,
+        0x0c => Key::ClearScreen,
+        0x0e => Key::Down,
+        0x10 => Key::Up,
+        0x15 => Key::KillLine,
+        0x17 => Key::KillWord,
+        0x1b => read_escape(input)?,
+        byte if byte < 0x20 => Key::Other,
+        byte => {
+            // The lead byte tells how many continuation bytes follow
+            let len = match byte {
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => 1,
+            };
+            let mut bytes = vec![byte];
+            for _ in 1..len {
+                match read_byte(input)? {
+                    Some(byte) => bytes.push(byte),
+                    None => break,
+                }
+            }
+            match std::str::from_utf8(&bytes)
+                .ok()
+                .and_then(|s| s.chars().next())
+            {
+                Some(c) => Key::Char(c),
+                None => Key::Other,
+            }
+        }
+    };
+    Ok(Some(key))
+}
+
+/// Decodes what follows an escape byte: `ESC [ ...` and `ESC O ...`
+fn read_escape(input: &mut impl Read) -> io::Result<Key> {
+    let Some(kind @ (b'[' | b'O')) = read_byte(input)? else {
+        return Ok(Key::Other);
+    };
+
+    // Parameters are digits and semicolons, ended by a letter or `~`
+    let mut params = String::new();
+    let last = loop {
+        match read_byte(input)? {
+            Some(byte) if byte.is_ascii_digit() || byte == b';' => params.push(byte as char),
+            Some(byte) => break byte,
+            None => return Ok(Key::Other),
+        }
+    };
+
+    Ok(match (kind, params.as_str(), last) {
+        (_, _, b'A') => Key::Up,
+        (_, _, b'B') => Key::Down,
+        (_, _, b'C') => Key::Right,
+        (_, _, b'D') => Key::Left,
+        (_, _, b'H') | (b'[', "1" | "7", b'~') => Key::Home,
+        (_, _, b'F') | (b'[', "4" | "8", b'~') => Key::End,
+        (b'[', "3", b'~') => Key::Delete,
+        _ => Key::Other,
+    })
+}
+
+fn read_byte(input: &mut impl Read) -> io::Result<Option<u8>> {
+    let mut byte = [0];
+    loop {
+        return match input.read(&mut byte) {
+            Ok(0) => Ok(None),
+            Ok(_) => Ok(Some(byte[0])),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => Err(err),
+        };
+    }
+}
+
+/// How reading a line ended
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Outcome {
+    Done,        // Enter was pressed
+    End,         // Ctrl-D was pressed on an empty line
+    Interrupted, // Ctrl-C was pressed
+}
+
+/// The line being edited, with the history it can step through
+struct Line<'a> {
+    chars: Vec<char>,
+    cursor: usize,
+    history: &'a [String],
+    entry: usize,     // The history entry shown, or `history.len()` for the new line
+    draft: Vec<char>, // The new line, kept while stepping through history
+}
+
+impl<'a> Line<'a> {
+    fn new(history: &'a [String]) -> Self {
+        Line {
+            chars: Vec::new(),
+            cursor: 0,
+            history,
+            entry: history.len(),
+            draft: Vec::new(),
+        }
+    }
+
+    fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    /// Applies a key press, returning how the line ended if it did
+    fn apply(&mut self, key: Key) -> Option<Outcome> {
+        match key {
+            Key::Enter => return Some(Outcome::Done),
+            Key::Interrupt => return Some(Outcome::Interrupted),
+            Key::Delete if self.chars.is_empty() => return Some(Outcome::End),
+            Key::Char(c) => {
+                self.chars.insert(self.cursor, c);
+                self.cursor += 1;
+            }
+            Key::Tab => {
+                for _ in 0..4 {
+                    self.chars.insert(self.cursor, ' ');
+                    self.cursor += 1;
+                }
+            }
+            Key::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.chars.remove(self.cursor);
+            }
+            Key::Delete if self.cursor < self.chars.len() => {
+                self.chars.remove(self.cursor);
+            }
+            Key::Left => self.cursor = self.cursor.saturating_sub(1),
+            Key::Right => self.cursor = (self.cursor + 1).min(self.chars.len()),
+            Key::Home => self.cursor = 0,
+            Key::End => self.cursor = self.chars.len(),
+            Key::Up if self.entry > 0 => self.show(self.entry - 1),
+            Key::Down if self.entry < self.history.len() => self.show(self.entry + 1),
+            Key::KillToEnd => self.chars.truncate(self.cursor),
+            Key::KillLine => {
+                self.chars.drain(..self.cursor);
+                self.cursor = 0;
+            }
+            Key::KillWord => {
+                // Spaces before the cursor, then the word before them
+                let mut start = self.cursor;
+                while start > 0 && self.chars[start - 1].is_whitespace() {
+                    start -= 1;
+                }
+                while start > 0 && !self.chars[start - 1].is_whitespace() {
+                    start -= 1;
+                }
+                self.chars.drain(start..self.cursor);
+                self.cursor = start;
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Replaces the line with a history entry, or the draft past the last one
+    fn show(&mut self, entry: usize) {
+        if self.entry == self.history.len() {
+            self.draft = std::mem::take(&mut self.chars);
+        }
+        self.entry = entry;
+        self.chars = match self.history.get(entry) {
+            Some(line) => line.chars().collect(),
+            None => std::mem::take(&mut self.draft),
+        };
+        self.cursor = self.chars.len();
+    }
+
+    /// Redraws the line and puts the cursor where it is in the text
+    fn render(&self, out: &mut impl Write, prompt: &str) -> io::Result<()> {
+        write!(out, "\r{}{}\x1b[K\r", prompt, self.text())?;
+        let column = prompt.chars().count() + self.cursor;
+        if column > 0 {
+            write!(out, "\x1b[{}C", column)?;
+        }
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(mut bytes: &[u8]) -> Vec<Key> {
+        let mut keys = Vec::new();
+        while let Some(key) = read_key(&mut bytes).unwrap() {
+            keys.push(key);
+        }
+        keys
+    }
+
+    #[test]
+    fn test_read_key() {
+        assert_eq!(
+            keys("aé\x1b[D\x1b[3~\x1bOH\x7f\r".as_bytes()),
+            vec![
+                Key::Char('a'),
+                Key::Char('é'),
+                Key::Left,
+                Key::Delete,
+                Key::Home,
+                Key::Backspace,
+                Key::Enter,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_editing() {
+        let history = Vec::new();
+        let mut line = Line::new(&history);
+        for key in keys(b"let y = 1\x01\x06\x06\x06\x06\x04x\x050") {
+            assert_eq!(line.apply(key), None);
+        }
+        assert_eq!(line.text(), "let x = 10");
+
+        // Ctrl-W takes the number, then the spaces and the word before it
+        for key in keys(b"\x17\x17= 2") {
+            line.apply(key);
+        }
+        assert_eq!(line.text(), "let x = 2");
+
+        line.apply(Key::Home);
+        line.apply(Key::KillToEnd);
+        assert_eq!(line.apply(Key::Delete), Some(Outcome::End));
+    }
+
+    #[test]
+    fn test_history() {
+        let history = vec!["let a = 1".to_string(), "a".to_string()];
+        let mut line = Line::new(&history);
+        line.apply(Key::Char('b'));
+
+        line.apply(Key::Up);
+        assert_eq!(line.text(), "a");
+        line.apply(Key::Up);
+        line.apply(Key::Up);
+        assert_eq!(line.text(), "let a = 1");
+        line.apply(Key::Down);
+        line.apply(Key::Down);
+        assert_eq!(line.text(), "b");
+    }
+}
@@ -0,0 +1,439 @@
+mod editor;
+
+pub use editor::LineEditor;
+
+use crate::ast::*;
+use crate::bytecode::{Compiler, Vm};
+use crate::diagnostic::{Severity, check_program};
+use crate::lexer::{Lexer, Token, TokenType};
+use crate::parser::Parser;
+use crate::typeck::TypeChecker;
+
+/// The module every input of a session is part of
+const MODULE: &str = "repl";
+
+/// Names the value of an expression entered on its own. It is not an
+/// identifier, so it cannot clash with the user's bindings.
+const VALUE: &str = "<value>";
+
+const HELP: &str = "\
+Enter statements to add them to the session, or an expression to see its value.
+  :type <expr>     Show the type of an expression
+  :tokens <input>  Show the tokens of the input
+  :ast <input>     Show the syntax tree of the input
+  :help            Show this message
+  :quit            Leave the session
+";
+
+/// What the session prints in answer to one input
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Reply {
+    pub output: String,      // Values, types and trees
+    pub diagnostics: String, // Errors and warnings, rendered against the session
+    pub quit: bool,
+}
+
+/// An interactive session. Inputs are the statements of one module that
+/// grows as they are accepted: each input is checked and run along with
+/// those before it, and is kept only if that succeeds. Positions count lines
+/// from the first accepted input.
+#[derive(Debug, Default)]
+pub struct Session {
+    source: String, // The accepted inputs, each ending with a newline
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Session::default()
+    }
+
+    /// Returns true if `input` could be a whole entry, and false if it opens
+    /// more braces than it closes so far
+    pub fn is_complete(input: &str) -> bool {
+        let Ok(tokens) = Lexer::new(input).tokenize() else {
+            return true; // Let the error be reported
+        };
+        let depth = tokens
+            .iter()
+            .fold(0i64, |depth, token| match token.token_type {
+                TokenType::LeftBrace => depth + 1,
+                TokenType::RightBrace => depth - 1,
+                _ => depth,
+            });
+        depth <= 0
+    }
+
+    /// Handles one entry: a command, one or more statements, or an expression
+    pub fn eval(&mut self, input: &str) -> Reply {
+        let input = input.trim_end();
+        if let Some(command) = input.strip_prefix(':') {
+            let (name, argument) = command
+                .split_once(char::is_whitespace)
+                .unwrap_or((command, ""));
+            return self.command(name, argument.trim_start());
+        }
+        if input.trim().is_empty() {
+            return Reply::default();
+        }
+
+        let source = format!("{}{}\n", self.source, input);
+        let (program, entry) = match self.parse(&source) {
+            Ok(parsed) => parsed,
+            Err(message) => return error(message),
+        };
+
+        let mut reply = match self.check(&program, &source) {
+            Ok(warnings) => Reply {
+                diagnostics: warnings,
+                ..Reply::default()
+            },
+            Err(errors) => return error(errors),
+        };
+
+        let bytecode = Compiler::new(&program).compile();
+        let globals = match Vm::new(&bytecode).run() {
+            Ok(globals) => globals,
+            Err(err) => {
+                reply.diagnostics += &err.diagnostic().render(&source, MODULE);
+                return reply;
+            }
+        };
+        let bindings = globals.module(MODULE).unwrap_or_default();
+        let value = |name: &str| {
+            bindings
+                .iter()
+                .rev()
+                .find(|(binding, _)| binding == name)
+                .map(|(_, value)| value)
+        };
+
+        match entry {
+            Entry::Expression => {
+                if let Some(value) = value(VALUE) {
+                    reply.output = format!("{}\n", value);
+                }
+            }
+            Entry::Statements(count) => {
+                let statements = &program.modules[0].statements;
+                for statement in &statements[statements.len() - count..] {
+                    let name = match statement {
+                        Statement::Let { name, .. } | Statement::Const { name, .. } => name,
+                        Statement::Assign { target, .. } => &target.name,
+                        Statement::TypeDef(_) => continue,
+                    };
+                    if let Some(value) = value(name) {
+                        reply.output += &format!("{} = {}\n", name, value);
+                    }
+                }
+                self.source = source;
+            }
+        }
+
+        reply
+    }
+
+    fn command(&mut self, name: &str, argument: &str) -> Reply {
+        match name {
+            "type" | "t" => self.type_of(argument),
+            "tokens" => match Lexer::new(argument).tokenize() {
+                Ok(tokens) => Reply {
+                    output: tokens
+                        .iter()
+                        .map(|token| format!("{:?}\n", token))
+                        .collect(),
+                    ..Reply::default()
+                },
+                Err(err) => error(format!("Lexer error: {}", err)),
+            },
+            "ast" => {
+                let parsed = Lexer::new(argument)
+                    .tokenize()
+                    .map_err(|err| format!("Lexer error: {}", err))
+                    .and_then(|tokens| parse_entry(tokens, 0));
+                match parsed {
+                    Ok((program, Entry::Expression)) => {
+                        let Some(Statement::Let { value, .. }) =
+                            program.modules[0].statements.last()
+                        else {
+                            unreachable!("expressions are bound to a value");
+                        };
+                        Reply {
+                            output: format!("{:#?}\n", value),
+                            ..Reply::default()
+                        }
+                    }
+                    Ok((program, Entry::Statements(_))) => Reply {
+                        output: format!("{:#?}\n", program.modules[0].statements),
+                        ..Reply::default()
+                    },
+                    Err(message) => error(message),
+                }
+            }
+            "help" | "h" | "?" => Reply {
+                output: HELP.to_string(),
+                ..Reply::default()
+            },
+            "quit" | "q" => Reply {
+                quit: true,
+                ..Reply::default()
+            },
+            _ => error(format!("Unknown command ':{}'; try :help", name)),
+        }
+    }
+
+    /// Shows the type checker's type for an expression, without running it
+    fn type_of(&self, input: &str) -> Reply {
+        let source = format!("{}{}\n", self.source, input.trim_end());
+        let program = match self.parse(&source) {
+            Ok((program, Entry::Expression)) => program,
+            Ok((_, Entry::Statements(_))) => return error(":type takes an expression"),
+            Err(message) => return error(message),
+        };
+
+        let warnings = match self.check(&program, &source) {
+            Ok(warnings) => warnings,
+            Err(errors) => return error(errors),
+        };
+
+        let Some(Statement::Let { value, .. }) = program.modules[0].statements.last() else {
+            unreachable!("expressions are bound to a value");
+        };
+        let mut checker = TypeChecker::new(&program);
+        checker.check();
+        match checker.type_of(value.id) {
+            Some(ty) => Reply {
+                output: format!("{}\n", ty),
+                diagnostics: warnings,
+                quit: false,
+            },
+            None => error("the type of this expression is unknown"),
+        }
+    }
+
+    /// Parses the session with the latest entry at its end
+    fn parse(&self, source: &str) -> Result<(Program, Entry), String> {
+        let tokens = Lexer::new(source)
+            .tokenize()
+            .map_err(|err| format!("Lexer error: {}", err))?;
+        parse_entry(tokens, self.source.lines().count() + 1)
+    }
+
+    /// Runs the semantic checks, rendering what they find in the latest
+    /// entry: its warnings if it has no errors, and everything otherwise
+    fn check(&self, program: &Program, source: &str) -> Result<String, String> {
+        let first_line = self.source.lines().count() + 1;
+        let mut diagnostics = check_program(program);
+
+        // A `let` may be assigned by a later entry, so the warnings on its
+        // declaration are dropped
+        let lets: Vec<Span> = program
+            .modules
+            .iter()
+            .flat_map(|module| &module.statements)
+            .filter_map(|statement| match statement {
+                Statement::Let { span, .. } => Some(*span),
+                _ => None,
+            })
+            .collect();
+        diagnostics.retain(|diagnostic| {
+            diagnostic.severity == Severity::Error
+                || diagnostic.span().is_none_or(|span| !lets.contains(&span))
+        });
+        // Earlier entries were reported when they were accepted
+        diagnostics
+            .retain(|diagnostic| diagnostic.span().is_none_or(|span| span.line >= first_line));
+        let rendered = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.render(source, MODULE))
+            .collect();
+        if diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+        {
+            Err(rendered)
+        } else {
+            Ok(rendered)
+        }
+    }
+}
+
+/// What the latest entry of a session holds
+enum Entry {
+    Statements(usize), // This many statements, at the end of the module
+    Expression,        // An expression, bound to `VALUE` at the end of the module
+}
+
+/// Parses the statements of a session into its module. The tokens from
+/// `first_line` on are the latest entry; if they do not start a statement,
+/// they are parsed as an expression bound to `VALUE`.
+fn parse_entry(tokens: Vec<Token>, first_line: usize) -> Result<(Program, Entry), String> {
+    let start = tokens
+        .iter()
+        .position(|token| token.line >= first_line)
+        .unwrap_or(tokens.len() - 1);
+    let end = tokens[tokens.len() - 1].clone(); // The EOF token
+
+    let empty = tokens[start].token_type == TokenType::EOF;
+    let entry = if empty || starts_statement(&tokens[start..]) {
+        None
+    } else {
+        Some(start)
+    };
+
+    // Wrap the session in a module, with tokens placed where they would not
+    // point at anything the user wrote
+    let synthetic = |token_type| Token::new(token_type, end.line, end.column);
+    let mut wrapped = vec![
+        synthetic(TokenType::Module),
+        synthetic(TokenType::Identifier(MODULE.to_string())),
+        synthetic(TokenType::LeftBrace),
+    ];
+    for (i, token) in tokens.into_iter().enumerate() {
+        if token.token_type == TokenType::EOF {
+            break;
+        }
+        if entry == Some(i) {
+            // The binding takes the position of the expression, where errors
+            // about it are reported
+            for token_type in [
+                TokenType::Let,
+                TokenType::Identifier(VALUE.to_string()),
+                TokenType::Equals,
+            ] {
+                wrapped.push(Token::new(token_type, token.line, token.column));
+            }
+        }
+        wrapped.push(token);
+    }
+    wrapped.push(synthetic(TokenType::RightBrace));
+    wrapped.push(end);
+
+    let program = Parser::new(wrapped)
+        .parse()
+        .map_err(|err| format!("Parser error: {}", err))?;
+
+    match entry {
+        Some(_) => Ok((program, Entry::Expression)),
+        None => {
+            let count = program.modules[0]
+                .statements
+                .iter()
+                .filter(|statement| statement.span().line >= first_line)
+                .count();
+            Ok((program, Entry::Statements(count)))
+        }
+    }
+}
+
+/// Returns true if the tokens start with a declaration or an assignment,
+/// rather than an expression
+fn starts_statement(tokens: &[Token]) -> bool {
+    let mut tokens = tokens
+        .iter()
+        .map(|token| &token.token_type)
+        .filter(|token_type| !matches!(token_type, TokenType::DocComment(_)));
+
+    match tokens.next() {
+        Some(TokenType::Let | TokenType::Const | TokenType::Type) => true,
+        // `name = value` and `name.field = value`
+        Some(TokenType::Identifier(_)) => loop {
+            match tokens.next() {
+                Some(TokenType::Equals) => break true,
+                Some(TokenType::Dot) => {}
+                _ => break false,
+            }
+            if !matches!(tokens.next(), Some(TokenType::Identifier(_))) {
+                break false;
+            }
+        },
+        _ => false,
+    }
+}
+
+/// Rejects an entry with the given message
+fn error(message: impl Into<String>) -> Reply {
+    let mut diagnostics = message.into();
+    if !diagnostics.ends_with('\n') {
+        diagnostics.push('\n');
+    }
+    Reply {
+        diagnostics,
+        ..Reply::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bindings_persist() {
+        let mut session = Session::new();
+        let reply = session.eval("let port = 8080");
+        assert_eq!(reply.output, "port = 8080\n");
+        // A later entry may still assign it
+        assert_eq!(reply.diagnostics, "");
+        assert_eq!(
+            session.eval("const host = \"localhost\"").output,
+            "host = \"localhost\"\n"
+        );
+        assert_eq!(
+            session.eval("\"${host}:${port + 1}\"").output,
+            "\"localhost:8081\"\n"
+        );
+        assert_eq!(session.eval("port = 443").output, "port = 443\n");
+        assert_eq!(session.eval("port").output, "443\n");
+    }
+
+    #[test]
+    fn test_rejected_entries_are_dropped() {
+        let mut session = Session::new();
+        session.eval("const a = 1");
+
+        let reply = session.eval("let b = a / 0");
+        assert_eq!(reply.output, "");
+        assert!(
+            reply
+                .diagnostics
+                .starts_with("error: division by zero\n --> repl:2:9\n")
+        );
+
+        // `b` was never defined, and the next entry takes its line
+        let reply = session.eval("b");
+        assert!(reply.diagnostics.contains("--> repl:2:1"));
+        assert_eq!(session.eval("let b = a").output, "b = 1\n");
+    }
+
+    #[test]
+    fn test_commands() {
+        let mut session = Session::new();
+        session.eval("type Server => {\n  host: String,\n  port: Number = 80\n}");
+        session.eval("let server: Server = { host: \"a\" }");
+
+        assert_eq!(session.eval(":type server").output, "Server\n");
+        assert_eq!(
+            session.eval(":type { port: 1 }").output,
+            "{ port: Number }\n"
+        );
+        assert_eq!(
+            session.eval(":type let x = 1").diagnostics,
+            ":type takes an expression\n"
+        );
+        assert_eq!(
+            session.eval(":tokens 1").output,
+            "Token { token_type: NumberLiteral(1.0), line: 1, column: 1 }\n\
+             Token { token_type: EOF, line: 1, column: 2 }\n"
+        );
+        assert!(session.eval(":ast -x").output.starts_with("Expr {"));
+        assert_eq!(session.eval(":ast").output, "[]\n");
+        assert!(session.eval(":quit").quit);
+    }
+
+    #[test]
+    fn test_is_complete() {
+        assert!(Session::is_complete("let x = 1"));
+        assert!(!Session::is_complete("let s = {\n  a: { b: 1 }"));
+        assert!(Session::is_complete("let s = {\n  a: { b: 1 }\n}"));
+        assert!(Session::is_complete("let s = \"{\""));
+    }
+}
@@ -0,0 +1,163 @@
+use std::fmt;
+
+use crate::ast::Span;
+use crate::diagnostic::Diagnostic;
+
+use super::Namespace;
+
+/// Represents problems found while resolving names
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolveError {
+    /// A name is used but never defined in its module
+    UndefinedName {
+        name: String,
+        namespace: Namespace,
+        suggestion: Option<String>, // The closest defined name, if any is close enough
+        span: Span,
+    },
+
+    /// A module defines the same name twice
+    DuplicateDefinition {
+        name: String,
+        first: Span,
+        span: Span,
+    },
+
+    /// A value is used before the statement that defines it, which has not
+    /// run yet at that point
+    UsedBeforeDefinition {
+        name: String,
+        definition: Span,
+        span: Span,
+    },
+}
+
+impl ResolveError {
+    /// Returns the position the error is reported at
+    pub fn span(&self) -> Span {
+        match self {
+            ResolveError::UndefinedName { span, .. }
+            | ResolveError::DuplicateDefinition { span, .. }
+            | ResolveError::UsedBeforeDefinition { span, .. } => *span,
+        }
+    }
+
+    /// Describes the error, pointing duplicates back at the first definition
+    pub fn diagnostic(&self) -> Diagnostic {
+        match self {
+            ResolveError::UndefinedName {
+                name,
+                namespace,
+                suggestion,
+                span,
+            } => {
+                let diagnostic = Diagnostic::error(format!("undefined {} '{}'", namespace, name))
+                    .with_label(*span, "not defined in this module");
+                match suggestion {
+                    Some(suggestion) => {
+                        diagnostic.with_note(format!("did you mean '{}'?", suggestion))
+                    }
+                    None => diagnostic,
+                }
+            }
+            ResolveError::DuplicateDefinition { name, first, span } => {
+                Diagnostic::error(format!("duplicate definition of '{}'", name))
+                    .with_label(*span, "defined again here")
+                    .with_label(*first, "first defined here")
+            }
+            ResolveError::UsedBeforeDefinition {
+                name,
+                definition,
+                span,
+            } => Diagnostic::error(format!("'{}' is used before its definition", name))
+                .with_label(*span, "used here")
+                .with_label(*definition, "defined later here")
+                .with_note("statements run in order, so move the definition up"),
+        }
+    }
+}
+
+impl std::error::Error for ResolveError {}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolveError::UndefinedName {
+                name,
+                namespace,
+                suggestion,
+                span,
+            } => {
+                write!(f, "Undefined {} '{}' at {}", namespace, name, span)?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, "; did you mean '{}'?", suggestion)?;
+                }
+                Ok(())
+            }
+            ResolveError::DuplicateDefinition { name, first, span } => write!(
+                f,
+                "Duplicate definition of '{}' at {}; first defined at {}",
+                name, span, first
+            ),
+            ResolveError::UsedBeforeDefinition {
+                name,
+                definition,
+                span,
+            } => write!(
+                f,
+                "'{}' is used at {} before its definition at {}",
+                name, span, definition
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_display() {
+        let err = ResolveError::UndefinedName {
+            name: "hots".to_string(),
+            namespace: Namespace::Value,
+            suggestion: Some("host".to_string()),
+            span: Span::new(3, 15),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Undefined variable 'hots' at line 3, column 15; did you mean 'host'?"
+        );
+
+        let err = ResolveError::UndefinedName {
+            name: "Thing".to_string(),
+            namespace: Namespace::Type,
+            suggestion: None,
+            span: Span::new(1, 12),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Undefined type 'Thing' at line 1, column 12"
+        );
+
+        let err = ResolveError::DuplicateDefinition {
+            name: "x".to_string(),
+            first: Span::new(2, 9),
+            span: Span::new(4, 9),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Duplicate definition of 'x' at line 4, column 9; first defined at line 2, column 9"
+        );
+
+        let err = ResolveError::UsedBeforeDefinition {
+            name: "b".to_string(),
+            definition: Span::new(3, 9),
+            span: Span::new(2, 13),
+        };
+        assert_eq!(
+            err.to_string(),
+            "'b' is used at line 2, column 13 before its definition at line 3, column 9"
+        );
+    }
+}
@@ -0,0 +1,658 @@
+mod error;
+
+pub use error::ResolveError;
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::ast::*;
+
+/// Identifies a definition: the module it belongs to and its position in
+/// that module's list of definitions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DefId {
+    pub module: usize,
+    pub index: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefKind {
+    Let,
+    Const,
+    Type,
+    TypeParam, // Visible only inside the type definition that declares it
+}
+
+impl DefKind {
+    /// Returns the namespace names of this kind are looked up in
+    pub fn namespace(self) -> Namespace {
+        match self {
+            DefKind::Let | DefKind::Const => Namespace::Value,
+            DefKind::Type | DefKind::TypeParam => Namespace::Type,
+        }
+    }
+}
+
+/// Values and types are named independently, so `type Env` and `const env`
+/// never clash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Namespace {
+    Value,
+    Type,
+}
+
+impl fmt::Display for Namespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Namespace::Value => write!(f, "variable"),
+            Namespace::Type => write!(f, "type"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    pub name: String,
+    pub kind: DefKind,
+    pub span: Span,
+}
+
+/// A use of a name, linked to the definition it refers to
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference {
+    pub name: String,
+    pub span: Span,
+    pub def: DefId,
+}
+
+/// The names defined at the top level of a single module
+#[derive(Debug, Default)]
+pub struct Scope {
+    values: HashMap<String, DefId>,
+    types: HashMap<String, DefId>,
+}
+
+impl Scope {
+    /// Returns the definition `name` refers to in `namespace`
+    pub fn lookup(&self, namespace: Namespace, name: &str) -> Option<DefId> {
+        match namespace {
+            Namespace::Value => self.values.get(name).copied(),
+            Namespace::Type => self.types.get(name).copied(),
+        }
+    }
+
+    fn names(&mut self, namespace: Namespace) -> &mut HashMap<String, DefId> {
+        match namespace {
+            Namespace::Value => &mut self.values,
+            Namespace::Type => &mut self.types,
+        }
+    }
+}
+
+/// Every definition in a program and every use of a name that was resolved
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    definitions: Vec<Vec<Definition>>, // Indexed by module, then by `DefId::index`
+    scopes: Vec<Scope>,
+    references: Vec<Reference>,
+}
+
+impl SymbolTable {
+    pub fn definition(&self, id: DefId) -> &Definition {
+        &self.definitions[id.module][id.index]
+    }
+
+    /// Returns the top-level scope of the module at `module`
+    pub fn scope(&self, module: usize) -> &Scope {
+        &self.scopes[module]
+    }
+
+    /// Returns every resolved use of a name, in source order
+    pub fn references(&self) -> &[Reference] {
+        &self.references
+    }
+
+    /// Returns the definition referred to by the identifier expression or
+    /// assignment target at `span`
+    pub fn reference_at(&self, span: Span) -> Option<DefId> {
+        self.references
+            .iter()
+            .find(|reference| reference.span == span)
+            .map(|reference| reference.def)
+    }
+}
+
+/// Type names that are always defined, offered as suggestions for misspellings
+const BUILTIN_TYPES: [&str; 3] = ["Number", "String", "Boolean"];
+
+/// Links every identifier and type name in a program to its definition
+pub struct Resolver<'a> {
+    program: &'a Program,
+    table: SymbolTable,
+    errors: Vec<ResolveError>,
+    defined: HashSet<DefId>, // Values whose defining statement has been passed
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        Resolver {
+            program,
+            table: SymbolTable::default(),
+            errors: Vec::new(),
+            defined: HashSet::new(),
+        }
+    }
+
+    /// Resolves every module, returning the symbol table along with any
+    /// undefined or duplicate names
+    pub fn resolve(mut self) -> (SymbolTable, Vec<ResolveError>) {
+        for (index, module) in self.program.modules.iter().enumerate() {
+            self.table.definitions.push(Vec::new());
+            self.table.scopes.push(Scope::default());
+            self.declare_module(index, module);
+        }
+
+        // Types are visible throughout their module, so uses are only
+        // resolved once every definition is known. Values only exist once
+        // their statement has run, so they are visible from there on.
+        for (index, module) in self.program.modules.iter().enumerate() {
+            for statement in &module.statements {
+                match statement {
+                    Statement::Let {
+                        name,
+                        ty,
+                        value,
+                        span,
+                    }
+                    | Statement::Const {
+                        name,
+                        ty,
+                        value,
+                        span,
+                    } => {
+                        if let Some(ty) = ty {
+                            self.resolve_type(index, &HashMap::new(), ty, *span);
+                        }
+                        self.resolve_expression(index, value);
+                        if let Some(def) = self.table.scopes[index].lookup(Namespace::Value, name) {
+                            self.defined.insert(def);
+                        }
+                    }
+                    Statement::TypeDef(def) => self.resolve_type_definition(index, def),
+                    Statement::Assign {
+                        target,
+                        value,
+                        span,
+                    } => {
+                        let def = self.table.scopes[index].lookup(Namespace::Value, &target.name);
+                        self.record_value(index, &target.name, def, *span);
+                        self.resolve_expression(index, value);
+                    }
+                }
+            }
+        }
+
+        (self.table, self.errors)
+    }
+
+    fn declare_module(&mut self, module: usize, ast: &Module) {
+        for statement in &ast.statements {
+            let (name, kind, span) = match statement {
+                Statement::Let { name, span, .. } => (name, DefKind::Let, *span),
+                Statement::Const { name, span, .. } => (name, DefKind::Const, *span),
+                Statement::TypeDef(def) => (&def.name, DefKind::Type, def.span),
+                Statement::Assign { .. } => continue,
+            };
+
+            if let Some(first) = self.table.scopes[module].lookup(kind.namespace(), name) {
+                self.errors.push(ResolveError::DuplicateDefinition {
+                    name: name.clone(),
+                    first: self.table.definition(first).span,
+                    span,
+                });
+                continue;
+            }
+
+            let id = self.define(module, name, kind, span);
+            self.table.scopes[module]
+                .names(kind.namespace())
+                .insert(name.clone(), id);
+        }
+    }
+
+    fn define(&mut self, module: usize, name: &str, kind: DefKind, span: Span) -> DefId {
+        let definitions = &mut self.table.definitions[module];
+        definitions.push(Definition {
+            name: name.to_string(),
+            kind,
+            span,
+        });
+        DefId {
+            module,
+            index: definitions.len() - 1,
+        }
+    }
+
+    fn resolve_type_definition(&mut self, module: usize, def: &TypeDefinition) {
+        // Duplicate parameters are reported by the type checker
+        let mut params = HashMap::new();
+        for param in &def.params {
+            if !params.contains_key(param.as_str()) {
+                let id = self.define(module, param, DefKind::TypeParam, def.span);
+                params.insert(param.as_str(), id);
+            }
+        }
+
+        for base in &def.extends {
+            self.resolve_type(module, &params, base, def.span);
+        }
+        if let Some(alias) = &def.alias {
+            self.resolve_type(module, &params, alias, def.span);
+        }
+        for field in &def.fields {
+            self.resolve_type(module, &params, &field.field_type, field.span);
+            if let Some(default) = &field.default {
+                self.resolve_expression(module, default);
+            }
+        }
+    }
+
+    /// Resolves the names in `ty`, which has no position of its own and is
+    /// reported at the `span` of whatever it annotates
+    fn resolve_type(
+        &mut self,
+        module: usize,
+        params: &HashMap<&str, DefId>,
+        ty: &Type,
+        span: Span,
+    ) {
+        match ty {
+            Type::Number | Type::String | Type::Boolean | Type::Literal(_) => {}
+            Type::List(element) => self.resolve_type(module, params, element, span),
+            Type::Object(fields) => {
+                for (_, field_type) in fields {
+                    self.resolve_type(module, params, field_type, span);
+                }
+            }
+            Type::Union(members) => {
+                for member in members {
+                    self.resolve_type(module, params, member, span);
+                }
+            }
+            Type::Custom(name, args) => {
+                let def = params
+                    .get(name.as_str())
+                    .copied()
+                    .or_else(|| self.table.scopes[module].lookup(Namespace::Type, name));
+                self.record(module, Namespace::Type, name, def, span);
+
+                for arg in args {
+                    self.resolve_type(module, params, arg, span);
+                }
+            }
+        }
+    }
+
+    fn resolve_expression(&mut self, module: usize, expr: &Expr) {
+        match &expr.kind {
+            Expression::NumberLiteral(_)
+            | Expression::StringLiteral(_)
+            | Expression::BooleanLiteral(_) => {}
+            Expression::Identifier(name) => {
+                let def = self.table.scopes[module].lookup(Namespace::Value, name);
+                self.record_value(module, name, def, expr.span);
+            }
+            Expression::Object { entries } => {
+                for entry in entries {
+                    match entry {
+                        ObjectEntry::Field { value, .. } | ObjectEntry::Spread(value) => {
+                            self.resolve_expression(module, value)
+                        }
+                    }
+                }
+            }
+            Expression::List(elements) => {
+                for element in elements {
+                    self.resolve_expression(module, element);
+                }
+            }
+            Expression::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.resolve_expression(module, condition);
+                self.resolve_expression(module, then_branch);
+                self.resolve_expression(module, else_branch);
+            }
+            Expression::Match { scrutinee, arms } => {
+                self.resolve_expression(module, scrutinee);
+                for arm in arms {
+                    self.resolve_expression(module, &arm.body);
+                }
+            }
+            Expression::Template(parts) => {
+                for part in parts {
+                    if let Part::Expr(value) = part {
+                        self.resolve_expression(module, value);
+                    }
+                }
+            }
+            Expression::Unary { operand, .. } => self.resolve_expression(module, operand),
+            Expression::Binary { left, right, .. } => {
+                self.resolve_expression(module, left);
+                self.resolve_expression(module, right);
+            }
+        }
+    }
+
+    /// Records a use of `name`, or reports it if `def` is `None`
+    fn record(
+        &mut self,
+        module: usize,
+        namespace: Namespace,
+        name: &str,
+        def: Option<DefId>,
+        span: Span,
+    ) {
+        match def {
+            Some(def) => self.table.references.push(Reference {
+                name: name.to_string(),
+                span,
+                def,
+            }),
+            None => self.errors.push(ResolveError::UndefinedName {
+                name: name.to_string(),
+                namespace,
+                suggestion: self.suggest(module, namespace, name),
+                span,
+            }),
+        }
+    }
+
+    /// Records a use of the value `name`, or reports it if `def` is `None`
+    /// or its statement has not run yet
+    fn record_value(&mut self, module: usize, name: &str, def: Option<DefId>, span: Span) {
+        match def {
+            Some(def) if !self.defined.contains(&def) => {
+                self.errors.push(ResolveError::UsedBeforeDefinition {
+                    name: name.to_string(),
+                    definition: self.table.definition(def).span,
+                    span,
+                })
+            }
+            _ => self.record(module, Namespace::Value, name, def, span),
+        }
+    }
+
+    /// Finds the defined name closest to `name`, if any is close enough to
+    /// be a likely typo. Values are only offered once their statement has
+    /// been passed, which leaves out the one being defined.
+    fn suggest(&self, module: usize, namespace: Namespace, name: &str) -> Option<String> {
+        let scope = &self.table.scopes[module];
+        let mut candidates: Vec<&str> = match namespace {
+            Namespace::Value => scope
+                .values
+                .iter()
+                .filter(|(_, def)| self.defined.contains(def))
+                .map(|(name, _)| name.as_str())
+                .collect(),
+            Namespace::Type => scope.types.keys().map(String::as_str).collect(),
+        };
+        if namespace == Namespace::Type {
+            candidates.extend(BUILTIN_TYPES);
+        }
+
+        // Allow roughly one edit for every three characters
+        let limit = (name.chars().count() / 3).max(1);
+        candidates
+            .into_iter()
+            .map(|candidate| (edit_distance(name, candidate), candidate))
+            .filter(|(distance, _)| *distance <= limit)
+            .min()
+            .map(|(_, candidate)| candidate.to_string())
+    }
+}
+
+/// Returns the number of single-character insertions, deletions,
+/// substitutions and swaps of adjacent characters needed to turn `a` into `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // distances[i][j] is the distance between the first i chars of `a` and
+    // the first j chars of `b`
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn parse(source: &str) -> Program {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        Parser::new(tokens).parse().unwrap()
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("host", "host"), 0);
+        assert_eq!(edit_distance("hots", "host"), 1);
+        assert_eq!(edit_distance("prot", "port"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("Usr", "User"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_links_uses_to_definitions() {
+        let program = parse(
+            "module m {
+                const PORT = 8080
+                type Page<T> => { items: T[], size: Number = PORT }
+                type User => { name: String }
+                let users: Page<User> = { size: 1 }
+                let host = \"localhost\"
+                let server = { port: PORT, url: \"http://${host}\" }
+            }",
+        );
+        let (table, errors) = Resolver::new(&program).resolve();
+        assert!(errors.is_empty());
+
+        let port = table.scope(0).lookup(Namespace::Value, "PORT").unwrap();
+        assert_eq!(table.definition(port).kind, DefKind::Const);
+
+        let host = table.reference_at(Span::new(7, 59)).unwrap();
+        assert_eq!(table.definition(host).name, "host");
+        assert_eq!(table.definition(host).span, Span::new(6, 21));
+
+        let names: Vec<(&str, DefKind)> = table
+            .references()
+            .iter()
+            .map(|reference| {
+                let def = table.definition(reference.def);
+                (reference.name.as_str(), def.kind)
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("T", DefKind::TypeParam),
+                ("PORT", DefKind::Const),
+                ("Page", DefKind::Type),
+                ("User", DefKind::Type),
+                ("PORT", DefKind::Const),
+                ("host", DefKind::Let),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_values_resolve_in_declaration_order() {
+        let program = parse(
+            "module m {
+                type Server => { port: Number = PORT }
+                let a = b
+                let b = 1
+                const PORT = 80
+                let c = c
+                b = a
+            }",
+        );
+        let (_, errors) = Resolver::new(&program).resolve();
+
+        // Types stay visible throughout, so only values are reported
+        assert_eq!(
+            errors,
+            vec![
+                ResolveError::UsedBeforeDefinition {
+                    name: "PORT".to_string(),
+                    definition: Span::new(5, 23),
+                    span: Span::new(2, 49),
+                },
+                ResolveError::UsedBeforeDefinition {
+                    name: "b".to_string(),
+                    definition: Span::new(4, 21),
+                    span: Span::new(3, 25),
+                },
+                ResolveError::UsedBeforeDefinition {
+                    name: "c".to_string(),
+                    definition: Span::new(6, 21),
+                    span: Span::new(6, 25),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_undefined_names_and_suggestions() {
+        let program = parse(
+            "module m {
+                type User => { name: Strin, role: Rol }
+                const host = \"localhost\"
+                let url = hots
+                let other = completely_unknown
+                const timeout = timeot
+                const u = \"${x}\"
+                let later = retrie
+                const retries = 1
+            }
+            module n {
+                let url = host
+            }",
+        );
+        let (_, errors) = Resolver::new(&program).resolve();
+
+        assert_eq!(
+            errors,
+            vec![
+                ResolveError::UndefinedName {
+                    name: "Strin".to_string(),
+                    namespace: Namespace::Type,
+                    suggestion: Some("String".to_string()),
+                    span: Span::new(2, 32),
+                },
+                ResolveError::UndefinedName {
+                    name: "Rol".to_string(),
+                    namespace: Namespace::Type,
+                    suggestion: None,
+                    span: Span::new(2, 45),
+                },
+                ResolveError::UndefinedName {
+                    name: "hots".to_string(),
+                    namespace: Namespace::Value,
+                    suggestion: Some("host".to_string()),
+                    span: Span::new(4, 27),
+                },
+                ResolveError::UndefinedName {
+                    name: "completely_unknown".to_string(),
+                    namespace: Namespace::Value,
+                    suggestion: None,
+                    span: Span::new(5, 29),
+                },
+                // Neither the binding being defined nor later ones are offered
+                ResolveError::UndefinedName {
+                    name: "timeot".to_string(),
+                    namespace: Namespace::Value,
+                    suggestion: None,
+                    span: Span::new(6, 33),
+                },
+                ResolveError::UndefinedName {
+                    name: "x".to_string(),
+                    namespace: Namespace::Value,
+                    suggestion: None,
+                    span: Span::new(7, 30),
+                },
+                ResolveError::UndefinedName {
+                    name: "retrie".to_string(),
+                    namespace: Namespace::Value,
+                    suggestion: None,
+                    span: Span::new(8, 29),
+                },
+                // Modules do not see each other's names
+                ResolveError::UndefinedName {
+                    name: "host".to_string(),
+                    namespace: Namespace::Value,
+                    suggestion: None,
+                    span: Span::new(12, 27),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_definitions() {
+        let program = parse(
+            "module m {
+                let x = 1
+                type x => { value: Number }
+                const x = 2
+                type x => { other: Number }
+            }",
+        );
+        let (table, errors) = Resolver::new(&program).resolve();
+
+        assert_eq!(
+            errors,
+            vec![
+                ResolveError::DuplicateDefinition {
+                    name: "x".to_string(),
+                    first: Span::new(2, 21),
+                    span: Span::new(4, 23),
+                },
+                ResolveError::DuplicateDefinition {
+                    name: "x".to_string(),
+                    first: Span::new(3, 22),
+                    span: Span::new(5, 22),
+                },
+            ]
+        );
+
+        // The first definition wins
+        let x = table.scope(0).lookup(Namespace::Value, "x").unwrap();
+        assert_eq!(table.definition(x).kind, DefKind::Let);
+    }
+}
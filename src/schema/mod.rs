@@ -0,0 +1,525 @@
+use std::collections::VecDeque;
+
+use crate::ast::*;
+use crate::eval::{Environment, EvalError, Value, evaluate};
+use crate::json::JsonValue;
+use crate::typeck::TypeEnv;
+
+/// The `$schema` dialect every generated document declares
+pub const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Generates JSON Schema (Draft 2020-12) documents from the type definitions
+/// of a module. Each definition becomes an entry in `$defs` and named types
+/// refer to each other with `$ref`. JSON Schema has no generics, so each
+/// instantiation of a generic type gets an entry of its own, such as
+/// `PairOfNumberAndString` for `Pair<Number, String>`.
+pub struct SchemaGenerator<'a> {
+    module: &'a Module,
+    types: TypeEnv<'a>,
+}
+
+impl<'a> SchemaGenerator<'a> {
+    pub fn new(module: &'a Module) -> Self {
+        SchemaGenerator {
+            module,
+            types: TypeEnv::new(module),
+        }
+    }
+
+    /// Returns a document whose `$defs` describe every non-generic
+    /// definition of the module, along with the instantiations they use
+    pub fn module_schema(&self) -> JsonValue {
+        let roots = self
+            .module
+            .statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::TypeDef(def) if def.params.is_empty() => {
+                    Some(Type::Custom(def.name.clone(), Vec::new()))
+                }
+                _ => None,
+            })
+            .collect();
+
+        JsonValue::Object(vec![
+            (
+                "$schema".to_string(),
+                JsonValue::String(SCHEMA_DIALECT.to_string()),
+            ),
+            (
+                "title".to_string(),
+                JsonValue::String(self.module.name.clone()),
+            ),
+            ("$defs".to_string(), self.definitions(roots)),
+        ])
+    }
+
+    /// Returns a document that validates values of `def`, holding in `$defs`
+    /// only the definitions it refers to
+    pub fn type_schema(&self, def: &TypeDefinition) -> JsonValue {
+        let root = Type::Custom(def.name.clone(), Vec::new());
+        JsonValue::Object(vec![
+            (
+                "$schema".to_string(),
+                JsonValue::String(SCHEMA_DIALECT.to_string()),
+            ),
+            ("title".to_string(), JsonValue::String(def.name.clone())),
+            // The root is keyed first, so it always gets its own name
+            ("$ref".to_string(), reference(&def.name)),
+            ("$defs".to_string(), self.definitions(vec![root])),
+        ])
+    }
+
+    /// Builds the `$defs` entries for `roots` and everything they refer to
+    fn definitions(&self, roots: Vec<Type>) -> JsonValue {
+        let mut pending = Pending::default();
+        for root in &roots {
+            pending.key(root);
+        }
+        let mut entries: Vec<(String, JsonValue)> = Vec::new();
+
+        while let Some((ty, key)) = pending.queue.pop_front() {
+            let Type::Custom(name, args) = &ty else {
+                continue;
+            };
+            let Some(def) = self.types.lookup(name) else {
+                continue;
+            };
+
+            let schema = self.definition_schema(def, args, &mut pending);
+            entries.push((key, schema));
+        }
+
+        JsonValue::Object(entries)
+    }
+
+    fn definition_schema(
+        &self,
+        def: &TypeDefinition,
+        args: &[Type],
+        pending: &mut Pending,
+    ) -> JsonValue {
+        let mut schema = match &def.alias {
+            Some(alias) => self.schema(&alias.substitute(&def.params, args), pending),
+            None => self.record_schema(&self.types.flattened_fields(def, args), pending),
+        };
+        describe(&mut schema, def.docs.as_deref());
+        schema
+    }
+
+    fn record_schema(&self, fields: &[TypeField], pending: &mut Pending) -> JsonValue {
+        let mut properties = Vec::new();
+        let mut required = Vec::new();
+
+        for field in fields {
+            let mut schema = self.schema(&field.field_type, pending);
+            describe(&mut schema, field.docs.as_deref());
+
+            match &field.default {
+                Some(default) => {
+                    // Defaults that refer to other bindings are left out
+                    let value = evaluate(
+                        &self.types,
+                        &mut NoBindings,
+                        default,
+                        Some(&field.field_type),
+                    );
+                    if let (Ok(value), JsonValue::Object(members)) = (value, &mut schema) {
+                        members.push(("default".to_string(), JsonValue::from(&value)));
+                    }
+                }
+                None => required.push(JsonValue::String(field.name.clone())),
+            }
+            properties.push((field.name.clone(), schema));
+        }
+
+        let mut schema = vec![
+            ("type".to_string(), JsonValue::String("object".to_string())),
+            ("properties".to_string(), JsonValue::Object(properties)),
+        ];
+        if !required.is_empty() {
+            schema.push(("required".to_string(), JsonValue::Array(required)));
+        }
+        schema.push(("additionalProperties".to_string(), JsonValue::Bool(false)));
+        JsonValue::Object(schema)
+    }
+
+    fn schema(&self, ty: &Type, pending: &mut Pending) -> JsonValue {
+        let object = |members: Vec<(&str, JsonValue)>| {
+            JsonValue::Object(
+                members
+                    .into_iter()
+                    .map(|(key, value)| (key.to_string(), value))
+                    .collect(),
+            )
+        };
+        let primitive = |name: &str| object(vec![("type", JsonValue::String(name.to_string()))]);
+
+        match ty {
+            Type::Number => primitive("number"),
+            Type::String => primitive("string"),
+            Type::Boolean => primitive("boolean"),
+            Type::Literal(literal) => object(vec![("const", literal_value(literal))]),
+            Type::List(element) => object(vec![
+                ("type", JsonValue::String("array".to_string())),
+                ("items", self.schema(element, pending)),
+            ]),
+            Type::Object(fields) => {
+                let fields: Vec<TypeField> = fields
+                    .iter()
+                    .map(|(name, field_type)| TypeField {
+                        name: name.clone(),
+                        field_type: field_type.clone(),
+                        default: None,
+                        docs: None,
+                        span: Span::default(),
+                    })
+                    .collect();
+                self.record_schema(&fields, pending)
+            }
+            Type::Union(members) => {
+                let literals: Option<Vec<JsonValue>> = members
+                    .iter()
+                    .map(|member| match member {
+                        Type::Literal(literal) => Some(literal_value(literal)),
+                        _ => None,
+                    })
+                    .collect();
+                match literals {
+                    Some(literals) => object(vec![("enum", JsonValue::Array(literals))]),
+                    None => object(vec![(
+                        "anyOf",
+                        JsonValue::Array(
+                            members
+                                .iter()
+                                .map(|member| self.schema(member, pending))
+                                .collect(),
+                        ),
+                    )]),
+                }
+            }
+            // Type parameters and unknown names accept anything; the type
+            // checker reports names that are not defined
+            Type::Custom(name, _) if self.types.lookup(name).is_none() => object(vec![]),
+            Type::Custom(..) => object(vec![("$ref", reference(&pending.key(ty)))]),
+        }
+    }
+}
+
+/// The named types still to describe, and the `$defs` key of every named
+/// type seen so far
+#[derive(Default)]
+struct Pending {
+    queue: VecDeque<(Type, String)>,
+    keys: Vec<(Type, String)>,
+}
+
+impl Pending {
+    /// Returns the key of `ty`, queueing it the first time it is seen. Two
+    /// types can spell the same key, as `Box<"a">` and `Box<"b">` both spell
+    /// `BoxOfLiteral`, so later ones get a number appended.
+    fn key(&mut self, ty: &Type) -> String {
+        if let Some((_, key)) = self.keys.iter().find(|(seen, _)| seen == ty) {
+            return key.clone();
+        }
+
+        let spelled = def_key(ty);
+        let mut key = spelled.clone();
+        let mut n = 1;
+        while self.keys.iter().any(|(_, taken)| *taken == key) {
+            n += 1;
+            key = format!("{}{}", spelled, n);
+        }
+
+        self.keys.push((ty.clone(), key.clone()));
+        self.queue.push_back((ty.clone(), key.clone()));
+        key
+    }
+}
+
+/// An environment without bindings, for evaluating self-contained defaults
+struct NoBindings;
+
+impl Environment for NoBindings {
+    type Error = EvalError;
+
+    fn lookup(&mut self, name: &str, span: Span) -> Result<Value, EvalError> {
+        Err(EvalError::UndefinedVariable {
+            name: name.to_string(),
+            span,
+        })
+    }
+}
+
+fn describe(schema: &mut JsonValue, docs: Option<&str>) {
+    if let (Some(docs), JsonValue::Object(members)) = (docs, schema) {
+        members.insert(
+            0,
+            (
+                "description".to_string(),
+                JsonValue::String(docs.to_string()),
+            ),
+        );
+    }
+}
+
+fn reference(key: &str) -> JsonValue {
+    JsonValue::String(format!("#/$defs/{}", key))
+}
+
+fn literal_value(literal: &Literal) -> JsonValue {
+    match literal {
+        Literal::Number(n) => JsonValue::Number(*n),
+        Literal::String(s) => JsonValue::String(s.clone()),
+        Literal::Boolean(b) => JsonValue::Bool(*b),
+    }
+}
+
+/// Returns the `$defs` key of a named type; instantiations of generic types
+/// spell out their arguments, as in `BoxOfNumberList` for `Box<Number[]>`
+fn def_key(ty: &Type) -> String {
+    match ty {
+        Type::Number => "Number".to_string(),
+        Type::String => "String".to_string(),
+        Type::Boolean => "Boolean".to_string(),
+        Type::Custom(name, args) if args.is_empty() => name.clone(),
+        Type::Custom(name, args) => format!(
+            "{}Of{}",
+            name,
+            args.iter().map(def_key).collect::<Vec<_>>().join("And")
+        ),
+        Type::List(element) => format!("{}List", def_key(element)),
+        Type::Object(_) => "Object".to_string(),
+        Type::Literal(_) => "Literal".to_string(),
+        Type::Union(members) => members.iter().map(def_key).collect::<Vec<_>>().join("Or"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::write_json;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn parse(source: &str) -> Program {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        Parser::new(tokens).parse().unwrap()
+    }
+
+    #[test]
+    fn test_type_schema() {
+        let program = parse(
+            "module users {
+                /// Access level
+                type Role => \"admin\" | \"member\"
+
+                /// A registered user
+                type User extends Named => {
+                    /// Unique across all users
+                    id: Number,
+                    active: Boolean = true,
+                    role: Role,
+                    tags: String[],
+                    home: Pair<Number, String> | Number,
+                    settings: { theme: String }
+                }
+
+                type Named => { name: String }
+                type Pair<A, B> => { first: A, second: B }
+                type Unused => { x: Number }
+            }",
+        );
+        let module = &program.modules[0];
+        let def = TypeEnv::new(module).lookup("User").unwrap();
+
+        assert_eq!(
+            write_json(&SchemaGenerator::new(module).type_schema(def)),
+            r##"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "title": "User",
+  "$ref": "#/$defs/User",
+  "$defs": {
+    "User": {
+      "description": "A registered user",
+      "type": "object",
+      "properties": {
+        "name": {
+          "type": "string"
+        },
+        "id": {
+          "description": "Unique across all users",
+          "type": "number"
+        },
+        "active": {
+          "type": "boolean",
+          "default": true
+        },
+        "role": {
+          "$ref": "#/$defs/Role"
+        },
+        "tags": {
+          "type": "array",
+          "items": {
+            "type": "string"
+          }
+        },
+        "home": {
+          "anyOf": [
+            {
+              "$ref": "#/$defs/PairOfNumberAndString"
+            },
+            {
+              "type": "number"
+            }
+          ]
+        },
+        "settings": {
+          "type": "object",
+          "properties": {
+            "theme": {
+              "type": "string"
+            }
+          },
+          "required": [
+            "theme"
+          ],
+          "additionalProperties": false
+        }
+      },
+      "required": [
+        "name",
+        "id",
+        "role",
+        "tags",
+        "home",
+        "settings"
+      ],
+      "additionalProperties": false
+    },
+    "Role": {
+      "description": "Access level",
+      "enum": [
+        "admin",
+        "member"
+      ]
+    },
+    "PairOfNumberAndString": {
+      "type": "object",
+      "properties": {
+        "first": {
+          "type": "number"
+        },
+        "second": {
+          "type": "string"
+        }
+      },
+      "required": [
+        "first",
+        "second"
+      ],
+      "additionalProperties": false
+    }
+  }
+}"##
+        );
+    }
+
+    #[test]
+    fn test_module_schema() {
+        let program = parse(
+            "module m {
+                type Tree => { value: Number, children: Tree[] }
+                type Box<T> => { value: T }
+                type Leaf => { limit: Number = 10 * 2, name: String = \"x${1}\", other: Number = MAX }
+                const MAX = 3
+            }",
+        );
+        let schema = SchemaGenerator::new(&program.modules[0]).module_schema();
+
+        let JsonValue::Object(members) = &schema else {
+            panic!("Expected an object");
+        };
+        assert_eq!(members[1].1, JsonValue::String("m".to_string()));
+        let JsonValue::Object(defs) = &members[2].1 else {
+            panic!("Expected $defs");
+        };
+        let keys: Vec<&str> = defs.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, vec!["Tree", "Leaf"]);
+
+        // Self-contained defaults are evaluated; ones naming bindings are left out
+        let defaults: Vec<Option<&JsonValue>> = match &defs[1].1 {
+            JsonValue::Object(leaf) => match &leaf[1].1 {
+                JsonValue::Object(properties) => properties
+                    .iter()
+                    .map(|(_, property)| match property {
+                        JsonValue::Object(keywords) => keywords
+                            .iter()
+                            .find(|(key, _)| key == "default")
+                            .map(|(_, value)| value),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => panic!("Expected properties"),
+            },
+            _ => panic!("Expected a schema"),
+        };
+        assert_eq!(
+            defaults,
+            vec![
+                Some(&JsonValue::Number(20.0)),
+                Some(&JsonValue::String("x1".to_string())),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_instantiations_with_the_same_spelling() {
+        let program = parse(
+            "module m {
+                type Box<T> => { value: T }
+                type BoxOfNumber => { n: Number }
+                type Pair => {
+                    x: Box<\"a\">,
+                    y: Box<\"b\">,
+                    p: Box<{ p: Number }>,
+                    q: Box<{ q: String }>,
+                    n: Box<Number>,
+                    again: Box<\"b\">
+                }
+            }",
+        );
+        let schema = SchemaGenerator::new(&program.modules[0]).module_schema();
+        let JsonValue::Object(members) = &schema else {
+            panic!("Expected an object");
+        };
+        let JsonValue::Object(defs) = &members[2].1 else {
+            panic!("Expected $defs");
+        };
+
+        let keys: Vec<&str> = defs.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "BoxOfNumber",
+                "Pair",
+                "BoxOfLiteral",
+                "BoxOfLiteral2",
+                "BoxOfObject",
+                "BoxOfObject2",
+                "BoxOfNumber2"
+            ]
+        );
+
+        let json = write_json(&schema).replace(['\n', ' '], "");
+        assert!(json.contains(
+            r##""x":{"$ref":"#/$defs/BoxOfLiteral"},"y":{"$ref":"#/$defs/BoxOfLiteral2"}"##
+        ));
+        assert!(json.contains(r##""again":{"$ref":"#/$defs/BoxOfLiteral2"}"##));
+        assert!(json.contains(
+            r##""BoxOfLiteral2":{"type":"object","properties":{"value":{"const":"b"}}"##
+        ));
+        assert!(json.contains(r##""BoxOfObject2":{"type":"object","properties":{"value":{"type":"object","properties":{"q""##));
+    }
+}
@@ -0,0 +1,398 @@
+use std::fmt;
+
+use crate::ast::{BinaryOp, Span, Type};
+use crate::diagnostic::{Diagnostic, Severity};
+
+/// Represents problems found while checking types
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeError {
+    /// A generic type was instantiated with the wrong number of type arguments
+    ArityMismatch {
+        name: String,
+        expected: usize,
+        found: usize,
+        span: Span,
+    },
+
+    /// A type parameter was declared twice on the same definition
+    DuplicateTypeParameter { name: String, span: Span },
+
+    /// An expression's type differs from the type it is required to have
+    TypeMismatch {
+        expected: Type,
+        found: Type,
+        span: Span,
+    },
+
+    /// An object literal lacks a field its type declares
+    MissingField {
+        type_name: String,
+        field: String,
+        field_type: Type,
+        span: Span,
+    },
+
+    /// An object literal has a field its type does not declare
+    UnknownField {
+        type_name: String,
+        field: String,
+        span: Span,
+    },
+
+    /// Two sources of a composed type declare the same field with different types
+    FieldConflict {
+        type_name: String,
+        field: String,
+        first: Type,
+        second: Type,
+        span: Span,
+    },
+
+    /// A type extends something that is not a record type
+    InvalidExtension {
+        type_name: String,
+        base: Type,
+        span: Span,
+    },
+
+    /// A type extends itself, directly or through other definitions
+    CyclicExtension { path: Vec<String>, span: Span },
+
+    /// Aliases that name each other, so none of them names an actual type
+    CyclicAlias { path: Vec<String>, span: Span },
+
+    /// Type definitions that contain each other with no list or union to
+    /// end the recursion, so no finite value can satisfy them
+    RecursiveType { path: Vec<String>, span: Span },
+
+    /// Something other than an object is spread into an object literal
+    InvalidSpread { found: Type, span: Span },
+
+    /// Warning: an object literal sets a key whose earlier value is then lost
+    DuplicateKey { key: String, span: Span },
+
+    /// A match expression has no arm for some possible values
+    NonExhaustiveMatch { missing: Vec<String>, span: Span },
+
+    /// Warning: a match arm can never be reached
+    UnreachablePattern { span: Span },
+
+    /// A template string interpolates a value that has no text form
+    InvalidInterpolation { found: Type, span: Span },
+
+    /// An arithmetic operator was applied to operands it does not support
+    InvalidOperands {
+        op: BinaryOp,
+        left: Type,
+        right: Type,
+        span: Span,
+    },
+}
+
+impl TypeError {
+    /// Returns the position the error is reported at
+    pub fn span(&self) -> Span {
+        match self {
+            TypeError::ArityMismatch { span, .. }
+            | TypeError::DuplicateTypeParameter { span, .. }
+            | TypeError::TypeMismatch { span, .. }
+            | TypeError::MissingField { span, .. }
+            | TypeError::UnknownField { span, .. }
+            | TypeError::FieldConflict { span, .. }
+            | TypeError::InvalidExtension { span, .. }
+            | TypeError::CyclicExtension { span, .. }
+            | TypeError::CyclicAlias { span, .. }
+            | TypeError::RecursiveType { span, .. }
+            | TypeError::InvalidSpread { span, .. }
+            | TypeError::DuplicateKey { span, .. }
+            | TypeError::NonExhaustiveMatch { span, .. }
+            | TypeError::UnreachablePattern { span }
+            | TypeError::InvalidInterpolation { span, .. }
+            | TypeError::InvalidOperands { span, .. } => *span,
+        }
+    }
+
+    /// Returns true for problems that do not stop the program from running
+    pub fn is_warning(&self) -> bool {
+        matches!(
+            self,
+            TypeError::DuplicateKey { .. } | TypeError::UnreachablePattern { .. }
+        )
+    }
+
+    /// Describes the error with labels that show the expected and found types
+    pub fn diagnostic(&self) -> Diagnostic {
+        let span = self.span();
+        let diagnostic = match self {
+            TypeError::ArityMismatch {
+                name,
+                expected,
+                found,
+                ..
+            } => Diagnostic::error(format!("wrong number of type arguments for '{}'", name))
+                .with_label(
+                    span,
+                    format!("expected {} type arguments, found {}", expected, found),
+                ),
+            TypeError::DuplicateTypeParameter { name, .. } => {
+                Diagnostic::error(format!("duplicate type parameter '{}'", name))
+                    .with_label(span, "declared more than once in this definition")
+            }
+            TypeError::TypeMismatch {
+                expected, found, ..
+            } => Diagnostic::error("mismatched types")
+                .with_label(span, format!("expected {}, found {}", expected, found)),
+            TypeError::MissingField {
+                type_name,
+                field,
+                field_type,
+                ..
+            } => Diagnostic::error(format!("missing field '{}'", field)).with_label(
+                span,
+                format!(
+                    "expected '{}: {}' for type {}",
+                    field, field_type, type_name
+                ),
+            ),
+            TypeError::UnknownField {
+                type_name, field, ..
+            } => Diagnostic::error(format!("unknown field '{}'", field))
+                .with_label(span, format!("type {} has no field '{}'", type_name, field)),
+            TypeError::FieldConflict {
+                type_name,
+                field,
+                first,
+                second,
+                ..
+            } => Diagnostic::error(format!("conflicting declarations of field '{}'", field))
+                .with_label(
+                    span,
+                    format!("declared as {} here but as {} elsewhere", second, first),
+                )
+                .with_note(format!(
+                    "every declaration of '{}' in {} must have the same type",
+                    field, type_name
+                )),
+            TypeError::InvalidExtension {
+                type_name, base, ..
+            } => Diagnostic::error(format!("type '{}' cannot extend '{}'", type_name, base))
+                .with_label(span, format!("expected a record type, found {}", base)),
+            TypeError::CyclicExtension { path, .. } => {
+                Diagnostic::error("cyclic type extension").with_label(span, path.join(" extends "))
+            }
+            TypeError::CyclicAlias { path, .. } => {
+                Diagnostic::error(format!("cyclic type alias '{}'", path[0]))
+                    .with_label(span, path.join(" => "))
+                    .with_note("an alias must lead to a type that is not an alias")
+            }
+            TypeError::RecursiveType { path, .. } => {
+                Diagnostic::error(format!("recursive type '{}' has infinite size", path[0]))
+                    .with_label(span, path.join(" -> "))
+                    .with_note(
+                        "recursion must pass through a list or a union with a non-recursive member",
+                    )
+            }
+            TypeError::InvalidSpread { found, .. } => {
+                Diagnostic::error("cannot spread a non-object")
+                    .with_label(span, format!("expected an object, found {}", found))
+            }
+            TypeError::DuplicateKey { key, .. } => {
+                Diagnostic::warning(format!("duplicate key '{}'", key))
+                    .with_label(span, "this overwrites an earlier value")
+            }
+            TypeError::NonExhaustiveMatch { missing, .. } => {
+                Diagnostic::error("non-exhaustive match")
+                    .with_label(span, format!("no arm for {}", missing.join(", ")))
+                    .with_note("add arms for the missing values or a '_' arm")
+            }
+            TypeError::UnreachablePattern { .. } => Diagnostic::warning("unreachable pattern")
+                .with_label(span, "earlier arms already match these values"),
+            TypeError::InvalidInterpolation { found, .. } => {
+                Diagnostic::error("cannot interpolate value").with_label(
+                    span,
+                    format!("expected String, Number or Boolean, found {}", found),
+                )
+            }
+            TypeError::InvalidOperands {
+                op, left, right, ..
+            } => Diagnostic::error(format!("cannot apply '{}' to {} and {}", op, left, right))
+                .with_label(span, format!("found {} {} {}", left, op, right))
+                .with_note(if *op == BinaryOp::Add {
+                    "'+' adds two numbers or joins two strings"
+                } else {
+                    "arithmetic operators take two numbers"
+                }),
+        };
+
+        debug_assert_eq!(diagnostic.severity == Severity::Warning, self.is_warning());
+        diagnostic
+    }
+}
+
+impl std::error::Error for TypeError {}
+
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeError::ArityMismatch {
+                name,
+                expected,
+                found,
+                span,
+            } => write!(
+                f,
+                "Wrong number of type arguments for '{}' at {}: expected {}, found {}",
+                name, span, expected, found
+            ),
+            TypeError::DuplicateTypeParameter { name, span } => {
+                write!(f, "Duplicate type parameter '{}' at {}", name, span)
+            }
+            TypeError::TypeMismatch {
+                expected,
+                found,
+                span,
+            } => write!(
+                f,
+                "Type mismatch at {}: expected {}, found {}",
+                span, expected, found
+            ),
+            TypeError::MissingField {
+                type_name,
+                field,
+                span,
+                ..
+            } => write!(
+                f,
+                "Missing field '{}' of type '{}' at {}",
+                field, type_name, span
+            ),
+            TypeError::UnknownField {
+                type_name,
+                field,
+                span,
+            } => write!(
+                f,
+                "Unknown field '{}' for type '{}' at {}",
+                field, type_name, span
+            ),
+            TypeError::FieldConflict {
+                type_name,
+                field,
+                first,
+                second,
+                span,
+            } => write!(
+                f,
+                "Conflicting declarations of field '{}' in type '{}' at {}: {} and {}",
+                field, type_name, span, first, second
+            ),
+            TypeError::InvalidExtension {
+                type_name,
+                base,
+                span,
+            } => write!(
+                f,
+                "Type '{}' cannot extend '{}' at {}: only record types can be extended",
+                type_name, base, span
+            ),
+            TypeError::CyclicExtension { path, span } => write!(
+                f,
+                "Cyclic type extension at {}: {}",
+                span,
+                path.join(" extends ")
+            ),
+            TypeError::CyclicAlias { path, span } => {
+                write!(f, "Cyclic type alias at {}: {}", span, path.join(" => "))
+            }
+            TypeError::RecursiveType { path, span } => write!(
+                f,
+                "Recursive type with infinite size at {}: {}",
+                span,
+                path.join(" -> ")
+            ),
+            TypeError::InvalidSpread { found, span } => write!(
+                f,
+                "Cannot spread a value of type {} at {}: only objects can be spread",
+                found, span
+            ),
+            TypeError::DuplicateKey { key, span } => write!(
+                f,
+                "Duplicate key '{}' at {} overwrites an earlier value",
+                key, span
+            ),
+            TypeError::NonExhaustiveMatch { missing, span } => write!(
+                f,
+                "Non-exhaustive match at {}: no arm for {}",
+                span,
+                missing.join(", ")
+            ),
+            TypeError::UnreachablePattern { span } => {
+                write!(f, "Unreachable match pattern at {}", span)
+            }
+            TypeError::InvalidInterpolation { found, span } => write!(
+                f,
+                "Cannot interpolate a value of type {} at {}: only strings, numbers and booleans can be interpolated",
+                found, span
+            ),
+            TypeError::InvalidOperands {
+                op,
+                left,
+                right,
+                span,
+            } => write!(
+                f,
+                "Cannot apply '{}' to {} and {} at {}",
+                op, left, right, span
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_display() {
+        let err = TypeError::ArityMismatch {
+            name: "Page".to_string(),
+            expected: 1,
+            found: 2,
+            span: Span::new(3, 16),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Wrong number of type arguments for 'Page' at line 3, column 16: expected 1, found 2"
+        );
+
+        let err = TypeError::DuplicateTypeParameter {
+            name: "T".to_string(),
+            span: Span::new(1, 5),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Duplicate type parameter 'T' at line 1, column 5"
+        );
+
+        let err = TypeError::TypeMismatch {
+            expected: Type::Number,
+            found: Type::String,
+            span: Span::new(4, 9),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Type mismatch at line 4, column 9: expected Number, found String"
+        );
+
+        let err = TypeError::MissingField {
+            type_name: "Vector3D".to_string(),
+            field: "z".to_string(),
+            field_type: Type::Number,
+            span: Span::new(2, 1),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Missing field 'z' of type 'Vector3D' at line 2, column 1"
+        );
+    }
+}
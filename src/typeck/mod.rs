@@ -0,0 +1,1744 @@
+mod error;
+
+pub use error::TypeError;
+
+use std::collections::HashMap;
+
+use crate::ast::*;
+
+/// How deeply one generic definition may nest inside itself before
+/// `TypeEnv::recursion_cycle` treats the nesting as unbounded
+const MAX_NESTING: usize = 32;
+
+/// Type definitions visible inside a single module
+#[derive(Debug)]
+pub struct TypeEnv<'a> {
+    module: &'a Module,
+    definitions: HashMap<&'a str, &'a TypeDefinition>,
+}
+
+impl<'a> TypeEnv<'a> {
+    /// Collects every type definition declared in `module`
+    pub fn new(module: &'a Module) -> Self {
+        let definitions = module
+            .statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::TypeDef(def) => Some((def.name.as_str(), def)),
+                _ => None,
+            })
+            .collect();
+
+        TypeEnv {
+            module,
+            definitions,
+        }
+    }
+
+    /// Looks up a type definition by name
+    pub fn lookup(&self, name: &str) -> Option<&'a TypeDefinition> {
+        self.definitions.get(name).copied()
+    }
+
+    /// Returns the first declared record type that a value of the structural
+    /// type `ty` could be annotated with, counting fields with defaults as
+    /// optional. Generic types are skipped since a value does not say which
+    /// type arguments it was meant to have.
+    pub fn matching_definition(&self, ty: &Type) -> Option<&'a TypeDefinition> {
+        let Type::Object(found) = ty else {
+            return None;
+        };
+
+        self.module
+            .statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::TypeDef(def) if def.params.is_empty() && def.alias.is_none() => {
+                    Some(def)
+                }
+                _ => None,
+            })
+            .find(|def| {
+                let fields = self.flattened_fields(def, &[]);
+                found.iter().all(|(name, found_type)| {
+                    fields.iter().any(|field| {
+                        field.name == *name && self.is_assignable(found_type, &field.field_type)
+                    })
+                }) && fields.iter().all(|field| {
+                    field.default.is_some() || found.iter().any(|(name, _)| *name == field.name)
+                })
+            })
+    }
+
+    /// Returns the fields of a record type with its type arguments substituted,
+    /// or `None` if `ty` does not name a type definition
+    pub fn fields_of(&self, ty: &Type) -> Option<Vec<TypeField>> {
+        match self.expand(ty) {
+            Type::Custom(name, args) => self
+                .lookup(&name)
+                .filter(|def| def.alias.is_none())
+                .map(|def| self.flattened_fields(def, &args)),
+            _ => None,
+        }
+    }
+
+    /// Returns every field of `def`, inherited fields first, with `args`
+    /// substituted for its type parameters. When two sources declare the same
+    /// field the first one wins; `TypeChecker` reports the conflict.
+    pub fn flattened_fields(&self, def: &'a TypeDefinition, args: &[Type]) -> Vec<TypeField> {
+        self.collect_fields(def, args, &mut Vec::new(), None)
+    }
+
+    fn collect_fields(
+        &self,
+        def: &'a TypeDefinition,
+        args: &[Type],
+        visiting: &mut Vec<&'a str>,
+        mut errors: Option<&mut Vec<TypeError>>,
+    ) -> Vec<TypeField> {
+        visiting.push(&def.name);
+
+        let mut fields: Vec<TypeField> = Vec::new();
+        let mut merge =
+            |field: TypeField, span: Span, errors: &mut Option<&mut Vec<TypeError>>| match fields
+                .iter()
+                .find(|existing| existing.name == field.name)
+            {
+                Some(existing) if existing.field_type != field.field_type => {
+                    if let Some(errors) = errors {
+                        errors.push(TypeError::FieldConflict {
+                            type_name: def.name.clone(),
+                            field: field.name.clone(),
+                            first: existing.field_type.clone(),
+                            second: field.field_type.clone(),
+                            span,
+                        });
+                    }
+                }
+                Some(_) => {}
+                None => fields.push(field),
+            };
+
+        for base in &def.extends {
+            let base = base.substitute(&def.params, args);
+            // A base may name its record type through an alias
+            let inherited = match self.expand(&base) {
+                Type::Custom(name, base_args) => match self.lookup(&name) {
+                    // Cycles are reported by `TypeChecker`; stop here to terminate
+                    Some(base_def) if visiting.contains(&base_def.name.as_str()) => continue,
+                    Some(base_def) if base_def.alias.is_none() => {
+                        Some(self.collect_fields(base_def, &base_args, visiting, None))
+                    }
+                    Some(_) => None,
+                    None if def.params.contains(&name) => None,
+                    // Unknown names are left to name resolution
+                    None => continue,
+                },
+                _ => None,
+            };
+
+            let Some(inherited) = inherited else {
+                if let Some(errors) = &mut errors {
+                    errors.push(TypeError::InvalidExtension {
+                        type_name: def.name.clone(),
+                        base,
+                        span: def.span,
+                    });
+                }
+                continue;
+            };
+
+            for field in inherited {
+                merge(field, def.span, &mut errors);
+            }
+        }
+
+        for field in def.instantiate(args) {
+            let span = field.span;
+            merge(field, span, &mut errors);
+        }
+
+        visiting.pop();
+        fields
+    }
+
+    /// Returns the chain of aliases leading from `def` back to itself, if
+    /// there is one
+    fn alias_cycle(&self, def: &'a TypeDefinition) -> Option<Vec<String>> {
+        let mut path = vec![def.name.clone()];
+        let mut current = def;
+        while let Some(Type::Custom(name, _)) = &current.alias {
+            current = self.lookup(name)?;
+            if path.contains(&current.name) {
+                path.push(current.name.clone());
+                return (current.name == def.name).then_some(path);
+            }
+            path.push(current.name.clone());
+        }
+        None
+    }
+
+    /// Returns the chain of definitions leading from `def` back to itself
+    /// through `extends` and aliases of named types, if there is one
+    fn extension_cycle(&self, def: &'a TypeDefinition) -> Option<Vec<String>> {
+        fn visit<'a>(
+            env: &TypeEnv<'a>,
+            current: &'a TypeDefinition,
+            target: &str,
+            path: &mut Vec<String>,
+        ) -> bool {
+            for base in current.extends.iter().chain(&current.alias) {
+                let Type::Custom(name, _) = base else {
+                    continue;
+                };
+                let Some(base_def) = env.lookup(name) else {
+                    continue;
+                };
+                if path.contains(&base_def.name) {
+                    if base_def.name == target {
+                        path.push(base_def.name.clone());
+                        return true;
+                    }
+                    continue;
+                }
+
+                path.push(base_def.name.clone());
+                if visit(env, base_def, target, path) {
+                    return true;
+                }
+                path.pop();
+            }
+            false
+        }
+
+        let mut path = vec![def.name.clone()];
+        visit(self, def, &def.name, &mut path).then_some(path)
+    }
+
+    /// Returns the chain of definitions leading from `def` back to itself
+    /// without passing through a list or a union with another way out. Such
+    /// a type has no finite values, so nothing can ever satisfy it.
+    pub fn recursion_cycle(&self, def: &'a TypeDefinition) -> Option<Vec<String>> {
+        let params: Vec<Type> = def
+            .params
+            .iter()
+            .map(|param| Type::Custom(param.clone(), Vec::new()))
+            .collect();
+        let root = Type::Custom(def.name.clone(), params);
+
+        match self.finite(&root, &mut Vec::new()) {
+            Err(path) if path[0] == def.name => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Checks that `ty` has at least one finite value, assuming the types on
+    /// `stack` do not. On failure, returns the cycle that prevents it.
+    fn finite(&self, ty: &Type, stack: &mut Vec<Type>) -> Result<(), Vec<String>> {
+        match ty {
+            Type::Number | Type::String | Type::Boolean | Type::Literal(_) => Ok(()),
+            // The empty list ends any recursion through a list
+            Type::List(_) => Ok(()),
+            Type::Union(members) => {
+                let mut cycle = None;
+                for member in members {
+                    match self.finite(member, stack) {
+                        Ok(()) => return Ok(()),
+                        Err(path) => {
+                            cycle.get_or_insert(path);
+                        }
+                    }
+                }
+                cycle.map_or(Ok(()), Err)
+            }
+            Type::Object(fields) => fields
+                .iter()
+                .try_for_each(|(_, field_type)| self.finite(field_type, stack)),
+            Type::Custom(name, args) => {
+                // Type parameters and unknown names are reported elsewhere
+                let Some(def) = self.lookup(name) else {
+                    return Ok(());
+                };
+
+                // Arguments that grow on every step, as in `type T<X> => { t: T<X[]> }`,
+                // never repeat exactly, so a deep enough nesting counts as a cycle too
+                let first = stack.iter().position(|entry| entry == ty).or_else(|| {
+                    (stack.len() >= MAX_NESTING)
+                        .then(|| {
+                            stack.iter().rposition(
+                                |entry| matches!(entry, Type::Custom(entered, _) if entered == name),
+                            )
+                        })
+                        .flatten()
+                });
+                if let Some(first) = first {
+                    let mut path: Vec<String> = stack[first..]
+                        .iter()
+                        .filter_map(|entry| match entry {
+                            Type::Custom(entered, _) => Some(entered.clone()),
+                            _ => None,
+                        })
+                        .collect();
+                    path.push(def.name.clone());
+                    return Err(path);
+                }
+
+                stack.push(ty.clone());
+                let result = match &def.alias {
+                    Some(alias) => self.finite(&alias.substitute(&def.params, args), stack),
+                    None => self
+                        .flattened_fields(def, args)
+                        .iter()
+                        .try_for_each(|field| self.finite(&field.field_type, stack)),
+                };
+                stack.pop();
+                result
+            }
+        }
+    }
+
+    /// Follows aliases such as `type Env => "dev" | "prod"` to the type they name
+    pub fn expand(&self, ty: &Type) -> Type {
+        let mut ty = ty.clone();
+        let mut seen: Vec<&str> = Vec::new();
+
+        while let Type::Custom(name, args) = &ty {
+            match self.lookup(name) {
+                Some(def) if !seen.contains(&def.name.as_str()) => match &def.alias {
+                    Some(alias) => {
+                        seen.push(&def.name);
+                        ty = alias.substitute(&def.params, args);
+                    }
+                    None => break,
+                },
+                _ => break,
+            }
+        }
+
+        ty
+    }
+
+    /// Returns the members of a union type with aliases expanded and nested
+    /// unions flattened; any other type is its own single member
+    pub fn union_members(&self, ty: &Type) -> Vec<Type> {
+        match self.expand(ty) {
+            Type::Union(members) => members
+                .iter()
+                .flat_map(|member| self.union_members(member))
+                .collect(),
+            other => vec![other],
+        }
+    }
+
+    /// Returns the fields of a named or inline record type
+    pub fn record_fields(&self, ty: &Type) -> Option<Vec<TypeField>> {
+        let ty = self.expand(ty);
+        match &ty {
+            Type::Object(fields) => Some(
+                fields
+                    .iter()
+                    .map(|(name, field_type)| TypeField {
+                        name: name.clone(),
+                        field_type: field_type.clone(),
+                        default: None,
+                        docs: None,
+                        span: Span::default(),
+                    })
+                    .collect(),
+            ),
+            _ => self.fields_of(&ty),
+        }
+    }
+
+    /// Returns true if a value of type `found` can be used where `expected` is required.
+    /// Named types match by name; inline object types match structurally.
+    pub fn is_assignable(&self, found: &Type, expected: &Type) -> bool {
+        if found == expected {
+            return true;
+        }
+
+        let found = self.expand(found);
+        let expected = self.expand(expected);
+        if found == expected {
+            return true;
+        }
+
+        match (&found, &expected) {
+            // Unknown names are left to name resolution
+            (Type::Custom(name, _), _) | (_, Type::Custom(name, _))
+                if self.lookup(name).is_none() =>
+            {
+                true
+            }
+            (Type::Union(members), _) => members
+                .iter()
+                .all(|member| self.is_assignable(member, &expected)),
+            (_, Type::Union(members)) => members
+                .iter()
+                .any(|member| self.is_assignable(&found, member)),
+            (Type::Literal(literal), _) => self.is_assignable(&literal.base_type(), &expected),
+            (Type::Custom(..), Type::Custom(..)) => false,
+            (Type::List(found), Type::List(expected)) => self.is_assignable(found, expected),
+            _ => match (self.record_fields(&found), self.record_fields(&expected)) {
+                (Some(found_fields), Some(expected_fields)) => {
+                    found_fields.len() == expected_fields.len()
+                        && expected_fields.iter().all(|expected| {
+                            found_fields.iter().any(|found| {
+                                found.name == expected.name
+                                    && self.is_assignable(&found.field_type, &expected.field_type)
+                            })
+                        })
+                }
+                _ => false,
+            },
+        }
+    }
+}
+
+/// Types of the bindings declared so far in a module
+type Bindings<'a> = HashMap<&'a str, Type>;
+
+/// Checks type definitions and their uses across a program
+pub struct TypeChecker<'a> {
+    program: &'a Program,
+    errors: Vec<TypeError>,
+    types: HashMap<ExprId, Type>,
+}
+
+impl<'a> TypeChecker<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        TypeChecker {
+            program,
+            errors: Vec::new(),
+            types: HashMap::new(),
+        }
+    }
+
+    /// Returns the type found for an expression by the last call to `check`.
+    /// Expressions checked against an annotation have the annotated type;
+    /// others have their inferred, structural type.
+    pub fn type_of(&self, id: ExprId) -> Option<&Type> {
+        self.types.get(&id)
+    }
+
+    /// Checks every module and returns the errors found
+    pub fn check(&mut self) -> Vec<TypeError> {
+        self.types.clear();
+        for module in &self.program.modules {
+            let env = TypeEnv::new(module);
+            let mut bindings = Bindings::new();
+
+            for statement in &module.statements {
+                match statement {
+                    Statement::TypeDef(def) => self.check_type_definition(&env, &bindings, def),
+                    Statement::Let {
+                        name,
+                        ty,
+                        value,
+                        span,
+                    }
+                    | Statement::Const {
+                        name,
+                        ty,
+                        value,
+                        span,
+                    } => {
+                        let binding_type = match ty {
+                            Some(ty) => {
+                                self.check_type(&env, &[], ty, *span);
+                                self.check_expression(&env, &bindings, value, ty);
+                                Some(ty.clone())
+                            }
+                            None => self.infer(&env, &bindings, value),
+                        };
+
+                        if let Some(binding_type) = binding_type {
+                            bindings.insert(name, binding_type);
+                        }
+                    }
+                    Statement::Assign {
+                        target,
+                        value,
+                        span,
+                    } => self.check_assignment(&env, &bindings, target, value, *span),
+                }
+            }
+        }
+
+        std::mem::take(&mut self.errors)
+    }
+
+    /// Checks that `value` fits the binding or field that `target` names
+    fn check_assignment(
+        &mut self,
+        env: &TypeEnv,
+        bindings: &Bindings,
+        target: &Place,
+        value: &Expr,
+        span: Span,
+    ) {
+        // Unknown names are left to name resolution
+        let Some(mut target_type) = bindings.get(target.name.as_str()).cloned() else {
+            self.infer(env, bindings, value);
+            return;
+        };
+
+        for name in &target.fields {
+            let field = env
+                .record_fields(&target_type)
+                .and_then(|fields| fields.into_iter().find(|field| field.name == *name));
+            match field {
+                Some(field) => target_type = field.field_type,
+                None => {
+                    self.errors.push(TypeError::UnknownField {
+                        type_name: target_type.to_string(),
+                        field: name.clone(),
+                        span,
+                    });
+                    self.infer(env, bindings, value);
+                    return;
+                }
+            }
+        }
+
+        self.check_expression(env, bindings, value, &target_type);
+    }
+
+    fn check_type_definition(&mut self, env: &TypeEnv, bindings: &Bindings, def: &TypeDefinition) {
+        for (i, param) in def.params.iter().enumerate() {
+            if def.params[..i].contains(param) {
+                self.errors.push(TypeError::DuplicateTypeParameter {
+                    name: param.clone(),
+                    span: def.span,
+                });
+            }
+        }
+
+        for base in &def.extends {
+            self.check_type(env, &def.params, base, def.span);
+        }
+
+        for field in &def.fields {
+            self.check_type(env, &def.params, &field.field_type, field.span);
+            if let Some(default) = &field.default {
+                self.check_expression(env, bindings, default, &field.field_type);
+            }
+        }
+
+        if let Some(alias) = &def.alias {
+            self.check_type(env, &def.params, alias, def.span);
+        }
+
+        // Each cycle is reported once, at the first definition it starts from
+        let first_in = |path: &[String]| {
+            path.iter().all(|name| {
+                env.lookup(name).is_none_or(|other| {
+                    (other.span.line, other.span.column) >= (def.span.line, def.span.column)
+                })
+            })
+        };
+
+        if let Some(path) = env.alias_cycle(def) {
+            if first_in(&path) {
+                self.errors.push(TypeError::CyclicAlias {
+                    path,
+                    span: def.span,
+                });
+            }
+            return;
+        }
+        if let Some(path) = env.extension_cycle(def) {
+            if first_in(&path) {
+                self.errors.push(TypeError::CyclicExtension {
+                    path,
+                    span: def.span,
+                });
+            }
+            return;
+        }
+
+        if let Some(path) = env.recursion_cycle(def)
+            && !path.iter().any(|name| {
+                env.lookup(name).is_some_and(|other| {
+                    (other.span.line, other.span.column) < (def.span.line, def.span.column)
+                        && env.recursion_cycle(other).is_some_and(|other_path| {
+                            other_path.iter().all(|name| path.contains(name))
+                        })
+                })
+            })
+        {
+            self.errors.push(TypeError::RecursiveType {
+                path,
+                span: def.span,
+            });
+        }
+
+        let params: Vec<Type> = def
+            .params
+            .iter()
+            .map(|param| Type::Custom(param.clone(), Vec::new()))
+            .collect();
+        env.collect_fields(def, &params, &mut Vec::new(), Some(&mut self.errors));
+    }
+
+    /// Checks that every generic type in `ty` gets the number of arguments it declares
+    fn check_type(&mut self, env: &TypeEnv, params: &[String], ty: &Type, span: Span) {
+        match ty {
+            Type::Number | Type::String | Type::Boolean | Type::Literal(_) => {}
+            Type::List(element) => self.check_type(env, params, element, span),
+            Type::Union(members) => {
+                for member in members {
+                    self.check_type(env, params, member, span);
+                }
+            }
+            Type::Object(fields) => {
+                for (_, field_type) in fields {
+                    self.check_type(env, params, field_type, span);
+                }
+            }
+            Type::Custom(name, args) => {
+                // Type parameters shadow definitions and never take arguments
+                let expected = if params.contains(name) {
+                    Some(0)
+                } else {
+                    env.lookup(name).map(|def| def.params.len())
+                };
+
+                if let Some(expected) = expected
+                    && expected != args.len()
+                {
+                    self.errors.push(TypeError::ArityMismatch {
+                        name: name.clone(),
+                        expected,
+                        found: args.len(),
+                        span,
+                    });
+                }
+
+                for arg in args {
+                    self.check_type(env, params, arg, span);
+                }
+            }
+        }
+    }
+
+    /// Checks `expr` against the type it is annotated with
+    fn check_expression(
+        &mut self,
+        env: &TypeEnv,
+        bindings: &Bindings,
+        expr: &Expr,
+        expected: &Type,
+    ) {
+        match &expr.kind {
+            Expression::Object { entries } => {
+                if let Some(expected_fields) = env.record_fields(expected) {
+                    self.types.insert(expr.id, expected.clone());
+                    self.check_object(
+                        env,
+                        bindings,
+                        expected,
+                        &expected_fields,
+                        entries,
+                        expr.span,
+                    );
+                    return;
+                }
+            }
+            Expression::List(elements) => {
+                if let Type::List(element_type) = env.expand(expected) {
+                    self.types.insert(expr.id, expected.clone());
+                    for element in elements {
+                        self.check_expression(env, bindings, element, &element_type);
+                    }
+                    return;
+                }
+            }
+            // Check each branch on its own so errors point into it
+            Expression::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.types.insert(expr.id, expected.clone());
+                self.check_expression(env, bindings, condition, &Type::Boolean);
+                self.check_expression(env, bindings, then_branch, expected);
+                self.check_expression(env, bindings, else_branch, expected);
+                return;
+            }
+            Expression::Match { scrutinee, arms } => {
+                self.types.insert(expr.id, expected.clone());
+                self.check_match(env, bindings, scrutinee, arms, Some(expected), expr.span);
+                return;
+            }
+            _ => {}
+        }
+
+        // Literals only need their exact value when a literal type is expected
+        let literal = expr.literal().filter(|_| {
+            env.union_members(expected)
+                .iter()
+                .any(|member| matches!(member, Type::Literal(_)))
+        });
+        let found = match literal {
+            Some(literal) => {
+                self.types.insert(expr.id, Type::Literal(literal.clone()));
+                Some(Type::Literal(literal))
+            }
+            None => self.infer(env, bindings, expr),
+        };
+
+        if let Some(found) = found
+            && !env.is_assignable(&found, expected)
+        {
+            self.errors.push(TypeError::TypeMismatch {
+                expected: expected.clone(),
+                found,
+                span: expr.span,
+            });
+        }
+    }
+
+    /// Checks an object literal field by field against a record type
+    fn check_object(
+        &mut self,
+        env: &TypeEnv,
+        bindings: &Bindings,
+        expected: &Type,
+        expected_fields: &[TypeField],
+        entries: &[ObjectEntry],
+        span: Span,
+    ) {
+        let (merged, complete) = self.merge_entries(env, bindings, entries);
+
+        for (name, source) in &merged {
+            let expected_field = expected_fields.iter().find(|field| field.name == *name);
+            match (source, expected_field) {
+                (FieldSource::Value(value, _), Some(field)) => {
+                    self.check_expression(env, bindings, value, &field.field_type)
+                }
+                (FieldSource::Spread(found, span), Some(field)) => {
+                    if !env.is_assignable(found, &field.field_type) {
+                        self.errors.push(TypeError::TypeMismatch {
+                            expected: field.field_type.clone(),
+                            found: found.clone(),
+                            span: *span,
+                        });
+                    }
+                }
+                (FieldSource::Value(_, span) | FieldSource::Spread(_, span), None) => {
+                    self.errors.push(TypeError::UnknownField {
+                        type_name: expected.to_string(),
+                        field: name.clone(),
+                        span: *span,
+                    })
+                }
+            }
+        }
+
+        // A spread of unknown type may supply any field
+        if !complete {
+            return;
+        }
+
+        for expected_field in expected_fields {
+            // Fields with a default are filled in by the evaluator
+            if expected_field.default.is_none()
+                && !merged.iter().any(|(name, _)| *name == expected_field.name)
+            {
+                self.errors.push(TypeError::MissingField {
+                    type_name: expected.to_string(),
+                    field: expected_field.name.clone(),
+                    field_type: expected_field.field_type.clone(),
+                    span,
+                });
+            }
+        }
+    }
+
+    /// Merges the entries of an object literal left to right, the way the
+    /// evaluator does, and warns about values that get overwritten. The flag is
+    /// false when a spread has an unknown type, so fields may be missing.
+    fn merge_entries<'e>(
+        &mut self,
+        env: &TypeEnv,
+        bindings: &Bindings,
+        entries: &'e [ObjectEntry],
+    ) -> (Vec<(String, FieldSource<'e>)>, bool) {
+        let mut merged: Vec<(String, FieldSource)> = Vec::new();
+        let mut complete = true;
+
+        let mut insert = |errors: &mut Vec<TypeError>, name: &str, source: FieldSource<'e>| {
+            match merged.iter_mut().find(|(existing, _)| existing == name) {
+                Some((_, existing)) => {
+                    // Overriding a spread field is the point of spreading; overwriting
+                    // an explicit field is almost always a mistake
+                    if matches!(existing, FieldSource::Value(..)) {
+                        errors.push(TypeError::DuplicateKey {
+                            key: name.to_string(),
+                            span: source.span(),
+                        });
+                    }
+                    *existing = source;
+                }
+                None => merged.push((name.to_string(), source)),
+            }
+        };
+
+        for entry in entries {
+            match entry {
+                ObjectEntry::Field { name, value, span } => {
+                    insert(&mut self.errors, name, FieldSource::Value(value, *span))
+                }
+                ObjectEntry::Spread(expr) => match self.spread_fields(env, bindings, expr) {
+                    Some(fields) => {
+                        for (name, field_type) in fields {
+                            insert(
+                                &mut self.errors,
+                                &name,
+                                FieldSource::Spread(field_type, expr.span),
+                            );
+                        }
+                    }
+                    None => complete = false,
+                },
+            }
+        }
+
+        (merged, complete)
+    }
+
+    /// Returns the fields a spread expression contributes, or `None` if its type is unknown
+    fn spread_fields(
+        &mut self,
+        env: &TypeEnv,
+        bindings: &Bindings,
+        expr: &Expr,
+    ) -> Option<Vec<(String, Type)>> {
+        let found = self.infer(env, bindings, expr)?;
+        match env.record_fields(&found) {
+            Some(fields) => Some(
+                fields
+                    .into_iter()
+                    .map(|field| (field.name, field.field_type))
+                    .collect(),
+            ),
+            None => {
+                // Unknown type names are left to name resolution
+                if !matches!(&found, Type::Custom(name, _) if env.lookup(name).is_none()) {
+                    self.errors.push(TypeError::InvalidSpread {
+                        found,
+                        span: expr.span,
+                    });
+                }
+                None
+            }
+        }
+    }
+
+    /// Infers the type of an expression, or `None` if it refers to something
+    /// unknown. Problems inside object literals are reported along the way.
+    fn infer(&mut self, env: &TypeEnv, bindings: &Bindings, expr: &Expr) -> Option<Type> {
+        let ty = self.infer_kind(env, bindings, expr)?;
+        self.types.insert(expr.id, ty.clone());
+        Some(ty)
+    }
+
+    fn infer_kind(&mut self, env: &TypeEnv, bindings: &Bindings, expr: &Expr) -> Option<Type> {
+        match &expr.kind {
+            Expression::NumberLiteral(_) => Some(Type::Number),
+            Expression::StringLiteral(_) => Some(Type::String),
+            Expression::BooleanLiteral(_) => Some(Type::Boolean),
+            Expression::Identifier(name) => bindings.get(name.as_str()).cloned(),
+            Expression::Object { entries } => {
+                let (merged, complete) = self.merge_entries(env, bindings, entries);
+
+                let mut fields = Vec::new();
+                for (name, source) in merged {
+                    let field_type = match source {
+                        FieldSource::Value(value, _) => self.infer(env, bindings, value),
+                        FieldSource::Spread(field_type, _) => Some(field_type),
+                    };
+                    fields.push(field_type.map(|field_type| (name, field_type)));
+                }
+
+                let fields = fields.into_iter().collect::<Option<Vec<_>>>()?;
+                complete.then_some(Type::Object(fields))
+            }
+            // An empty list has elements of the empty union, so it fits
+            // every list type
+            Expression::List(elements) => {
+                let mut element_types = Vec::new();
+                for element in elements {
+                    element_types.push(self.infer(env, bindings, element));
+                }
+                let element_type = element_types
+                    .into_iter()
+                    .try_fold(Type::Union(Vec::new()), |joined, element_type| {
+                        Some(join(env, joined, element_type?))
+                    })?;
+                Some(Type::List(Box::new(element_type)))
+            }
+            Expression::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.check_expression(env, bindings, condition, &Type::Boolean);
+                let then_type = self.infer(env, bindings, then_branch);
+                let else_type = self.infer(env, bindings, else_branch);
+                Some(join(env, then_type?, else_type?))
+            }
+            Expression::Match { scrutinee, arms } => {
+                self.check_match(env, bindings, scrutinee, arms, None, expr.span)
+            }
+            Expression::Template(parts) => {
+                for part in parts {
+                    if let Part::Expr(value) = part
+                        && let Some(found) = self.infer(env, bindings, value)
+                        && !is_formattable(env, &found)
+                    {
+                        self.errors.push(TypeError::InvalidInterpolation {
+                            found,
+                            span: value.span,
+                        });
+                    }
+                }
+                Some(Type::String)
+            }
+            Expression::Unary { operand, .. } => {
+                self.check_expression(env, bindings, operand, &Type::Number);
+                Some(Type::Number)
+            }
+            Expression::Binary { op, left, right } => {
+                let left = self.infer(env, bindings, left);
+                let right = self.infer(env, bindings, right);
+                let (left, right) = (left?, right?);
+
+                let both =
+                    |ty: &Type| env.is_assignable(&left, ty) && env.is_assignable(&right, ty);
+                if both(&Type::Number) {
+                    Some(Type::Number)
+                } else if *op == BinaryOp::Add && both(&Type::String) {
+                    Some(Type::String)
+                } else {
+                    self.errors.push(TypeError::InvalidOperands {
+                        op: *op,
+                        left,
+                        right,
+                        span: expr.span,
+                    });
+                    None
+                }
+            }
+        }
+    }
+
+    /// Checks a match expression and returns the type of its value. Arm bodies
+    /// are checked against `expected` when there is one, and joined otherwise.
+    fn check_match(
+        &mut self,
+        env: &TypeEnv,
+        bindings: &Bindings,
+        scrutinee: &Expr,
+        arms: &[MatchArm],
+        expected: Option<&Type>,
+        span: Span,
+    ) -> Option<Type> {
+        let domain = self
+            .infer(env, bindings, scrutinee)
+            .map(|ty| env.expand(&ty));
+
+        let mut covered: Vec<&Literal> = Vec::new();
+        let mut has_wildcard = false;
+        let mut arm_types = Vec::new();
+
+        for arm in arms {
+            if has_wildcard {
+                self.errors
+                    .push(TypeError::UnreachablePattern { span: arm.span });
+            }
+
+            for pattern in &arm.patterns {
+                match pattern {
+                    Pattern::Wildcard => has_wildcard = true,
+                    Pattern::Literal(literal) => {
+                        let pattern_type = Type::Literal(literal.clone());
+                        if let Some(domain) = &domain
+                            && !env.is_assignable(&pattern_type, domain)
+                        {
+                            self.errors.push(TypeError::TypeMismatch {
+                                expected: domain.clone(),
+                                found: pattern_type,
+                                span: arm.span,
+                            });
+                        } else if covered.contains(&literal) && !has_wildcard {
+                            self.errors
+                                .push(TypeError::UnreachablePattern { span: arm.span });
+                        }
+                        covered.push(literal);
+                    }
+                }
+            }
+
+            match expected {
+                Some(expected) => self.check_expression(env, bindings, &arm.body, expected),
+                None => arm_types.push(self.infer(env, bindings, &arm.body)),
+            }
+        }
+
+        // A scrutinee of unknown type is reported by name resolution
+        if !has_wildcard && let Some(domain) = &domain {
+            let missing = missing_cases(env, domain, &covered);
+            if !missing.is_empty() {
+                self.errors
+                    .push(TypeError::NonExhaustiveMatch { missing, span });
+            }
+        }
+
+        match expected {
+            Some(expected) => Some(expected.clone()),
+            None => arm_types
+                .into_iter()
+                .reduce(|joined, arm_type| Some(join(env, joined?, arm_type?)))
+                .flatten(),
+        }
+    }
+}
+
+/// Returns the narrowest type that both `a` and `b` can be used as
+fn join(env: &TypeEnv, a: Type, b: Type) -> Type {
+    if env.is_assignable(&a, &b) {
+        b
+    } else if env.is_assignable(&b, &a) {
+        a
+    } else {
+        let mut members = env.union_members(&a);
+        for member in env.union_members(&b) {
+            if !members.contains(&member) {
+                members.push(member);
+            }
+        }
+        Type::Union(members)
+    }
+}
+
+/// Returns true if values of `ty` can be written into a template string
+fn is_formattable(env: &TypeEnv, ty: &Type) -> bool {
+    env.union_members(ty).iter().all(|member| match member {
+        Type::List(_) | Type::Object(_) => false,
+        Type::Custom(..) => env.record_fields(member).is_none(),
+        _ => true,
+    })
+}
+
+/// Describes the values of `domain` that none of the `covered` literals match
+fn missing_cases(env: &TypeEnv, domain: &Type, covered: &[&Literal]) -> Vec<String> {
+    let mut missing = Vec::new();
+    for member in env.union_members(domain) {
+        match member {
+            Type::Literal(literal) => {
+                if !covered.contains(&&literal) {
+                    missing.push(literal.to_string());
+                }
+            }
+            Type::Boolean => {
+                for value in [true, false] {
+                    if !covered.contains(&&Literal::Boolean(value)) {
+                        missing.push(value.to_string());
+                    }
+                }
+            }
+            // Other types have too many values to list, so only `_` covers them
+            _ => {
+                if !missing.iter().any(|case| case == "_") {
+                    missing.push("_".to_string());
+                }
+            }
+        }
+    }
+    missing
+}
+
+/// Where a field of an object literal gets its final value from
+enum FieldSource<'e> {
+    Value(&'e Expr, Span),
+    Spread(Type, Span),
+}
+
+impl FieldSource<'_> {
+    fn span(&self) -> Span {
+        match self {
+            FieldSource::Value(_, span) | FieldSource::Spread(_, span) => *span,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn parse(source: &str) -> Program {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        Parser::new(tokens).parse().unwrap()
+    }
+
+    #[test]
+    fn test_generic_instantiation() {
+        let program = parse(
+            "module m {
+                type User => { name: String }
+                type Page<T> => { items: T[], total: Number }
+            }",
+        );
+        let env = TypeEnv::new(&program.modules[0]);
+
+        let page_of_users = Type::Custom(
+            "Page".to_string(),
+            vec![Type::Custom("User".to_string(), vec![])],
+        );
+        let fields = env.fields_of(&page_of_users).unwrap();
+        assert_eq!(fields[0].name, "items");
+        assert_eq!(
+            fields[0].field_type,
+            Type::List(Box::new(Type::Custom("User".to_string(), vec![])))
+        );
+        assert_eq!(fields[1].field_type, Type::Number);
+
+        assert!(TypeChecker::new(&program).check().is_empty());
+    }
+
+    #[test]
+    fn test_arity_mismatch() {
+        let program = parse(
+            "module m {
+                type Pair<A, B> => { first: A, second: B }
+                type Bad => { pair: Pair<Number>, param: Number }
+                type Worse<T> => { value: T<String> }
+            }",
+        );
+        let errors = TypeChecker::new(&program).check();
+
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(
+            &errors[0],
+            TypeError::ArityMismatch { name, expected: 2, found: 1, .. } if name == "Pair"
+        ));
+        assert!(matches!(
+            &errors[1],
+            TypeError::ArityMismatch { name, expected: 0, found: 1, .. } if name == "T"
+        ));
+        assert_eq!(errors[0].span(), Span::new(3, 31));
+    }
+
+    #[test]
+    fn test_annotated_object_literal() {
+        let program = parse(
+            "module m {
+                type Vector3D => { x: Number, y: Number, z: Number }
+                type Matrix => { elements: Vector3D, metadata: { created: String } }
+                const ORIGIN: Vector3D = { x: 0, y: 0, z: 0 }
+                let identity: Matrix = { elements: ORIGIN, metadata: { created: \"now\" } }
+                let bad: Vector3D = {
+                    x: \"one\",
+                    y: 0,
+                    w: 0
+                }
+            }",
+        );
+        let errors = TypeChecker::new(&program).check();
+
+        assert_eq!(
+            errors,
+            vec![
+                TypeError::TypeMismatch {
+                    expected: Type::Number,
+                    found: Type::String,
+                    span: Span::new(7, 24),
+                },
+                TypeError::UnknownField {
+                    type_name: "Vector3D".to_string(),
+                    field: "w".to_string(),
+                    span: Span::new(9, 21),
+                },
+                TypeError::MissingField {
+                    type_name: "Vector3D".to_string(),
+                    field: "z".to_string(),
+                    field_type: Type::Number,
+                    span: Span::new(6, 37),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_annotated_generic_and_scalar_bindings() {
+        let program = parse(
+            "module m {
+                type Page<T> => { items: T[], total: Number }
+                let count: Number = 3
+                let label: String = count
+                let page: Page<String> = { total: count, extra: true }
+            }",
+        );
+        let errors = TypeChecker::new(&program).check();
+
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(
+            &errors[0],
+            TypeError::TypeMismatch {
+                expected: Type::String,
+                found: Type::Number,
+                ..
+            }
+        ));
+        assert!(matches!(&errors[1], TypeError::UnknownField { field, .. } if field == "extra"));
+        assert!(matches!(
+            &errors[2],
+            TypeError::MissingField { type_name, field, .. }
+                if type_name == "Page<String>" && field == "items"
+        ));
+    }
+
+    #[test]
+    fn test_flattened_fields() {
+        let program = parse(
+            "module m {
+                type Point => { x: Number, y: Number }
+                type Point3D => Point & { z: Number }
+                type Labeled<T> extends Point3D => { label: T }
+                let p: Labeled<String> = { x: 1, y: 2, label: \"a\" }
+            }",
+        );
+        let env = TypeEnv::new(&program.modules[0]);
+
+        let fields = env
+            .fields_of(&Type::Custom("Labeled".to_string(), vec![Type::String]))
+            .unwrap();
+        let names: Vec<&str> = fields.iter().map(|field| field.name.as_str()).collect();
+        assert_eq!(names, vec!["x", "y", "z", "label"]);
+        assert_eq!(fields[3].field_type, Type::String);
+
+        let errors = TypeChecker::new(&program).check();
+        assert_eq!(
+            errors,
+            vec![TypeError::MissingField {
+                type_name: "Labeled<String>".to_string(),
+                field: "z".to_string(),
+                field_type: Type::Number,
+                span: Span::new(5, 42),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_extension_errors() {
+        let program = parse(
+            "module m {
+                type Point => { x: Number, y: Number }
+                type Named => { x: String }
+                type Both => Point & Named
+                type Wrapper<T> extends T => { }
+                type A extends B => { a: Number }
+                type B => A & { b: Number }
+                type Shadow extends Point => { x: Number }
+                type X => Y
+                type Y => X
+                type Me => Me
+                type Uses => { x: X }
+            }",
+        );
+        let errors = TypeChecker::new(&program).check();
+
+        assert_eq!(errors.len(), 5, "{:?}", errors);
+        assert!(matches!(
+            &errors[0],
+            TypeError::FieldConflict { type_name, field, first: Type::Number, second: Type::String, .. }
+                if type_name == "Both" && field == "x"
+        ));
+        assert!(matches!(
+            &errors[1],
+            TypeError::InvalidExtension { type_name, .. } if type_name == "Wrapper"
+        ));
+        assert!(matches!(
+            &errors[2],
+            TypeError::CyclicExtension { path, .. } if path == &["A", "B", "A"]
+        ));
+
+        // Each cycle is reported once, at its first member
+        assert_eq!(
+            errors[3],
+            TypeError::CyclicAlias {
+                path: vec!["X".to_string(), "Y".to_string(), "X".to_string()],
+                span: Span::new(9, 22),
+            }
+        );
+        assert_eq!(
+            errors[4],
+            TypeError::CyclicAlias {
+                path: vec!["Me".to_string(), "Me".to_string()],
+                span: Span::new(11, 22),
+            }
+        );
+    }
+
+    #[test]
+    fn test_aliases_of_named_types() {
+        let program = parse(
+            "module m {
+                type Env => \"dev\" | \"prod\"
+                type Stage => Env
+                type User => { name: String, active: Boolean = true }
+                type Admin => User
+                type Super extends Admin => { level: Number }
+                const S: Stage = \"dev\"
+                const A: Admin = { name: \"root\" }
+                const X: Super = { name: \"x\", level: 2 }
+                const BAD: Stage = \"test\"
+            }",
+        );
+        let env = TypeEnv::new(&program.modules[0]);
+        let admin = Type::Custom("Admin".to_string(), vec![]);
+        let fields = env.fields_of(&admin).unwrap();
+        assert_eq!(fields[0].name, "name");
+
+        let errors = TypeChecker::new(&program).check();
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(matches!(
+            &errors[0],
+            TypeError::TypeMismatch { span, .. } if span.line == 10
+        ));
+    }
+
+    #[test]
+    fn test_recursive_types() {
+        let program = parse(
+            "module m {
+                type A => { b: B }
+                type B => { a: A, name: String }
+                type Self => { me: Self }
+                type Holder => { a: A }
+                type Tree => { value: Number, children: Tree[] }
+                type Chain => { next: Chain | \"end\" }
+                type Box<T> => { value: T }
+                type Boxed => { inner: Box<Boxed> }
+                type Pair<L, R> => { left: L, right: R }
+                type Nested => { pair: Pair<Number, Pair<String, Tree>> }
+                type Either => Left | Right
+                type Left => { value: Either }
+                type Right => { value: Either }
+                type Grow<X> => { next: Grow<X[]> }
+            }",
+        );
+        let errors = TypeChecker::new(&program).check();
+
+        let cycles: Vec<(Vec<String>, Span)> = errors
+            .into_iter()
+            .map(|err| match err {
+                TypeError::RecursiveType { path, span } => (path, span),
+                other => panic!("Expected a recursive type, found {:?}", other),
+            })
+            .collect();
+        let path = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        assert_eq!(
+            cycles,
+            vec![
+                (path(&["A", "B", "A"]), Span::new(2, 22)),
+                (path(&["Self", "Self"]), Span::new(4, 22)),
+                (path(&["Boxed", "Box", "Boxed"]), Span::new(9, 22)),
+                (path(&["Either", "Left", "Either"]), Span::new(12, 22)),
+                (path(&["Grow", "Grow"]), Span::new(15, 22)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_field_defaults() {
+        let program = parse(
+            "module m {
+                type Server => {
+                    host: String,
+                    port: Number = 8080,
+                    secure: Boolean = \"yes\"
+                }
+                let local: Server = { host: \"localhost\" }
+            }",
+        );
+        let errors = TypeChecker::new(&program).check();
+
+        assert_eq!(
+            errors,
+            vec![TypeError::TypeMismatch {
+                expected: Type::Boolean,
+                found: Type::String,
+                span: Span::new(5, 39),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_spread_in_annotated_literal() {
+        let program = parse(
+            "module m {
+                type Vector3D => { x: Number, y: Number, z: Number }
+                type Point => { x: Number, y: Number }
+                const FLAT: Point = { x: 0, y: 0 }
+                let up: Vector3D = { ...FLAT, z: 1 }
+                let short: Vector3D = { ...FLAT }
+                let label = \"a\"
+                let odd: Point = { ...FLAT, label }
+                let n = 3
+                let bad = { ...n }
+            }",
+        );
+        let errors = TypeChecker::new(&program).check();
+
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(
+            &errors[0],
+            TypeError::MissingField { field, .. } if field == "z"
+        ));
+        assert!(matches!(
+            &errors[1],
+            TypeError::UnknownField { field, span, .. }
+                if field == "label" && *span == Span::new(8, 45)
+        ));
+        assert!(matches!(
+            &errors[2],
+            TypeError::InvalidSpread {
+                found: Type::Number,
+                ..
+            }
+        ));
+        assert!(errors.iter().all(|error| !error.is_warning()));
+    }
+
+    #[test]
+    fn test_duplicate_keys() {
+        let program = parse(
+            "module m {
+                const ORIGIN = { x: 0, y: 0 }
+                let a = { x: 1, y: 2, x: 3 }
+                let b = { x: 1, ...ORIGIN }
+                let c = { ...ORIGIN, x: 1 }
+            }",
+        );
+        let errors = TypeChecker::new(&program).check();
+
+        assert_eq!(
+            errors,
+            vec![
+                TypeError::DuplicateKey {
+                    key: "x".to_string(),
+                    span: Span::new(3, 39),
+                },
+                TypeError::DuplicateKey {
+                    key: "x".to_string(),
+                    span: Span::new(4, 36),
+                },
+            ]
+        );
+        assert!(errors.iter().all(TypeError::is_warning));
+    }
+
+    #[test]
+    fn test_duplicate_type_parameter() {
+        let program = parse("module m { type Pair<T, T> => { first: T } }");
+        let errors = TypeChecker::new(&program).check();
+
+        assert_eq!(
+            errors,
+            vec![TypeError::DuplicateTypeParameter {
+                name: "T".to_string(),
+                span: Span::new(1, 17),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_match_exhaustiveness() {
+        let program = parse(
+            "module m {
+                type Env => \"dev\" | \"staging\" | \"prod\"
+                const ENV: Env = \"prod\"
+                let port = match ENV { \"dev\" => 3000, \"prod\" => 443 }
+                let host = match ENV { \"dev\" | \"staging\" => \"localhost\", _ => \"example.com\" }
+                let tls = match ENV { \"dev\" => false, _ => true, \"prod\" => true }
+                let flag = match true { true => 1 }
+            }",
+        );
+        let errors = TypeChecker::new(&program).check();
+
+        assert_eq!(
+            errors,
+            vec![
+                TypeError::NonExhaustiveMatch {
+                    missing: vec!["\"staging\"".to_string()],
+                    span: Span::new(4, 28),
+                },
+                TypeError::UnreachablePattern {
+                    span: Span::new(6, 66),
+                },
+                TypeError::NonExhaustiveMatch {
+                    missing: vec!["false".to_string()],
+                    span: Span::new(7, 28),
+                },
+            ]
+        );
+        assert!(errors[1].is_warning());
+    }
+
+    #[test]
+    fn test_literal_types_and_conditions() {
+        let program = parse(
+            "module m {
+                type Env => \"dev\" | \"prod\"
+                const ENV: Env = \"test\"
+                const PORT: Number = if ENV then 1 else \"2\"
+                let name = match PORT { \"a\" => 1, _ => 2 }
+                let mixed = if true then 1 else \"one\"
+                let checked: Number = mixed
+            }",
+        );
+        let errors = TypeChecker::new(&program).check();
+        let env = TypeEnv::new(&program.modules[0]);
+        let env_type = env.expand(&Type::Custom("Env".to_string(), vec![]));
+
+        assert_eq!(
+            errors,
+            vec![
+                TypeError::TypeMismatch {
+                    expected: Type::Custom("Env".to_string(), vec![]),
+                    found: Type::Literal(Literal::String("test".to_string())),
+                    span: Span::new(3, 34),
+                },
+                TypeError::TypeMismatch {
+                    expected: Type::Boolean,
+                    found: Type::Custom("Env".to_string(), vec![]),
+                    span: Span::new(4, 41),
+                },
+                TypeError::TypeMismatch {
+                    expected: Type::Number,
+                    found: Type::String,
+                    span: Span::new(4, 57),
+                },
+                TypeError::TypeMismatch {
+                    expected: Type::Number,
+                    found: Type::Literal(Literal::String("a".to_string())),
+                    span: Span::new(5, 41),
+                },
+                TypeError::TypeMismatch {
+                    expected: Type::Number,
+                    found: Type::Union(vec![Type::Number, Type::String]),
+                    span: Span::new(7, 39),
+                },
+            ]
+        );
+        assert_eq!(env_type.to_string(), "\"dev\" | \"prod\"");
+    }
+
+    #[test]
+    fn test_template_interpolation() {
+        let program = parse(
+            "module m {
+                type Env => \"dev\" | \"prod\"
+                type Server => { host: String }
+                const ENV: Env = \"dev\"
+                const server: Server = { host: \"localhost\" }
+                let url: String = \"http://${ENV}.example.com:${8080}/${true}\"
+                let bad = \"server ${server}\"
+                let count: Number = \"${1}\"
+            }",
+        );
+        let errors = TypeChecker::new(&program).check();
+
+        assert_eq!(
+            errors,
+            vec![
+                TypeError::InvalidInterpolation {
+                    found: Type::Custom("Server".to_string(), vec![]),
+                    span: Span::new(7, 37),
+                },
+                TypeError::TypeMismatch {
+                    expected: Type::Number,
+                    found: Type::String,
+                    span: Span::new(8, 37),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let program = parse(
+            "module m {
+                const RETRIES = 3
+                let delay: Number = RETRIES * 1.5 - -2
+                let url: String = \"http://\" + \"localhost\"
+                let bad = \"port\" + 80
+                let worse = -true
+                let size: String = RETRIES % 2
+            }",
+        );
+        let errors = TypeChecker::new(&program).check();
+
+        assert_eq!(
+            errors,
+            vec![
+                TypeError::InvalidOperands {
+                    op: BinaryOp::Add,
+                    left: Type::String,
+                    right: Type::Number,
+                    span: Span::new(5, 27),
+                },
+                TypeError::TypeMismatch {
+                    expected: Type::Number,
+                    found: Type::Boolean,
+                    span: Span::new(6, 30),
+                },
+                TypeError::TypeMismatch {
+                    expected: Type::String,
+                    found: Type::Number,
+                    span: Span::new(7, 36),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_infers_structural_types() {
+        let program = parse(
+            "module m {
+                type Vector3D => { x: Number, y: Number, z: Number }
+                type Server => { host: String, port: Number = 80 }
+                let point1 = { x: 1, y: 0, z: 0 }
+                let moved = { ...point1, x: 2 }
+                let local = { host: \"localhost\" }
+                let other = { x: 1, y: 0 }
+                let origin: Vector3D = { x: 0, y: 0, z: 0 }
+            }",
+        );
+        let mut checker = TypeChecker::new(&program);
+        assert!(checker.check().is_empty());
+        let env = TypeEnv::new(&program.modules[0]);
+
+        let values: Vec<&Expr> = program.modules[0]
+            .statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::Let { value, .. } => Some(value),
+                _ => None,
+            })
+            .collect();
+        let type_of = |expr: &Expr| checker.type_of(expr.id).unwrap().clone();
+        let matching = |expr: &Expr| {
+            env.matching_definition(&type_of(expr))
+                .map(|def| def.name.as_str())
+        };
+
+        assert_eq!(
+            type_of(values[0]).to_string(),
+            "{ x: Number, y: Number, z: Number }"
+        );
+        assert_eq!(matching(values[0]), Some("Vector3D"));
+        assert_eq!(matching(values[1]), Some("Vector3D"));
+        assert_eq!(matching(values[2]), Some("Server"));
+        assert_eq!(matching(values[3]), None);
+        assert_eq!(
+            type_of(values[4]),
+            Type::Custom("Vector3D".to_string(), vec![])
+        );
+
+        // Nested expressions have types of their own
+        let Expression::Object { entries } = &values[1].kind else {
+            panic!("Expected object literal");
+        };
+        let ObjectEntry::Spread(spread) = &entries[0] else {
+            panic!("Expected spread");
+        };
+        assert_eq!(type_of(spread), type_of(values[0]));
+    }
+
+    #[test]
+    fn test_list_literals() {
+        let program = parse(
+            "module m {
+                type User => { name: String, admin: Boolean = false }
+                type Page<T> => { items: T[], size: Number }
+                let empty: Page<User> = { items: [], size: 0 }
+                let users: User[] = [{ name: \"ada\" }, { name: \"bob\", admin: true }]
+                let ports = [80, 443]
+                let mixed = [1, \"two\"]
+                let nested: Number[][] = [[1], []]
+                let bad: Page<Number> = { items: [1, \"two\"], size: 2 }
+            }",
+        );
+        let mut checker = TypeChecker::new(&program);
+        let errors = checker.check();
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            TypeError::TypeMismatch {
+                expected: Type::Number,
+                found: Type::String,
+                ..
+            }
+        ));
+        assert_eq!(errors[0].span(), Span::new(9, 54));
+
+        let values: Vec<&Expr> = program.modules[0]
+            .statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::Let { value, .. } => Some(value),
+                _ => None,
+            })
+            .collect();
+        let type_of = |expr: &Expr| checker.type_of(expr.id).unwrap().clone();
+        assert_eq!(type_of(values[2]), Type::List(Box::new(Type::Number)));
+        assert_eq!(
+            type_of(values[3]),
+            Type::List(Box::new(Type::Union(vec![Type::Number, Type::String])))
+        );
+    }
+
+    #[test]
+    fn test_assignments() {
+        let program = parse(
+            "module m {
+                type Server => { host: String, port: Number }
+                let server: Server = { host: \"localhost\", port: 80 }
+                let retries = 3
+                server.port = 443
+                server.port = \"443\"
+                server.tls = true
+                retries = \"many\"
+            }",
+        );
+        let errors = TypeChecker::new(&program).check();
+
+        assert_eq!(
+            errors,
+            vec![
+                TypeError::TypeMismatch {
+                    expected: Type::Number,
+                    found: Type::String,
+                    span: Span::new(6, 31),
+                },
+                TypeError::UnknownField {
+                    type_name: "Server".to_string(),
+                    field: "tls".to_string(),
+                    span: Span::new(7, 17),
+                },
+                TypeError::TypeMismatch {
+                    expected: Type::Number,
+                    found: Type::String,
+                    span: Span::new(8, 27),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use std::fmt;
+
+use crate::ast::Type;
+
+/// Represents ways a JSON document fails to match a jlang type. Each error
+/// carries the JSON pointer (RFC 6901) of the value it is about.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    /// A value of the wrong kind, or a literal that is not the one expected
+    TypeMismatch {
+        pointer: String,
+        expected: Type,
+        found: String,
+    },
+
+    /// An object lacks a field that has no default
+    MissingField {
+        pointer: String,
+        field: String,
+        field_type: Type,
+    },
+
+    /// An object has a field its type does not declare
+    UnknownField { pointer: String, field: String },
+}
+
+impl ValidationError {
+    /// Returns the JSON pointer of the value the error is about
+    pub fn pointer(&self) -> &str {
+        match self {
+            ValidationError::TypeMismatch { pointer, .. }
+            | ValidationError::MissingField { pointer, .. }
+            | ValidationError::UnknownField { pointer, .. } => pointer,
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The empty pointer refers to the whole document
+        let location = match self.pointer() {
+            "" => "the document root".to_string(),
+            pointer => format!("'{}'", pointer),
+        };
+
+        match self {
+            ValidationError::TypeMismatch {
+                expected, found, ..
+            } => write!(f, "Expected {} at {}, found {}", expected, location, found),
+            ValidationError::MissingField {
+                field, field_type, ..
+            } => write!(
+                f,
+                "Missing field '{}' of type {} at {}",
+                field, field_type, location
+            ),
+            ValidationError::UnknownField { field, .. } => {
+                write!(f, "Unknown field '{}' at {}", field, location)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_display() {
+        let err = ValidationError::TypeMismatch {
+            pointer: "/users/0/id".to_string(),
+            expected: Type::Number,
+            found: "string \"42\"".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Expected Number at '/users/0/id', found string \"42\""
+        );
+
+        let err = ValidationError::MissingField {
+            pointer: String::new(),
+            field: "name".to_string(),
+            field_type: Type::String,
+        };
+        assert_eq!(
+            err.to_string(),
+            "Missing field 'name' of type String at the document root"
+        );
+    }
+}
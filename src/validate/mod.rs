@@ -0,0 +1,241 @@
+mod error;
+
+pub use error::ValidationError;
+
+use crate::ast::*;
+use crate::json::{JsonValue, pointer_join};
+use crate::typeck::TypeEnv;
+
+/// Checks JSON documents against the type definitions of a module, so jlang
+/// types can serve as schemas for data from elsewhere
+pub struct Validator<'a> {
+    types: TypeEnv<'a>,
+}
+
+impl<'a> Validator<'a> {
+    pub fn new(module: &'a Module) -> Self {
+        Validator {
+            types: TypeEnv::new(module),
+        }
+    }
+
+    /// Returns every way `value` fails to match the non-generic definition
+    /// `def`, or nothing if it matches
+    pub fn validate(&self, def: &TypeDefinition, value: &JsonValue) -> Vec<ValidationError> {
+        self.validate_type(&Type::Custom(def.name.clone(), Vec::new()), value)
+    }
+
+    /// Returns every way `value` fails to match `ty`, or nothing if it matches
+    pub fn validate_type(&self, ty: &Type, value: &JsonValue) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        self.check(ty, value, "", &mut errors);
+        errors
+    }
+
+    fn check(
+        &self,
+        ty: &Type,
+        value: &JsonValue,
+        pointer: &str,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let members = self.types.union_members(ty);
+
+        // A union is matched when any one member is; there is no telling
+        // which member was intended, so the mismatch is reported as a whole
+        if members.len() > 1 {
+            let matched = members
+                .iter()
+                .any(|member| self.validate_at(member, value, pointer).is_empty());
+            if !matched {
+                errors.push(mismatch(ty, value, pointer));
+            }
+            return;
+        }
+
+        let matches = match (&members[0], value) {
+            (Type::Number, JsonValue::Number(_))
+            | (Type::String, JsonValue::String(_))
+            | (Type::Boolean, JsonValue::Bool(_)) => true,
+            (Type::Literal(Literal::Number(a)), JsonValue::Number(b)) => a == b,
+            (Type::Literal(Literal::String(a)), JsonValue::String(b)) => a == b,
+            (Type::Literal(Literal::Boolean(a)), JsonValue::Bool(b)) => a == b,
+            (Type::List(element), JsonValue::Array(elements)) => {
+                for (i, item) in elements.iter().enumerate() {
+                    self.check(
+                        element,
+                        item,
+                        &pointer_join(pointer, &i.to_string()),
+                        errors,
+                    );
+                }
+                true
+            }
+            // Type parameters and unknown names are reported when checking the module
+            (Type::Custom(name, _), _) if self.types.lookup(name).is_none() => true,
+            (record @ (Type::Custom(..) | Type::Object(_)), JsonValue::Object(members)) => {
+                let fields = self.types.record_fields(record).unwrap_or_default();
+                self.check_record(&fields, members, pointer, errors);
+                true
+            }
+            _ => false,
+        };
+
+        if !matches {
+            errors.push(mismatch(ty, value, pointer));
+        }
+    }
+
+    fn validate_at(&self, ty: &Type, value: &JsonValue, pointer: &str) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        self.check(ty, value, pointer, &mut errors);
+        errors
+    }
+
+    fn check_record(
+        &self,
+        fields: &[TypeField],
+        members: &[(String, JsonValue)],
+        pointer: &str,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        for field in fields {
+            match members.iter().find(|(name, _)| *name == field.name) {
+                Some((name, value)) => self.check(
+                    &field.field_type,
+                    value,
+                    &pointer_join(pointer, name),
+                    errors,
+                ),
+                None if field.default.is_some() => {}
+                None => errors.push(ValidationError::MissingField {
+                    pointer: pointer.to_string(),
+                    field: field.name.clone(),
+                    field_type: field.field_type.clone(),
+                }),
+            }
+        }
+
+        for (name, _) in members {
+            if !fields.iter().any(|field| field.name == *name) {
+                errors.push(ValidationError::UnknownField {
+                    pointer: pointer_join(pointer, name),
+                    field: name.clone(),
+                });
+            }
+        }
+    }
+}
+
+fn mismatch(expected: &Type, value: &JsonValue, pointer: &str) -> ValidationError {
+    let found = match value {
+        JsonValue::Bool(b) => format!("boolean {}", b),
+        JsonValue::Number(n) => format!("number {}", n),
+        JsonValue::String(s) => format!("string \"{}\"", s),
+        other => other.type_name().to_string(),
+    };
+
+    ValidationError::TypeMismatch {
+        pointer: pointer.to_string(),
+        expected: expected.clone(),
+        found,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::parse_json;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn validate(type_name: &str, json: &str) -> Vec<String> {
+        let tokens = Lexer::new(
+            "module types {
+                type Role => \"admin\" | \"member\"
+                type Address => { city: String, zip: String | Number }
+                type User => {
+                    id: Number,
+                    name: String,
+                    active: Boolean = true,
+                    role: Role,
+                    address: Address,
+                    tags: String[]
+                }
+                type Team extends Named => { members: User[], lead: { id: Number } }
+                type Named => { name: String }
+            }",
+        )
+        .tokenize()
+        .unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        let module = &program.modules[0];
+        let def = TypeEnv::new(module).lookup(type_name).unwrap();
+
+        Validator::new(module)
+            .validate(def, &parse_json(json).unwrap())
+            .iter()
+            .map(|err| err.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_documents() {
+        let user = r#"{
+            "id": 1,
+            "name": "Ada",
+            "role": "admin",
+            "address": { "city": "London", "zip": 12345 },
+            "tags": ["a", "b"]
+        }"#;
+        assert!(validate("User", user).is_empty());
+
+        let team = format!(
+            r#"{{ "name": "core", "members": [{}], "lead": {{ "id": 1 }} }}"#,
+            user
+        );
+        assert!(validate("Team", &team).is_empty());
+    }
+
+    #[test]
+    fn test_reports_every_mismatch() {
+        let errors = validate(
+            "Team",
+            r#"{
+                "members": [
+                    {
+                        "id": "1",
+                        "name": "Ada",
+                        "active": null,
+                        "role": "owner",
+                        "address": { "city": "London", "zip": true },
+                        "tags": ["a", 2],
+                        "email": "ada@example.com"
+                    }
+                ],
+                "lead": {},
+                "a/b": 1
+            }"#,
+        );
+
+        assert_eq!(
+            errors,
+            vec![
+                "Missing field 'name' of type String at the document root",
+                "Expected Number at '/members/0/id', found string \"1\"",
+                "Expected Boolean at '/members/0/active', found null",
+                "Expected Role at '/members/0/role', found string \"owner\"",
+                "Expected String | Number at '/members/0/address/zip', found boolean true",
+                "Expected String at '/members/0/tags/1', found number 2",
+                "Unknown field 'email' at '/members/0/email'",
+                "Missing field 'id' of type Number at '/lead'",
+                "Unknown field 'a/b' at '/a~1b'",
+            ]
+        );
+
+        assert_eq!(
+            validate("User", "[]"),
+            vec!["Expected User at the document root, found array"]
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// Runs the binary with `source` on stdin
+fn jlang(args: &[&str], source: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jlang"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(source.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn test_exit_statuses() {
+    let cases = [
+        (&["frobnicate"][..], "", 2),
+        (&["tokens", "--format", "xml", "-"][..], "", 2),
+        (&["build", "--target", "js", "-", "--out"][..], "", 2),
+        (&["schema", "-", "--module"][..], "", 2),
+        (&["schema", "-", "--type"][..], "", 2),
+        (&["validate", "-", "--type"][..], "", 2),
+        (&["validate", "--type", "m.T", "-", "--schema"][..], "", 2),
+        (&["tokens", "-"][..], "module m { let s = \"open }", 3),
+        (&["parse", "-"][..], "module m { let }", 4),
+        (
+            &["check", "-"][..],
+            "module m { const x: Number = \"a\" }",
+            5,
+        ),
+        (&["eval", "-"][..], "module m { let x = 1 / 0 }", 6),
+        // Consts run in order like everything else, so this is not a runtime error
+        (
+            &["eval", "-"][..],
+            "module m {\n    const TIMEOUT = DELAY * 2\n    const DELAY = 5\n}",
+            5,
+        ),
+        (&["eval", "-"][..], "module m { const x = 1 }", 0),
+    ];
+    for (args, source, status) in cases {
+        let output = jlang(args, source);
+        assert_eq!(output.status.code(), Some(status), "jlang {:?}", args);
+    }
+}
+
+#[test]
+fn test_json_output() {
+    let output = jlang(&["tokens", "--format", "json", "-"], "module");
+    assert_eq!(
+        stdout(&output).replace(['\n', ' '], ""),
+        r#"[{"kind":"Module","text":"module","line":1,"column":1},{"kind":"EOF","line":1,"column":7}]"#
+    );
+
+    let output = jlang(
+        &["check", "--format", "json", "-"],
+        "module m { const x = y }",
+    );
+    assert_eq!(output.status.code(), Some(5));
+    let diagnostics = stdout(&output);
+    assert!(diagnostics.contains(r#""severity": "error""#));
+    assert!(diagnostics.contains(r#""column": 22"#));
+}
+
+#[test]
+fn test_type_errors_are_reported_once() {
+    let sources = [
+        "module m { const s = \"a\" + 1 }",
+        "module m {\n    const o = { a: 1 }\n    const t = \"v${o}\"\n}",
+    ];
+    for source in sources {
+        let output = jlang(&["check", "--format", "json", "-"], source);
+        assert_eq!(output.status.code(), Some(5));
+        assert_eq!(
+            stdout(&output).matches(r#""severity": "error""#).count(),
+            1,
+            "{}",
+            source
+        );
+    }
+}
+
+#[test]
+fn test_build_from_stdin() {
+    let output = jlang(
+        &["build", "--target", "js", "-"],
+        "module m { const a = 1 }",
+    );
+    assert!(stdout(&output).starts_with("// Generated by jlang from <stdin>. Do not edit."));
+    // No map file is written to link to
+    assert!(!stdout(&output).contains("sourceMappingURL"));
+}
+
+#[test]
+fn test_fmt() {
+    let output = jlang(&["fmt", "-"], "module m { const a=1 }");
+    assert_eq!(stdout(&output), "module m {\n    const a = 1\n}\n");
+
+    let output = jlang(&["fmt", "--check", "-"], "module m { const a=1 }");
+    assert_eq!(output.status.code(), Some(1));
+
+    // Comments are kept
+    let output = jlang(
+        &["fmt", "-"],
+        "module m {\n    // port\n    const a=1 // default\n}\n",
+    );
+    assert_eq!(
+        stdout(&output),
+        "module m {\n    // port\n    const a = 1 // default\n}\n"
+    );
+}
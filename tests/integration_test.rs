@@ -1,3 +1,5 @@
+#![allow(clippy::approx_constant)] // Fixtures use literal constants such as 3.14159
+
 use jlang::*;
 use std::fs;
 
@@ -245,3 +247,122 @@ fn test_unicode_file() {
     assert!(identifiers.contains(&"pi"));
     assert!(identifiers.contains(&"theta"));
 }
+
+#[test]
+fn test_files_resolve() {
+    for file in ["basic.j", "complex.j", "constants.j", "types.j"] {
+        let source = fs::read_to_string(format!("tests/test_files/{}", file)).unwrap();
+        let tokens = Lexer::new(&source).tokenize().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+
+        let (table, errors) = Resolver::new(&program).resolve();
+        assert!(errors.is_empty(), "{}: {:?}", file, errors);
+
+        if file == "complex.j" {
+            let matrix = table.scope(0).lookup(Namespace::Type, "Matrix").unwrap();
+            assert_eq!(table.definition(matrix).kind, DefKind::Type);
+            assert_eq!(table.references().len(), 1); // `elements: Vector3D`
+        }
+    }
+}
+
+#[test]
+fn test_typecheck_file() {
+    let source = fs::read_to_string("tests/test_files/typecheck.j").unwrap();
+    let tokens = Lexer::new(&source).tokenize().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+
+    let errors = TypeChecker::new(&program).check();
+    let rendered: Vec<String> = errors
+        .iter()
+        .map(|err| err.diagnostic().render(&source, "typecheck.j"))
+        .collect();
+
+    assert_eq!(
+        rendered,
+        vec![
+            "error: mismatched types
+  --> typecheck.j:17:30
+   |
+17 |         elements: { x: 1, y: \"0\", z: 0 },
+   |                              ^ expected Number, found String
+",
+            "error: missing field 'modified'
+  --> typecheck.j:18:19
+   |
+18 |         metadata: {
+   |                   ^ expected 'modified: String' for type { created: String, modified: String }
+",
+        ]
+    );
+}
+
+#[test]
+fn test_complex_file_inference() {
+    let source = fs::read_to_string("tests/test_files/complex.j").unwrap();
+    let tokens = Lexer::new(&source).tokenize().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+
+    let mut checker = TypeChecker::new(&program);
+    assert!(checker.check().is_empty());
+    let env = TypeEnv::new(&program.modules[0]);
+
+    for statement in &program.modules[0].statements {
+        if let Statement::Let { value, .. } | Statement::Const { value, .. } = statement {
+            let ty = checker.type_of(value.id).unwrap();
+            let def = env.matching_definition(ty).unwrap();
+            assert_eq!(def.name, "Vector3D");
+        }
+    }
+}
+
+#[test]
+fn test_constants_file_evaluation() {
+    let source = fs::read_to_string("tests/test_files/constants.j").unwrap();
+    let tokens = Lexer::new(&source).tokenize().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+
+    let globals = Interpreter::new(&program).run().unwrap();
+    let names: Vec<&str> = globals
+        .module("math")
+        .unwrap()
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        vec!["PI", "E", "GOLDEN_RATIO", "GREETING", "DEBUG_MODE"]
+    );
+    assert_eq!(globals.get("math", "PI"), Some(&Value::Number(3.14159)));
+    assert_eq!(
+        globals.get("math", "GREETING"),
+        Some(&Value::String("Hello, World!".to_string()))
+    );
+    assert_eq!(globals.get("math", "DEBUG_MODE"), Some(&Value::Bool(true)));
+}
+
+#[test]
+fn test_files_run_the_same_on_the_vm() {
+    let mut compared = 0;
+    for entry in fs::read_dir("tests/test_files").unwrap() {
+        let path = entry.unwrap().path();
+        let source = fs::read_to_string(&path).unwrap();
+        // Files that fail before evaluation have nothing to compare
+        let Ok(tokens) = Lexer::new(&source).tokenize() else {
+            continue;
+        };
+        let Ok(program) = Parser::new(tokens).parse() else {
+            continue;
+        };
+
+        let bytecode = Compiler::new(&program).compile();
+        assert_eq!(
+            Vm::new(&bytecode).run(),
+            Interpreter::new(&program).run(),
+            "{}",
+            path.display()
+        );
+        compared += 1;
+    }
+    assert!(compared >= 7);
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::approx_constant)] // Fixtures use literal constants such as 3.14159
+
 use jlang::*;
 
 #[test]
@@ -20,7 +22,12 @@ fn test_parse_basic_module() {
     assert_eq!(module.statements.len(), 1);
 
     match &module.statements[0] {
-        Statement::Let { name, value } => {
+        Statement::Let {
+            name,
+            ty: None,
+            value,
+            ..
+        } => {
             assert_eq!(name, "x");
             match **value {
                 Expression::NumberLiteral(n) => assert_eq!(n, 42.0),
@@ -66,6 +73,151 @@ fn test_parse_type_definition() {
     }
 }
 
+#[test]
+fn test_parse_generic_type_definition() {
+    let source = r#"
+        module pages {
+            type Page<T> => {
+                items: T[],
+                total: Number
+            }
+            type UserPage => {
+                page: Page<User>
+            }
+        }
+    "#;
+
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+
+    let program = parser.parse().unwrap();
+    let module = &program.modules[0];
+
+    match &module.statements[0] {
+        Statement::TypeDef(type_def) => {
+            assert_eq!(type_def.name, "Page");
+            assert_eq!(type_def.params, vec!["T".to_string()]);
+            assert_eq!(
+                type_def.fields[0].field_type,
+                Type::List(Box::new(Type::Custom("T".to_string(), vec![])))
+            );
+            assert_eq!(type_def.fields[1].field_type, Type::Number);
+        }
+        _ => panic!("Expected type definition"),
+    }
+
+    match &module.statements[1] {
+        Statement::TypeDef(type_def) => {
+            assert!(type_def.params.is_empty());
+            assert_eq!(
+                type_def.fields[0].field_type,
+                Type::Custom(
+                    "Page".to_string(),
+                    vec![Type::Custom("User".to_string(), vec![])]
+                )
+            );
+        }
+        _ => panic!("Expected type definition"),
+    }
+}
+
+#[test]
+fn test_parse_type_extension() {
+    let source = r#"
+        module shapes {
+            type Point3D => Point & { z: Number }
+            type Labeled extends Point3D, Tagged => { label: String }
+            type Origin => Point
+        }
+    "#;
+
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+
+    let program = parser.parse().unwrap();
+    let module = &program.modules[0];
+
+    match &module.statements[0] {
+        Statement::TypeDef(type_def) => {
+            assert_eq!(type_def.name, "Point3D");
+            assert_eq!(
+                type_def.extends,
+                vec![Type::Custom("Point".to_string(), vec![])]
+            );
+            assert_eq!(type_def.fields.len(), 1);
+            assert_eq!(type_def.fields[0].name, "z");
+        }
+        _ => panic!("Expected type definition"),
+    }
+
+    match &module.statements[1] {
+        Statement::TypeDef(type_def) => {
+            assert_eq!(
+                type_def.extends,
+                vec![
+                    Type::Custom("Point3D".to_string(), vec![]),
+                    Type::Custom("Tagged".to_string(), vec![])
+                ]
+            );
+            assert_eq!(type_def.fields[0].name, "label");
+        }
+        _ => panic!("Expected type definition"),
+    }
+
+    // A lone named type is an alias rather than a base
+    match &module.statements[2] {
+        Statement::TypeDef(type_def) => {
+            assert!(type_def.extends.is_empty());
+            assert_eq!(
+                type_def.alias,
+                Some(Type::Custom("Point".to_string(), vec![]))
+            );
+        }
+        _ => panic!("Expected type definition"),
+    }
+}
+
+#[test]
+fn test_parse_field_defaults() {
+    let source = r#"
+        module config {
+            type Server => {
+                host: String,
+                port: Number = 8080
+            }
+        }
+    "#;
+
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+
+    let program = parser.parse().unwrap();
+
+    match &program.modules[0].statements[0] {
+        Statement::TypeDef(type_def) => {
+            assert!(type_def.fields[0].default.is_none());
+            match type_def.fields[1].default.as_deref() {
+                Some(Expression::NumberLiteral(n)) => assert_eq!(*n, 8080.0),
+                other => panic!("Expected number default, got {:?}", other),
+            }
+        }
+        _ => panic!("Expected type definition"),
+    }
+
+    // Inline object types cannot declare defaults
+    let source = "module config { type Server => { limits: { max: Number = 1 } } }";
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    assert!(matches!(
+        parser.parse(),
+        Err(ParseError::InvalidExpression { .. })
+    ));
+}
+
 #[test]
 fn test_parse_const_declaration() {
     let source = r#"
@@ -86,7 +238,12 @@ fn test_parse_const_declaration() {
     assert_eq!(module.statements.len(), 3);
 
     match &module.statements[0] {
-        Statement::Const { name, value } => {
+        Statement::Const {
+            name,
+            ty: None,
+            value,
+            ..
+        } => {
             assert_eq!(name, "PI");
             match **value {
                 Expression::NumberLiteral(n) => assert!((n - 3.14159).abs() < 1e-5),
@@ -97,7 +254,12 @@ fn test_parse_const_declaration() {
     }
 
     match &module.statements[1] {
-        Statement::Const { name, value } => {
+        Statement::Const {
+            name,
+            ty: None,
+            value,
+            ..
+        } => {
             assert_eq!(name, "GREETING");
             match **value {
                 Expression::StringLiteral(ref s) => assert_eq!(s, "Hello"),
@@ -108,10 +270,15 @@ fn test_parse_const_declaration() {
     }
 
     match &module.statements[2] {
-        Statement::Const { name, value } => {
+        Statement::Const {
+            name,
+            ty: None,
+            value,
+            ..
+        } => {
             assert_eq!(name, "ENABLED");
             match **value {
-                Expression::BooleanLiteral(b) => assert_eq!(b, true),
+                Expression::BooleanLiteral(b) => assert!(b),
                 _ => panic!("Expected boolean literal"),
             }
         }
@@ -138,20 +305,35 @@ fn test_parse_object_literal() {
     let module = &program.modules[0];
 
     match &module.statements[0] {
-        Statement::Let { name, value } => {
+        Statement::Let {
+            name,
+            ty: None,
+            value,
+            ..
+        } => {
             assert_eq!(name, "point");
             match **value {
-                Expression::Object { ref fields } => {
-                    assert_eq!(fields.len(), 2);
-                    assert_eq!(fields[0].0, "x");
-                    match &fields[0].1 {
-                        Expression::NumberLiteral(n) => assert_eq!(*n, 10.0),
-                        _ => panic!("Expected number literal"),
+                Expression::Object { ref entries } => {
+                    assert_eq!(entries.len(), 2);
+                    match &entries[0] {
+                        ObjectEntry::Field { name, value, .. } => {
+                            assert_eq!(name, "x");
+                            match value.kind {
+                                Expression::NumberLiteral(n) => assert_eq!(n, 10.0),
+                                _ => panic!("Expected number literal"),
+                            }
+                        }
+                        _ => panic!("Expected field"),
                     }
-                    assert_eq!(fields[1].0, "y");
-                    match &fields[1].1 {
-                        Expression::NumberLiteral(n) => assert_eq!(*n, 20.0),
-                        _ => panic!("Expected number literal"),
+                    match &entries[1] {
+                        ObjectEntry::Field { name, value, .. } => {
+                            assert_eq!(name, "y");
+                            match value.kind {
+                                Expression::NumberLiteral(n) => assert_eq!(n, 20.0),
+                                _ => panic!("Expected number literal"),
+                            }
+                        }
+                        _ => panic!("Expected field"),
                     }
                 }
                 _ => panic!("Expected object literal"),
@@ -161,6 +343,378 @@ fn test_parse_object_literal() {
     }
 }
 
+#[test]
+fn test_parse_annotated_bindings() {
+    let source = r#"
+        module annotated {
+            let origin: Vector3D = { x: 0, y: 0, z: 0 }
+            const LIMIT: Number = 10
+        }
+    "#;
+
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+
+    let program = parser.parse().unwrap();
+    let module = &program.modules[0];
+
+    match &module.statements[0] {
+        Statement::Let {
+            name,
+            ty,
+            value,
+            span,
+        } => {
+            assert_eq!(name, "origin");
+            assert_eq!(*ty, Some(Type::Custom("Vector3D".to_string(), vec![])));
+            assert!(matches!(value.kind, Expression::Object { .. }));
+            assert_eq!(value.span, Span::new(3, 36));
+            assert_eq!(*span, Span::new(3, 17));
+        }
+        _ => panic!("Expected let statement"),
+    }
+
+    match &module.statements[1] {
+        Statement::Const { name, ty, .. } => {
+            assert_eq!(name, "LIMIT");
+            assert_eq!(*ty, Some(Type::Number));
+        }
+        _ => panic!("Expected const statement"),
+    }
+}
+
+#[test]
+fn test_parse_spread_and_shorthand() {
+    let source = r#"
+        module objects {
+            let moved = { ...ORIGIN, z: 5, x }
+        }
+    "#;
+
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+
+    let program = parser.parse().unwrap();
+
+    match &program.modules[0].statements[0] {
+        Statement::Let { value, .. } => match &value.kind {
+            Expression::Object { entries } => {
+                assert_eq!(entries.len(), 3);
+                match &entries[0] {
+                    ObjectEntry::Spread(expr) => {
+                        assert_eq!(expr.kind, Expression::Identifier("ORIGIN".to_string()))
+                    }
+                    _ => panic!("Expected spread"),
+                }
+                match &entries[2] {
+                    ObjectEntry::Field { name, value, .. } => {
+                        assert_eq!(name, "x");
+                        assert_eq!(value.kind, Expression::Identifier("x".to_string()));
+                    }
+                    _ => panic!("Expected shorthand field"),
+                }
+            }
+            _ => panic!("Expected object literal"),
+        },
+        _ => panic!("Expected let statement"),
+    }
+}
+
+#[test]
+fn test_parse_conditionals_and_match() {
+    let source = r#"
+        module config {
+            type Env => "dev" | "staging" | "prod"
+            let debug = if verbose then true else false
+            let port = match ENV { "dev" | "staging" => 3000, _ => 443 }
+        }
+    "#;
+
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+
+    let program = parser.parse().unwrap();
+    let statements = &program.modules[0].statements;
+
+    match &statements[0] {
+        Statement::TypeDef(def) => {
+            assert!(def.fields.is_empty());
+            assert_eq!(
+                def.alias,
+                Some(Type::Union(vec![
+                    Type::Literal(Literal::String("dev".to_string())),
+                    Type::Literal(Literal::String("staging".to_string())),
+                    Type::Literal(Literal::String("prod".to_string())),
+                ]))
+            );
+        }
+        _ => panic!("Expected type definition"),
+    }
+
+    match &statements[1] {
+        Statement::Let { value, .. } => match &value.kind {
+            Expression::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                assert_eq!(
+                    condition.kind,
+                    Expression::Identifier("verbose".to_string())
+                );
+                assert_eq!(then_branch.kind, Expression::BooleanLiteral(true));
+                assert_eq!(else_branch.kind, Expression::BooleanLiteral(false));
+            }
+            _ => panic!("Expected if expression"),
+        },
+        _ => panic!("Expected let statement"),
+    }
+
+    match &statements[2] {
+        Statement::Let { value, .. } => match &value.kind {
+            Expression::Match { scrutinee, arms } => {
+                assert_eq!(scrutinee.kind, Expression::Identifier("ENV".to_string()));
+                assert_eq!(arms.len(), 2);
+                assert_eq!(
+                    arms[0].patterns,
+                    vec![
+                        Pattern::Literal(Literal::String("dev".to_string())),
+                        Pattern::Literal(Literal::String("staging".to_string())),
+                    ]
+                );
+                assert_eq!(arms[0].body.kind, Expression::NumberLiteral(3000.0));
+                assert!(arms[1].patterns[0].is_wildcard());
+            }
+            _ => panic!("Expected match expression"),
+        },
+        _ => panic!("Expected let statement"),
+    }
+}
+
+#[test]
+fn test_parse_template_string() {
+    let source = r#"
+        module urls {
+            let api = "http://${host}:${port}/api"
+        }
+    "#;
+
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+
+    let program = parser.parse().unwrap();
+
+    match &program.modules[0].statements[0] {
+        Statement::Let { value, .. } => match &value.kind {
+            Expression::Template(parts) => {
+                assert_eq!(parts.len(), 5);
+                assert_eq!(parts[0], Part::Text("http://".to_string()));
+                match &parts[1] {
+                    Part::Expr(expr) => {
+                        assert_eq!(expr.kind, Expression::Identifier("host".to_string()));
+                        assert_eq!((expr.span.line, expr.span.column), (3, 33));
+                    }
+                    _ => panic!("Expected interpolated expression"),
+                }
+                assert_eq!(parts[4], Part::Text("/api".to_string()));
+            }
+            _ => panic!("Expected template string"),
+        },
+        _ => panic!("Expected let statement"),
+    }
+
+    let mut lexer = Lexer::new(r#"module m { let s = "${a b}" }"#);
+    let tokens = lexer.tokenize().unwrap();
+    assert!(Parser::new(tokens).parse().is_err());
+}
+
+#[test]
+fn test_parse_list_literal() {
+    let source = r#"
+        module m {
+            let ports = [80, 443,]
+            let grid = [[1] [2, 3]]
+            let empty = []
+        }
+    "#;
+
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+
+    let program = parser.parse().unwrap();
+    let values: Vec<&Expression> = program.modules[0]
+        .statements
+        .iter()
+        .map(|statement| match statement {
+            Statement::Let { value, .. } => &value.kind,
+            _ => panic!("Expected let statement"),
+        })
+        .collect();
+
+    match values[0] {
+        Expression::List(elements) => {
+            assert_eq!(elements.len(), 2);
+            assert_eq!(elements[1].kind, Expression::NumberLiteral(443.0));
+            assert_eq!((elements[1].span.line, elements[1].span.column), (3, 30));
+        }
+        _ => panic!("Expected list literal"),
+    }
+    match values[1] {
+        Expression::List(rows) => {
+            assert_eq!(rows.len(), 2);
+            assert!(matches!(&rows[1].kind, Expression::List(row) if row.len() == 2));
+        }
+        _ => panic!("Expected list literal"),
+    }
+    assert_eq!(values[2], &Expression::List(vec![]));
+
+    let mut lexer = Lexer::new("module m { let s = [1, 2 }");
+    let tokens = lexer.tokenize().unwrap();
+    assert!(Parser::new(tokens).parse().is_err());
+}
+
+#[test]
+fn test_parse_assignment() {
+    let source = r#"
+        module config {
+            let server = { port: 80 }
+            server.tls.port = 443
+        }
+    "#;
+
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+
+    let program = parser.parse().unwrap();
+
+    match &program.modules[0].statements[1] {
+        Statement::Assign {
+            target,
+            value,
+            span,
+        } => {
+            assert_eq!(target.name, "server");
+            assert_eq!(target.fields, vec!["tls", "port"]);
+            assert_eq!(target.to_string(), "server.tls.port");
+            assert_eq!(value.kind, Expression::NumberLiteral(443.0));
+            assert_eq!((span.line, span.column), (4, 13));
+        }
+        _ => panic!("Expected assignment"),
+    }
+
+    let mut lexer = Lexer::new("module m { server. = 1 }");
+    let tokens = lexer.tokenize().unwrap();
+    assert!(Parser::new(tokens).parse().is_err());
+}
+
+#[test]
+fn test_parse_arithmetic() {
+    let source = "module m { const X = -1 + 2 * (3 - 4) % 5 }";
+
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+
+    let program = parser.parse().unwrap();
+
+    let Statement::Const { value, .. } = &program.modules[0].statements[0] else {
+        panic!("Expected const declaration");
+    };
+    let Expression::Binary {
+        op: BinaryOp::Add,
+        left,
+        right,
+    } = &value.kind
+    else {
+        panic!("Expected addition, found {:?}", value.kind);
+    };
+    assert!(matches!(
+        left.kind,
+        Expression::Unary {
+            op: UnaryOp::Negate,
+            ..
+        }
+    ));
+    assert_eq!((value.span.line, value.span.column), (1, 22));
+
+    // Multiplication and remainder bind tighter and associate to the left
+    let Expression::Binary {
+        op: BinaryOp::Remainder,
+        left: product,
+        right: divisor,
+    } = &right.kind
+    else {
+        panic!("Expected remainder, found {:?}", right.kind);
+    };
+    assert_eq!(divisor.kind, Expression::NumberLiteral(5.0));
+    let Expression::Binary {
+        op: BinaryOp::Multiply,
+        right: group,
+        ..
+    } = &product.kind
+    else {
+        panic!("Expected multiplication, found {:?}", product.kind);
+    };
+    assert!(matches!(
+        group.kind,
+        Expression::Binary {
+            op: BinaryOp::Subtract,
+            ..
+        }
+    ));
+    assert_eq!((right.span.line, right.span.column), (1, 27));
+
+    let mut lexer = Lexer::new("module m { const X = (1 + 2 }");
+    let tokens = lexer.tokenize().unwrap();
+    assert!(Parser::new(tokens).parse().is_err());
+}
+
+#[test]
+fn test_parse_doc_comments() {
+    let source = r#"
+        module users {
+            /// A registered user
+            /// of the service
+            type User => {
+                /// Unique across all users
+                id: Number,
+                // An ordinary comment
+                name: String
+            }
+
+            /// Dropped, since bindings have no docs
+            let x = 1
+        }
+    "#;
+
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+
+    let program = parser.parse().unwrap();
+
+    let Statement::TypeDef(def) = &program.modules[0].statements[0] else {
+        panic!("Expected type definition");
+    };
+    assert_eq!(
+        def.docs.as_deref(),
+        Some("A registered user\nof the service")
+    );
+    assert_eq!(
+        def.fields[0].docs.as_deref(),
+        Some("Unique across all users")
+    );
+    assert_eq!(def.fields[1].docs, None);
+    assert_eq!((def.span.line, def.span.column), (5, 18));
+    assert_eq!(program.modules[0].statements.len(), 2);
+}
+
 #[test]
 fn test_parse_errors() {
     // Test case 1: Missing module name
@@ -169,9 +723,7 @@ fn test_parse_errors() {
     let tokens = lexer.tokenize().unwrap();
     let mut parser = Parser::new(tokens);
     match parser.parse() {
-        Err(ParseError::UnexpectedToken {
-            expected, found, ..
-        }) => {
+        Err(ParseError::UnexpectedToken { expected, .. }) => {
             assert!(expected.contains("identifier"));
         }
         other => panic!("Expected UnexpectedToken error, got {:?}", other),
@@ -183,9 +735,7 @@ fn test_parse_errors() {
     let tokens = lexer.tokenize().unwrap();
     let mut parser = Parser::new(tokens);
     match parser.parse() {
-        Err(ParseError::UnexpectedToken {
-            expected, found, ..
-        }) => {
+        Err(ParseError::UnexpectedToken { expected, .. }) => {
             assert!(expected.contains("LeftBrace") || expected.contains("{"));
         }
         other => panic!("Expected UnexpectedToken error, got {:?}", other),
@@ -197,9 +747,7 @@ fn test_parse_errors() {
     let tokens = lexer.tokenize().unwrap();
     let mut parser = Parser::new(tokens);
     match parser.parse() {
-        Err(ParseError::UnexpectedToken {
-            expected, found, ..
-        }) => {
+        Err(ParseError::UnexpectedToken { expected, .. }) => {
             assert!(expected.contains("RightBrace") || expected.contains("}"));
         }
         other => panic!("Expected UnexpectedToken error, got {:?}", other),
@@ -210,7 +758,7 @@ fn test_parse_errors() {
     let mut lexer = Lexer::new(source);
     match lexer.tokenize() {
         Err(_) => (), // Lexer should catch the invalid character
-        Ok(tokens) => panic!("Expected lexer error for invalid character '@'"),
+        Ok(_) => panic!("Expected lexer error for invalid character '@'"),
     }
 
     // Test case 5: Missing equals in let statement
@@ -219,9 +767,7 @@ fn test_parse_errors() {
     let tokens = lexer.tokenize().unwrap();
     let mut parser = Parser::new(tokens);
     match parser.parse() {
-        Err(ParseError::UnexpectedToken {
-            expected, found, ..
-        }) => {
+        Err(ParseError::UnexpectedToken { expected, .. }) => {
             assert!(expected.contains("Equals") || expected.contains("="));
         }
         other => panic!("Expected UnexpectedToken error, got {:?}", other),
@@ -233,9 +779,7 @@ fn test_parse_errors() {
     let tokens = lexer.tokenize().unwrap();
     let mut parser = Parser::new(tokens);
     match parser.parse() {
-        Err(ParseError::UnexpectedToken {
-            expected, found, ..
-        }) => {
+        Err(ParseError::UnexpectedToken { expected, .. }) => {
             assert!(expected.contains("LeftBrace") || expected.contains("{"));
         }
         other => panic!("Expected UnexpectedToken error, got {:?}", other),
@@ -247,9 +791,7 @@ fn test_parse_errors() {
     let tokens = lexer.tokenize().unwrap();
     let mut parser = Parser::new(tokens);
     match parser.parse() {
-        Err(ParseError::UnexpectedToken {
-            expected, found, ..
-        }) => {
+        Err(ParseError::UnexpectedToken { expected, .. }) => {
             assert!(expected.contains("RightBrace") || expected.contains("}"));
         }
         other => panic!("Expected UnexpectedToken error, got {:?}", other),
@@ -261,9 +803,7 @@ fn test_parse_errors() {
     let tokens = lexer.tokenize().unwrap();
     let mut parser = Parser::new(tokens);
     match parser.parse() {
-        Err(ParseError::UnexpectedToken {
-            expected, found, ..
-        }) => {
+        Err(ParseError::UnexpectedToken { expected, .. }) => {
             assert!(expected.contains("identifier"));
         }
         other => panic!("Expected UnexpectedToken error, got {:?}", other),